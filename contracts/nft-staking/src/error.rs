@@ -9,6 +9,9 @@ pub enum ContractError {
     #[error("Unauthorized address")]
     Unauthorized {},
 
+    #[error("only the contract owner is allowed, granted address is not allowed")]
+    OwnerOnly {},
+
     #[error("cycle length is invalid, at least {min_cycle_length} seconds > request {cycle_length_in_seconds} seconds")]
     CycleLengthInvalid {
         min_cycle_length: u64,
@@ -21,6 +24,12 @@ pub enum ContractError {
         period_length_in_cycles: u64,
     },
 
+    #[error("period length is too long, at most {max_period_length} cycles < request {period_length_in_cycles} cycles")]
+    PeriodLengthTooLong {
+        max_period_length: u64,
+        period_length_in_cycles: u64,
+    },
+
     #[error("cycle cannot be zero")]
     CycleNotZero {},
 
@@ -39,6 +48,9 @@ pub enum ContractError {
     #[error("disabled")]
     Disabled {},
 
+    #[error("not disabled, run disable() first")]
+    NotDisabled {},
+
     #[error("cannot enable, disable state is {disable}")]
     CannotEnable {
         disable: bool,
@@ -50,12 +62,20 @@ pub enum ContractError {
         requester: String,
     },
 
+    #[error("rewards token contract cannot be the nft staking contract")]
+    RewardsTokenIsStakingContract {},
+
     #[error("invalid cw721 contract, whitelisted contract is {white_listed_contract}, but request is {requester}")]
     InvalidWhitelistedContract {
         white_listed_contract: String,
         requester: String,
     },
 
+    #[error("whitelisted nft contract cannot be replaced, token id {token_id} is staked without the nft contract")]
+    WhitelistReplaceBlocked {
+        token_id: String,
+    },
+
     #[error("token id is already staked")]
     AlreadyStaked {},
 
@@ -77,8 +97,14 @@ pub enum ContractError {
         nft_owner: String,
     },
 
-    #[error("invalid claim of requester")]
-    InvalidClaim {},
+    #[error("no claimable periods, rewards are already claimed up to now")]
+    NoClaimablePeriods {},
+
+    #[error("next claim is not initialized, the token is not staked properly")]
+    ClaimNotInitialized {},
+
+    #[error("nothing is claimable yet, the token is staked in the current period")]
+    NothingClaimableYet {},
 
     #[error("next claim is empty")]
     EmptyNextClaim {},
@@ -95,12 +121,33 @@ pub enum ContractError {
     #[error("have not history")]
     HaveNotHistory {},
 
+    #[error("config is changed, expected config version {expected} but current is {current}")]
+    ConfigChanged {
+        expected: u64,
+        current: u64,
+    },
+
     #[error("invalid rewards schedule")]
     InvalidRewardsSchedule {},
 
+    #[error("no rewards schedule yet, rewards are zero")]
+    NoRewardsSchedule {},
+
+    #[error("rewards per period {rewards_per_period} is not divisible by period length {period_length_in_cycles} cycles")]
+    RewardsPerPeriodNotDivisible {
+        rewards_per_period: u128,
+        period_length_in_cycles: u64,
+    },
+
     #[error("rewards pool is empty")]
     EmptyRewardsPool {},
 
+    #[error("rewards pool balance {balance} is below the minimum {minimum} to stake")]
+    PoolBelowStakingMinimum {
+        balance: u128,
+        minimum: u128,
+    },
+
     #[error("request claimable periods value for rewards is invalid, request periods: {periods} is bigger than max period: {max_compute_period}")]
     InvalidMaxPeriod {
         periods: u64,
@@ -110,11 +157,20 @@ pub enum ContractError {
     #[error("invalid set max_compute_period, need bigger than zero")]
     InvalidSetMaxPeriod {},
 
+    #[error("max_compute_period {max_compute_period} exceeds the max allowed compute period {max_allowed_compute_period}")]
+    MaxComputePeriodTooLarge {
+        max_compute_period: u64,
+        max_allowed_compute_period: u64,
+    },
+
     #[error("already granted address {address}")]
     AlreadyGranted {
         address: String,
     },
 
+    #[error("the contract owner cannot be granted or revoked")]
+    CannotGrantSelf {},
+
     #[error("invalid granted address {address}")]
     InvalidGrantedAddress {
         address: String,
@@ -125,4 +181,162 @@ pub enum ContractError {
 
     #[error("request token id is under unbonding, or unbonded token id should execute unstake not claim")]
     TokenIdIsUnbonding {},
+
+    #[error("token id is burned")]
+    TokenBurned {},
+
+    #[error("token id still exists in the nft contract, owner is {owner}")]
+    TokenNotBurned {
+        owner: String,
+    },
+
+    #[error("{forwarder} is not allowed to stake on behalf of other address")]
+    UnauthorizedForwarder {
+        forwarder: String,
+    },
+
+    #[error("rewards cannot be claimed before the cliff period {cliff}")]
+    BeforeCliff {
+        cliff: u64,
+    },
+
+    #[error("claim recipient address is not approved by the staker")]
+    RecipientNotApproved {},
+
+    #[error("forfeit amount {forfeit_amount} does not match the forfeited rewards {forfeited_rewards}")]
+    ForfeitAmountMismatch {
+        forfeit_amount: u128,
+        forfeited_rewards: u128,
+    },
+
+    #[error("rewards token takes a transfer fee, rewards pool must be added by add_rewards_pool_from")]
+    FeeOnTransferDepositRequired {},
+
+    #[error("rewards pool deposit is not pending")]
+    NoPendingRewardsDeposit {},
+
+    #[error("rewards emission is ended at period {emission_end_period}")]
+    EmissionEnded {
+        emission_end_period: u64,
+    },
+
+    #[error("snapshot index of the next claim is out of the staker history")]
+    CorruptedNextClaim {},
+
+    #[error("invalid set min_unstake_cycles, need bigger than zero")]
+    InvalidMinUnstakeCycles {},
+
+    #[error("too many snapshots in the staker history")]
+    TooManySnapshots {},
+
+    #[error("rewards token contract {rewards_token_contract} is not available as cw20, {reason}")]
+    RewardsTokenContractUnavailable {
+        rewards_token_contract: String,
+        reason: String,
+    },
+
+    #[error("whitelisted nft contract {white_listed_nft_contract} is not available as cw721, {reason}")]
+    WhitelistedNftContractUnavailable {
+        white_listed_nft_contract: String,
+        reason: String,
+    },
+
+    #[error("reward per period is required in the shared per period emission mode")]
+    EmptyRewardPerPeriod {},
+
+    #[error("{setting} is not set, the default {default} is used")]
+    DefaultedSetting {
+        setting: String,
+        default: String,
+    },
+
+    #[error("booster contract {booster_contract} must be different from the whitelisted nft contract")]
+    InvalidBoosterContract {
+        booster_contract: String,
+    },
+
+    #[error("booster contract cannot be changed while booster nfts are staked")]
+    BoosterContractInUse {},
+
+    #[error("rewards must be claimed before unstaking, unstake with claim_on_unstake false to claim them later")]
+    ClaimBeforeUnstake {},
+
+    #[error("withdraw timelock is set, request the withdrawal and execute it after the timelock")]
+    WithdrawTimelockEnabled {},
+
+    #[error("withdraw timelock is not set, withdraw the rewards pool directly")]
+    WithdrawTimelockNotSet {},
+
+    #[error("withdraw timelock cannot be decreased or removed, current withdraw timelock is {current} seconds")]
+    WithdrawTimelockDecreased {
+        current: u64,
+    },
+
+    #[error("withdrawal is already pending, ready at {ready_at}")]
+    WithdrawalAlreadyPending {
+        ready_at: u64,
+    },
+
+    #[error("no pending withdrawal")]
+    NoPendingWithdrawal {},
+
+    #[error("withdraw timelock has not ended, ready at {ready_at}")]
+    WithdrawTimelockNotEnded {
+        ready_at: u64,
+    },
+
+    #[error("rewards deposit is not received, the rewards pool {total_rewards_pool} with reported amount {reported_amount} is more than the rewards token balance {balance}")]
+    RewardsDepositNotReceived {
+        reported_amount: u128,
+        total_rewards_pool: u128,
+        balance: u128,
+    },
+
+    #[error("token id is blocked")]
+    TokenBlocked {},
+
+    #[error("granted address is not allowed while the contract is disabled")]
+    GrantSuspended {},
+
+    #[error("cutover period {cutover_period} must be after the current period {current_period}")]
+    InvalidCutoverPeriod {
+        cutover_period: u64,
+        current_period: u64,
+    },
+
+    #[error("rewards token cutover is already reached at period {cutover_period}")]
+    RewardsTokenCutoverReached {
+        cutover_period: u64,
+    },
+
+    #[error("secondary rewards token must differ from the rewards token and the nft staking contract")]
+    InvalidSecondaryRewardsToken {},
+
+    #[error("insufficient secondary rewards pool, balance {balance} is less than {amount}")]
+    InsufficientSecondaryRewardsPool {
+        balance: u128,
+        amount: u128,
+    },
+
+    #[error("token ids are empty")]
+    EmptyTokenIds {},
+
+    #[error("too many token ids, max is {max}")]
+    TooManyTokenIds {
+        max: u64,
+    },
+
+    #[error("pool constrained emission is not set")]
+    PoolConstrainedEmissionDisabled {},
+
+    #[error("emission ledger is processed until period {processed_period} before the current period {current_period}, process it by process_emission_ledger")]
+    EmissionLedgerNotSettled {
+        processed_period: u64,
+        current_period: u64,
+    },
+
+    #[error("unknown reply id {id}")]
+    UnknownReplyId {
+        id: u64,
+    },
 }
\ No newline at end of file
@@ -1,57 +1,106 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, CosmosMsg, StdError};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, CosmosMsg, StdError, StdResult, SubMsg, Reply, Order, Addr, to_binary};
 use cw2::{set_contract_version, get_contract_version};
 use cw20::{Cw20ReceiveMsg, Expiration};
 use cw721::Cw721ReceiveMsg;
+use cw_storage_plus::Bound;
 
 use crate::error::{ContractError};
-use crate::handler::{execute_token_contract_transfer, get_cycle, get_period, update_histories, IS_STAKED, check_start_timestamp, check_disable, check_contract_owner, execute_transfer_nft_unstake, compute_rewards, staker_tokenid_key, query_rewards_token_balance, is_valid_cycle_length, is_valid_period_length, manage_number_nfts, contract_info, check_contract_owner_only, check_unbonding_end, check_rewards_pool_balance, CHECK_REWARDS_POOL_AIM_EMPTY, CHECK_REWARDS_POOL_AIM_BOTH, CHECK_REWARDS_POOL_AIM_INSUFFICIENT};
+use crate::handler::{execute_token_contract_transfer, get_cycle, get_period, update_histories, IS_STAKED, check_start_timestamp, check_disable, check_contract_owner, execute_transfer_nft_unstake, compute_rewards, staker_tokenid_key, query_rewards_token_balance, is_valid_cycle_length, is_valid_period_length, manage_number_nfts, manage_number_boosters, contract_info, check_contract_owner_only, check_unbonding_end, check_rewards_pool_balance, CHECK_REWARDS_POOL_AIM_EMPTY, CHECK_REWARDS_POOL_AIM_BOTH, CHECK_REWARDS_POOL_AIM_INSUFFICIENT, compute_remain_rewards, query_nft_owner, withdraw_recipient, stake_owner, check_claim_cliff, check_auto_disable, claim_recipient, execute_token_contract_transfer_from, increase_total_paid_out, get_current_period, settle_deferred_rewards, validate_token_contracts, get_min_cycle_length, MIN_CYCLE_LENGTH, check_whitelist_replaceable, unstake_phase_event, UNSTAKE_PHASE_START_UNBONDING, UNSTAKE_PHASE_FINALIZED, increase_config_version, check_config_version, check_withdraw_timelock, record_claim_receipt, is_orphaned_claim, advance_emission_ledger, settle_emission_ledger, update_emission_budget, max_token_multiplier_bps, execute_transfer_nft_stake, execute_rewards_transfer, increase_counter, DEFAULT_TOKEN_MULTIPLIER_BPS, query_is_reward_minter, get_unbond_end_claim_period, check_min_pool_balance_to_stake, is_not_found_error, save_token_info};
 use crate::msg::{ExecuteMsg, InstantiateMsg, SetConfigMsg, MigrateMsg};
-use crate::state::{Config, CONFIG_STATE, START_TIMESTAMP, REWARDS_SCHEDULE, TOTAL_REWARDS_POOL, DISABLE, NEXT_CLAIMS, NextClaim, TOKEN_INFOS, TokenInfo, STAKER_HISTORIES, Claim, NUMBER_OF_STAKED_NFTS, MAX_COMPUTE_PERIOD, GRANTS, Grant, UNBONDING_DURATION, UNBONDING, BONDED};
+use crate::state::{Config, CONFIG_STATE, START_TIMESTAMP, REWARDS_SCHEDULE, TOTAL_REWARDS_POOL, DISABLE, NEXT_CLAIMS, NextClaim, TOKEN_INFOS, TokenInfo, STAKER_HISTORIES, Claim, NUMBER_OF_STAKED_NFTS, MAX_COMPUTE_PERIOD, GRANTS, Grant, UNBONDING_DURATION, UNBONDING, UNBONDED, BONDED, MIN_TOKENS_TO_EARN, STAKE_FORWARDERS, CLAIM_CLIFF_PERIOD, AUTO_DISABLE_THRESHOLD, TOKEN_RECIPIENTS, TokenRecipient, REQUIRE_APPROVED_RECIPIENT, APPROVED_RECIPIENTS, ALLOW_PRESTAKE, FEE_ON_TRANSFER_TOKEN, PENDING_REWARDS_DEPOSIT, PendingRewardsDeposit, MAX_REWARD_PER_CLAIM_PER_TOKEN, EMISSION_END_PERIOD, TOTAL_PAID_OUT, VALIDATE_CONTRACTS, EmissionMode, EMISSION_MODE, REWARD_PER_PERIOD, STAKED_COUNT_HISTORY, MIN_CYCLE_LENGTH_IN_SECONDS, MIN_UNSTAKE_CYCLES, DEFAULT_MIN_UNSTAKE_CYCLES, DEFAULT_MAX_COMPUTE_PERIOD, DEFAULT_UNBONDING_DURATION, ALLOW_PUBLIC_FUNDING, BOOSTER_TOKENS, REQUIRE_CLAIM_BEFORE_UNSTAKE, CONFIG_VERSION, WITHDRAW_TIMELOCK_SECONDS, PENDING_WITHDRAWAL, PendingWithdrawal, BLOCKED_TOKENS, ClaimReceipt, MAX_ALLOWED_COMPUTE_PERIOD, RECOMMENDED_MAX_COMPUTE_CYCLES, POOL_CONSTRAINED_EMISSION, EMISSION_LEDGER, EmissionLedger, REWARDS_FOLLOW_NFT, REWARDS_TOKEN_CUTOVER, RewardsTokenCutover, TOTAL_CLAIMS, TOTAL_UNSTAKES, AUTO_FINALIZE_ON_CLAIM, EARLY_STAKER_BOOST, EarlyStakerBoost, TOKEN_MULTIPLIERS, MAX_TOKEN_MULTIPLIER_BPS, IS_REWARD_MINTER, PRORATE_FINAL_PERIOD, MIN_POOL_BALANCE_TO_STAKE, STAKER_TOKENS};
 
 // version info for migration info
-const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// reply id of transfer from the owner for adding rewards pool.
+pub const ADD_REWARDS_POOL_REPLY_ID: u64 = 1;
+const MAX_CLAIM_ALL_TOKENS: usize = 10;
+const DEFAULT_PRUNE_ORPHANED_CLAIMS: u32 = 10;
+const MAX_PRUNE_ORPHANED_CLAIMS: u32 = 30;
+const DEFAULT_SWEEP_ABANDONED_REWARDS: u32 = 10;
+const MAX_SWEEP_ABANDONED_REWARDS: u32 = 30;
+const DEFAULT_INDEX_STAKER_TOKENS: u32 = 30;
+const MAX_INDEX_STAKER_TOKENS: u32 = 100;
+const MAX_STAKE_MANY_TOKENS: usize = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    is_valid_cycle_length(msg.cycle_length_in_seconds)?;
+    // the minimum cycle length is able to be raised for the chain which has the long block time.
+    // it is floored at MIN_CYCLE_LENGTH.
+    let min_cycle_length = msg.min_cycle_length_override.unwrap_or(MIN_CYCLE_LENGTH).max(MIN_CYCLE_LENGTH);
+    is_valid_cycle_length(msg.cycle_length_in_seconds, min_cycle_length)?;
     is_valid_period_length(msg.period_length_in_cycles)?;
 
+    // the contract addresses must be valid, empty strings and malformed addresses are rejected.
+    // the rewards token contract cannot be the nft staking contract itself.
+    let white_listed_nft_contract = deps.api.addr_validate(&msg.white_listed_nft_contract)?.to_string();
+    let rewards_token_contract = deps.api.addr_validate(&msg.rewards_token_contract)?.to_string();
+    if rewards_token_contract == env.contract.address {
+        return Err(ContractError::RewardsTokenIsStakingContract {})
+    }
+
+    // the owner is contract instantiater if it is not requested.
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender.clone(),
+    };
+
     // setup contract configuration.
-    // the owner is contract instantiater and is able to execute functions except stake, unstake and claim rewards.
+    // the owner is able to execute functions except stake, unstake and claim rewards.
     // Warning: cycles and periods need to be calibrated carefully. 
     //          Small values will increase computation load while estimating and claiming rewards. 
     //          Big values will increase the time to wait before a new period becomes claimable.
     // rewards_token_contract is cw20 and white_listed_nft_contract is cw721.
     let config_state = Config {
-        owner: info.sender.clone(),
+        owner,
         cycle_length_in_seconds: msg.cycle_length_in_seconds,
         period_length_in_cycles: msg.period_length_in_cycles,
-        white_listed_nft_contract: msg.white_listed_nft_contract,
-        rewards_token_contract: msg.rewards_token_contract,
+        white_listed_nft_contract,
+        rewards_token_contract,
+        booster_contract: None,
+        booster_bps: 0,
     };
 
+    // if validate contracts is set, the rewards token contract and the whitelisted nft contract are probed.
+    let validate_contracts = msg.validate_contracts.unwrap_or(false);
+    if validate_contracts {
+        validate_token_contracts(deps.as_ref(), config_state.clone())?;
+    }
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     CONFIG_STATE.save(deps.storage, &config_state)?;
-
-    // default max compute period = 2500.
-    // default unbonding duration = 1814400 (= 3 weeks).
-    let default_max_compute_period: u64 = 2_500;
-    let default_unbonding_duration: u64 = 1_814_400;
+    MIN_CYCLE_LENGTH_IN_SECONDS.save(deps.storage, &min_cycle_length)?;
 
     // Default of total rewards pool is zero and of disable state is false.
     TOTAL_REWARDS_POOL.save(deps.storage, &0)?;
     DISABLE.save(deps.storage, &false)?;
     NUMBER_OF_STAKED_NFTS.save(deps.storage, &0)?;
-    MAX_COMPUTE_PERIOD.save(deps.storage, &default_max_compute_period)?;
-    UNBONDING_DURATION.save(deps.storage, &default_unbonding_duration)?;
+    MAX_COMPUTE_PERIOD.save(deps.storage, &DEFAULT_MAX_COMPUTE_PERIOD)?;
+    UNBONDING_DURATION.save(deps.storage, &DEFAULT_UNBONDING_DURATION)?;
+    MIN_TOKENS_TO_EARN.save(deps.storage, &0)?;
+    CLAIM_CLIFF_PERIOD.save(deps.storage, &0)?;
+    REQUIRE_APPROVED_RECIPIENT.save(deps.storage, &false)?;
+    ALLOW_PRESTAKE.save(deps.storage, &false)?;
+    FEE_ON_TRANSFER_TOKEN.save(deps.storage, &false)?;
+    TOTAL_PAID_OUT.save(deps.storage, &0)?;
+    VALIDATE_CONTRACTS.save(deps.storage, &validate_contracts)?;
+    EMISSION_MODE.save(deps.storage, &EmissionMode::Flat)?;
+    MIN_UNSTAKE_CYCLES.save(deps.storage, &DEFAULT_MIN_UNSTAKE_CYCLES)?;
+    ALLOW_PUBLIC_FUNDING.save(deps.storage, &false)?;
+    REQUIRE_CLAIM_BEFORE_UNSTAKE.save(deps.storage, &false)?;
+    CONFIG_VERSION.save(deps.storage, &0)?;
+
+    // the minter role of the rewards token is verified for the features which mint rewards.
+    let is_reward_minter = query_is_reward_minter(deps.as_ref(), env.contract.address.to_string(), config_state.rewards_token_contract.clone())?;
+    IS_REWARD_MINTER.save(deps.storage, &is_reward_minter)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -74,20 +123,66 @@ pub fn execute(
     
     match msg {
         ExecuteMsg::SetConfig(msg) => set_config(deps, info, env, config, msg),
-        ExecuteMsg::Grant { address, expires } => grant(deps, info, config, address, expires),
-        ExecuteMsg::Revoke { address } => revoke(deps, info, config, address),
+        ExecuteMsg::Grant { address, expires } => grant(deps, info, env, config, address, expires),
+        ExecuteMsg::Revoke { address } => revoke(deps, info, env, config, address),
+        ExecuteMsg::ExtendGrant { address, expires } => extend_grant(deps, info, env, config, address, expires),
+        ExecuteMsg::ReplaceWhitelistedContract { old, new } => replace_whitelisted_contract(deps, info, env, config, old, new),
         ExecuteMsg::AddRewardsForPeriods { rewards_per_cycle } => add_rewards_for_periods(deps, env, info, rewards_per_cycle, config),
+        ExecuteMsg::SetRewardsPerPeriod { rewards_per_period } => set_rewards_per_period(deps, env, info, rewards_per_period, config),
         ExecuteMsg::Receive (msg) => add_rewards_pool(deps, info, env, config, msg),
+        ExecuteMsg::AddRewardsPoolFrom { amount } => add_rewards_pool_from(deps, info, env, config, amount),
         ExecuteMsg::SetMaxComputePeriod { new_max_compute_period } => set_max_compute_period(deps, info, env, new_max_compute_period, config),
         ExecuteMsg::SetUnbondingDuration { new_unbonding_duration } => set_unbonding_duration(deps, info, env, config, new_unbonding_duration),
-        ExecuteMsg::Start {} => start(deps, info, env, config),
+        ExecuteMsg::SetMinTokensToEarn { new_min_tokens_to_earn } => set_min_tokens_to_earn(deps, info, env, config, new_min_tokens_to_earn),
+        ExecuteMsg::SetStakeForwarders { new_stake_forwarders } => set_stake_forwarders(deps, info, env, config, new_stake_forwarders),
+        ExecuteMsg::SetClaimCliffPeriod { new_claim_cliff_period } => set_claim_cliff_period(deps, info, env, config, new_claim_cliff_period),
+        ExecuteMsg::SetAutoDisableThreshold { new_auto_disable_threshold } => set_auto_disable_threshold(deps, info, env, config, new_auto_disable_threshold),
+        ExecuteMsg::SetRequireApprovedRecipient { require_approved_recipient } => set_require_approved_recipient(deps, info, env, config, require_approved_recipient),
+        ExecuteMsg::SetAllowPrestake { allow_prestake } => set_allow_prestake(deps, info, env, config, allow_prestake),
+        ExecuteMsg::SetAllowPublicFunding { allow_public_funding } => set_allow_public_funding(deps, info, env, config, allow_public_funding),
+        ExecuteMsg::SetRequireClaimBeforeUnstake { require_claim_before_unstake } => set_require_claim_before_unstake(deps, info, env, config, require_claim_before_unstake),
+        ExecuteMsg::SetAutoFinalizeOnClaim { auto_finalize_on_claim } => set_auto_finalize_on_claim(deps, info, env, config, auto_finalize_on_claim),
+        ExecuteMsg::SetProrateFinalPeriod { prorate_final_period } => set_prorate_final_period(deps, info, env, config, prorate_final_period),
+        ExecuteMsg::SetRewardsFollowNft { rewards_follow_nft } => set_rewards_follow_nft(deps, info, env, config, rewards_follow_nft),
+        ExecuteMsg::SetBooster { booster_contract, booster_bps } => set_booster(deps, info, env, config, booster_contract, booster_bps),
+        ExecuteMsg::RefreshRewardMinter {} => refresh_reward_minter(deps, env, config),
+        ExecuteMsg::SetTokenMultiplier { token_id, bps } => set_token_multiplier(deps, info, env, config, token_id, bps),
+        ExecuteMsg::SetEarlyStakerBoost { boost_bps, boost_deadline_period, boost_decay_periods } => set_early_staker_boost(deps, info, env, config, boost_bps, boost_deadline_period, boost_decay_periods),
+        ExecuteMsg::SetRewardsTokenCutover { secondary_rewards_token, cutover_period } => set_rewards_token_cutover(deps, info, env, config, secondary_rewards_token, cutover_period),
+        ExecuteMsg::SetFeeOnTransferToken { fee_on_transfer_token } => set_fee_on_transfer_token(deps, info, env, config, fee_on_transfer_token),
+        ExecuteMsg::SetMaxRewardPerClaimPerToken { new_max_reward_per_claim_per_token } => set_max_reward_per_claim_per_token(deps, info, env, config, new_max_reward_per_claim_per_token),
+        ExecuteMsg::SetMinPoolBalanceToStake { min_pool_balance_to_stake } => set_min_pool_balance_to_stake(deps, info, env, config, min_pool_balance_to_stake),
+        ExecuteMsg::SetEmissionEndPeriod { new_emission_end_period } => set_emission_end_period(deps, info, env, config, new_emission_end_period),
+        ExecuteMsg::SetEmissionMode { emission_mode, reward_per_period } => set_emission_mode(deps, info, env, config, emission_mode, reward_per_period),
+        ExecuteMsg::SetPoolConstrainedEmission { pool_constrained_emission } => set_pool_constrained_emission(deps, info, env, config, pool_constrained_emission),
+        ExecuteMsg::SetMinUnstakeCycles { new_min_unstake_cycles } => set_min_unstake_cycles(deps, info, env, config, new_min_unstake_cycles),
+        ExecuteMsg::SetWithdrawTimelockSeconds { new_withdraw_timelock_seconds } => set_withdraw_timelock_seconds(deps, info, env, config, new_withdraw_timelock_seconds),
+        ExecuteMsg::Start { idempotent } => start(deps, info, env, config, idempotent),
         ExecuteMsg::Disable {} => disable(deps, info, env, config),
         ExecuteMsg::Enable {} => enable(deps, info, env, config),
-        ExecuteMsg::WithdrawRewardsPool { amount } => withdraw_rewards_pool(deps, info, env, config, amount),
-        ExecuteMsg::WithdrawAllRewardsPool {} => withdraw_all_rewards_pool(deps, info, env, config),
+        ExecuteMsg::SetDisabled { disabled } => set_disabled(deps, info, env, config, disabled),
+        ExecuteMsg::WithdrawRewardsPool { amount, recipient } => withdraw_rewards_pool(deps, info, env, config, amount, recipient),
+        ExecuteMsg::WithdrawAllRewardsPool { recipient } => withdraw_all_rewards_pool(deps, info, env, config, recipient),
+        ExecuteMsg::RequestWithdraw { amount, recipient } => request_withdraw(deps, info, env, config, amount, recipient),
+        ExecuteMsg::ExecuteWithdraw {} => execute_withdraw(deps, info, env, config),
+        ExecuteMsg::CancelWithdraw {} => cancel_withdraw(deps, info, env, config),
+        ExecuteMsg::SyncRewardsPool {} => sync_rewards_pool(deps, info, env, config),
+        ExecuteMsg::PruneOrphanedClaims { start_after, limit } => prune_orphaned_claims(deps, info, env, config, start_after, limit),
+        ExecuteMsg::SweepAbandonedRewards { older_than_periods, start_after, limit } => sweep_abandoned_rewards(deps, info, env, config, older_than_periods, start_after, limit),
+        ExecuteMsg::IndexStakerTokens { start_after, limit } => index_staker_tokens(deps, info, env, config, start_after, limit),
+        ExecuteMsg::ProcessEmissionLedger {} => process_emission_ledger(deps, env, config),
         ExecuteMsg::ReceiveNft(msg) => stake_nft(deps, env, info, config, msg),
-        ExecuteMsg::UnstakeNft { token_id, claim_recipient_address } => unstake_nft(deps, env, info, config, token_id, claim_recipient_address),
-        ExecuteMsg::ClaimRewards { periods, token_id, claim_recipient_address } => claim_rewards(deps, info, env, periods, token_id, config, claim_recipient_address),
+        ExecuteMsg::StakeMany { token_ids } => stake_many(deps, env, info, config, token_ids),
+        ExecuteMsg::UnstakeBooster { token_id } => unstake_booster(deps, info, env, config, token_id),
+        ExecuteMsg::UnstakeNft { token_id, claim_recipient_address, claim_on_unstake } => unstake_nft(deps, env, info, config, token_id, claim_recipient_address, claim_on_unstake),
+        ExecuteMsg::ClaimRewards { periods, token_id, claim_recipient_address, expected_config_version } => claim_rewards(deps, info, env, periods, token_id, config, claim_recipient_address, expected_config_version),
+        ExecuteMsg::ClaimAllRewards { periods, claim_recipient_address, start_after } => claim_all_rewards(deps, info, env, periods, config, claim_recipient_address, start_after),
+        ExecuteMsg::MarkTokenBurned { token_id } => mark_token_burned(deps, env, info, config, token_id),
+        ExecuteMsg::BlockToken { token_id } => block_token(deps, info, env, config, token_id),
+        ExecuteMsg::UnblockToken { token_id } => unblock_token(deps, info, env, config, token_id),
+        ExecuteMsg::SetTokenRewardRecipient { token_id, recipient, expires } => set_token_reward_recipient(deps, info, token_id, recipient, expires),
+        ExecuteMsg::ApproveRewardRecipient { token_id, recipient } => approve_reward_recipient(deps, info, token_id, recipient),
+        ExecuteMsg::SkipToCurrentPeriod { token_id, forfeit_amount } => skip_to_current_period(deps, env, info, config, token_id, forfeit_amount),
     }
 }
 
@@ -106,28 +201,39 @@ pub fn set_config(
     let mut white_listed_nft_contract = config.clone().white_listed_nft_contract;
     let mut rewards_token_contract = config.clone().rewards_token_contract;
 
-    if !msg.cycle_length_in_seconds.is_none() && is_valid_cycle_length(msg.cycle_length_in_seconds.unwrap())? {
-        cycle_length_in_seconds = msg.cycle_length_in_seconds.unwrap();
+    let min_cycle_length = get_min_cycle_length(deps.as_ref())?;
+    if let Some(new_cycle_length_in_seconds) = msg.cycle_length_in_seconds {
+        if is_valid_cycle_length(new_cycle_length_in_seconds, min_cycle_length)? {
+            cycle_length_in_seconds = new_cycle_length_in_seconds;
+        }
     } 
-    if !msg.period_length_in_cycles.is_none() && is_valid_period_length(msg.period_length_in_cycles.unwrap())? {
-        period_length_in_cycles = msg.period_length_in_cycles.unwrap();
+    if let Some(new_period_length_in_cycles) = msg.period_length_in_cycles {
+        if is_valid_period_length(new_period_length_in_cycles)? {
+            period_length_in_cycles = new_period_length_in_cycles;
+        }
     }
-    if !msg.white_listed_nft_contract.is_none() {
-        white_listed_nft_contract = msg.white_listed_nft_contract.unwrap();
+    if let Some(new_white_listed_nft_contract) = msg.white_listed_nft_contract {
+        if new_white_listed_nft_contract != white_listed_nft_contract {
+            check_whitelist_replaceable(deps.as_ref())?;
+        }
+        white_listed_nft_contract = new_white_listed_nft_contract;
     }
-    if !msg.rewards_token_contract.is_none() {
-        rewards_token_contract = msg.rewards_token_contract.unwrap();
+    if let Some(new_rewards_token_contract) = msg.rewards_token_contract {
+        rewards_token_contract = new_rewards_token_contract;
     }
 
     let config_state = Config {
         owner: config.clone().owner,
-        cycle_length_in_seconds: cycle_length_in_seconds.clone(),
-        period_length_in_cycles: period_length_in_cycles.clone(),
+        cycle_length_in_seconds,
+        period_length_in_cycles,
         white_listed_nft_contract: white_listed_nft_contract.clone(),
         rewards_token_contract: rewards_token_contract.clone(),
+        booster_contract: config.clone().booster_contract,
+        booster_bps: config.booster_bps,
     };
 
     CONFIG_STATE.save(deps.storage, &config_state)?;
+    increase_config_version(deps.branch())?;
 
     Ok(Response::new()
         .add_attribute("method", "set_config")
@@ -138,15 +244,58 @@ pub fn set_config(
     )
 }
 
+// replace the whitelisted nft contract.
+// already staked nfts keep the nft contract which is recorded in the token info,
+// so they are returned to the staker via the original nft contract when they are unstaked.
+// new nfts are only able to be staked from the new whitelisted nft contract.
+pub fn replace_whitelisted_contract(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    old: String,
+    new: String,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config.clone())?;
+
+    if old != config.white_listed_nft_contract {
+        return Err(ContractError::InvalidWhitelistedContract {
+            white_listed_contract: config.white_listed_nft_contract,
+            requester: old,
+        })
+    }
+    check_whitelist_replaceable(deps.as_ref())?;
+
+    let config_state = Config {
+        white_listed_nft_contract: new.clone(),
+        ..config
+    };
+    CONFIG_STATE.save(deps.storage, &config_state)?;
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "replace_whitelisted_contract")
+        .add_attribute("old_white_listed_nft_contract", old)
+        .add_attribute("new_white_listed_nft_contract", new)
+    )
+}
+
 // grant other account which it will be given a role of contract owner.
+// only the contract owner can grant, granted address cannot grant others.
 pub fn grant(
     deps: DepsMut,
     info: MessageInfo,
+    env: Env,
     config: Config,
     address: String,
     expires: Option<Expiration>
 ) -> Result<Response, ContractError> {
-    check_contract_owner_only(info.clone(), config.clone())?;
+    check_contract_owner_only(deps.as_ref(), info.clone(), env, config.clone())?;
+
+    // the contract owner is already authorized.
+    if address == config.owner {
+        return Err(ContractError::CannotGrantSelf {})
+    }
 
     let grants = GRANTS.may_load(deps.storage, address.clone())?;
     if grants.is_none() {
@@ -163,13 +312,19 @@ pub fn grant(
 }
 
 // revoke granted address.
+// only the contract owner can revoke.
 pub fn revoke(
     deps: DepsMut,
     info: MessageInfo,
+    env: Env,
     config: Config,
     address: String,
 ) -> Result<Response, ContractError> {
-    check_contract_owner_only(info, config)?;
+    check_contract_owner_only(deps.as_ref(), info, env, config.clone())?;
+
+    if address == config.owner {
+        return Err(ContractError::CannotGrantSelf {})
+    }
 
     let grants = GRANTS.may_load(deps.storage, address.clone())?;
     if grants.is_none() {
@@ -184,6 +339,33 @@ pub fn revoke(
     )
 }
 
+// update the expiration of the granted address without revoke and grant.
+// only the contract owner can extend.
+pub fn extend_grant(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    address: String,
+    expires: Option<Expiration>
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env, config)?;
+
+    let grants = GRANTS.may_load(deps.storage, address.clone())?;
+    if grants.is_none() {
+        return Err(ContractError::InvalidGrantedAddress { address: address.clone() })
+    }
+
+    let grants_data = Grant::new(address.clone(), expires);
+    GRANTS.save(deps.storage, address.clone(), &grants_data)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "extend_grant")
+        .add_attribute("grant_address", address)
+        .add_attribute("expires", grants_data.expires.to_string())
+    )
+}
+
 // set rewards schedule.
 // rewards per cycle can changed by executing add_rewards_for_periods even after start.
 // if rewards per cycle are replaced to new value of rewards per cycle, 
@@ -198,10 +380,11 @@ pub fn add_rewards_for_periods(
     check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
 
     // rewards per cycle shoule be bigger than zero.
-    if rewards_per_cycle <= 0 {
+    if rewards_per_cycle == 0 {
         return Err(ContractError::InvalidRewardsSchedule {})
     }
     REWARDS_SCHEDULE.save(deps.storage, &rewards_per_cycle)?;
+    increase_config_version(deps.branch())?;
     
     Ok(Response::new()
         .add_attribute("method", "add_rewards_for_periods")
@@ -209,6 +392,35 @@ pub fn add_rewards_for_periods(
     )
 }
 
+// set the rewards schedule by rewards per period.
+// the equivalent rewards per cycle is saved as same as add_rewards_for_periods, a separate per period value is not kept.
+// rewards per period should be divisible by the period length in cycles to avoid silent rounding,
+// and the rewards per period is changed if the period length is changed by set_config later.
+#[allow(clippy::manual_is_multiple_of)]
+pub fn set_rewards_per_period(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rewards_per_period: u128,
+    config: Config,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    let period_length_in_cycles = config.period_length_in_cycles;
+    if rewards_per_period % period_length_in_cycles as u128 != 0 {
+        return Err(ContractError::RewardsPerPeriodNotDivisible { rewards_per_period, period_length_in_cycles })
+    }
+
+    let rewards_per_cycle = rewards_per_period / period_length_in_cycles as u128;
+    add_rewards_for_periods(deps, env, info, rewards_per_cycle, config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_rewards_per_period")
+        .add_attribute("rewards_per_period", rewards_per_period.to_string())
+        .add_attribute("rewards_per_cycle", rewards_per_cycle.to_string())
+    )
+}
+
 // increase rewards pool.
 // nft staking contract requests to transfer rewards from contract instantiater, as contract owner, to nft staking contract.
 pub fn add_rewards_pool (
@@ -218,16 +430,41 @@ pub fn add_rewards_pool (
     config: Config,
     msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    if info.sender.to_string() != config.clone().rewards_token_contract {
+    if info.sender != config.clone().rewards_token_contract {
         return Err(ContractError::InvalidRewardsTokenContract { 
             rewards_token_contract: config.clone().rewards_token_contract, 
             requester: info.sender.to_string(), 
         })
     }
 
-    check_contract_owner(deps.branch(), contract_info(msg.clone()).unwrap(), env.clone(), config.clone())?;
+    // the sender of the rewards token is not checked when the public funding is allowed.
+    if !ALLOW_PUBLIC_FUNDING.may_load(deps.storage)?.unwrap_or(false) {
+        check_contract_owner(deps.branch(), contract_info(msg.clone()), env.clone(), config.clone())?;
+    }
+
+    // the received amount of fee on transfer token is less than msg.amount,
+    // and the balance before sending cannot be known in the receive hook.
+    if FEE_ON_TRANSFER_TOKEN.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::FeeOnTransferDepositRequired {})
+    }
 
+    // post-condition of the deposit before crediting msg.amount.
+    // the receive hook is dispatched by the rewards token after the transfer, so the balance must cover the rewards pool and the reported amount.
+    // a rewards token or a proxy which reports more than it transfers is rejected even if the pool is already funded.
     let total_rewards_pool = TOTAL_REWARDS_POOL.load(deps.storage)?;
+    let balance = query_rewards_token_balance(deps.as_ref(), env.contract.address.to_string(), config.rewards_token_contract.clone())?.balance.u128();
+    if balance < total_rewards_pool + msg.amount.u128() {
+        return Err(ContractError::RewardsDepositNotReceived { 
+            reported_amount: msg.amount.u128(), 
+            total_rewards_pool,
+            balance,
+        })
+    }
+
+    // the periods completed before the deposit are decided with the budget before the deposit.
+    settle_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+    update_emission_budget(deps.branch(), msg.amount.u128(), 0)?;
+
     let rewards = total_rewards_pool + msg.amount.clone().u128();
 
     TOTAL_REWARDS_POOL.save(deps.storage, &rewards)?;
@@ -237,11 +474,44 @@ pub fn add_rewards_pool (
         .add_attribute("added_rewards", msg.amount.to_string())
         .add_attribute("total_rewards", rewards.to_string())
         .add_attribute("send_from", info.sender)
+        .add_attribute("depositor", msg.sender)
+    )
+}
+
+// increase rewards pool by pulling rewards from the contract owner.
+// the owner should increase allowance of the nft staking contract before.
+// the balance of the nft staking contract is checked before and after the transfer,
+// and the actual received amount is added to the rewards pool in the reply.
+// it is used when the rewards token takes a transfer fee.
+pub fn add_rewards_pool_from(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    amount: u128,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    let balance_before = query_rewards_token_balance(deps.as_ref(), env.contract.address.to_string(), config.rewards_token_contract.clone())?.balance.u128();
+    PENDING_REWARDS_DEPOSIT.save(deps.storage, &PendingRewardsDeposit { 
+        requested_amount: amount, 
+        balance_before,
+    })?;
+
+    let message = execute_token_contract_transfer_from(config.rewards_token_contract, info.sender.to_string(), env.contract.address.to_string(), amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "add_rewards_pool_from")
+        .add_attribute("requested_amount", amount.to_string())
+        .add_attribute("balance_before", balance_before.to_string())
+        .add_submessage(SubMsg::reply_on_success(message, ADD_REWARDS_POOL_REPLY_ID))
     )
 }
 
 // change max_compute_period that default value is 2500.
 // nft staking contract needs max_compute_period to avoid restriction about query gas limit of wasmd(defaultSmartQueryGasLimit is 3,000,000).  
+// it is rejected over MAX_ALLOWED_COMPUTE_PERIOD, and the warning attribute is added over the recommended max compute period,
+// which computes up to RECOMMENDED_MAX_COMPUTE_CYCLES cycles by the period length.
 pub fn set_max_compute_period (
     mut deps: DepsMut,
     info: MessageInfo,
@@ -250,18 +520,32 @@ pub fn set_max_compute_period (
     config: Config,
 ) -> Result<Response, ContractError> {
     check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
-    if new_max_compute_period <= 0 {
+    if new_max_compute_period == 0 {
         return Err(ContractError::InvalidSetMaxPeriod {})
     }
+    if new_max_compute_period > MAX_ALLOWED_COMPUTE_PERIOD {
+        return Err(ContractError::MaxComputePeriodTooLarge { 
+            max_compute_period: new_max_compute_period, 
+            max_allowed_compute_period: MAX_ALLOWED_COMPUTE_PERIOD,
+        })
+    }
 
     let previous_max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
     MAX_COMPUTE_PERIOD.save(deps.storage, &new_max_compute_period)?;
 
-    Ok(Response::new()
+    let mut response = Response::new()
         .add_attribute("method", "set_max_compute_period")
         .add_attribute("previous_max_compute_period", previous_max_compute_period.to_string())
-        .add_attribute("new_max_compute_period", new_max_compute_period.to_string())
-    )
+        .add_attribute("new_max_compute_period", new_max_compute_period.to_string());
+
+    let recommended_max_compute_period = (RECOMMENDED_MAX_COMPUTE_CYCLES / config.period_length_in_cycles).max(1);
+    if new_max_compute_period > recommended_max_compute_period {
+        response = response
+            .add_attribute("warning", "max_compute_period exceeds the recommended value, the computation may reach the gas limit")
+            .add_attribute("recommended_max_compute_period", recommended_max_compute_period.to_string());
+    }
+
+    Ok(response)
 }
 
 // change unbonding_duration that default value is 1814400.
@@ -277,6 +561,7 @@ pub fn set_unbonding_duration(
     check_contract_owner(deps.branch(), info, env, config)?;
 
     UNBONDING_DURATION.save(deps.storage, &new_unbonding_duration.clone())?;
+    increase_config_version(deps.branch())?;
 
     Ok(Response::new()
         .add_attribute("method", "set_unbonding_duration")
@@ -284,223 +569,1308 @@ pub fn set_unbonding_duration(
     )
 }
 
-// nft staking contract start.
-// every calculating period and cycle are affected by start timestamp.
-pub fn start(
+// change min_tokens_to_earn that default value is 0.
+// the staker who stakes less nfts than min_tokens_to_earn does not earn rewards of the period,
+// the number of staked nfts of the staker must be held for the full period.
+pub fn set_min_tokens_to_earn(
     mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
     config: Config,
+    new_min_tokens_to_earn: u64,
 ) -> Result<Response, ContractError> {
-    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
-    if !start_timestamp.is_none() {
-        return Err(ContractError::AlreadyStarted {})
-    }
-    let now = env.block.time.seconds();
-    
-    START_TIMESTAMP.save(deps.storage, &now)?;
+    MIN_TOKENS_TO_EARN.save(deps.storage, &new_min_tokens_to_earn)?;
+    increase_config_version(deps.branch())?;
 
     Ok(Response::new()
-        .add_attribute("method", "start")
-        .add_attribute("start_time_stamp", now.to_string())
+        .add_attribute("method", "set_min_tokens_to_earn")
+        .add_attribute("new_min_tokens_to_earn", new_min_tokens_to_earn.to_string())
     )
 }
 
-// nft staking contract halt.
-// after disabled, functions are stop.
-pub fn disable(
+// set forwarder addresses which can stake nft on behalf of other address.
+// anyone can stake on behalf of other address when forwarders are not set.
+pub fn set_stake_forwarders(
     mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
     config: Config,
+    new_stake_forwarders: Option<Vec<String>>,
 ) -> Result<Response, ContractError> {
-    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    DISABLE.save(deps.storage, &true)?;
+    if new_stake_forwarders.is_none() {
+        STAKE_FORWARDERS.remove(deps.storage);
+
+        return Ok(Response::new()
+            .add_attribute("method", "set_stake_forwarders")
+            .add_attribute("new_stake_forwarders", "none")
+        )
+    }
+
+    let mut stake_forwarders: Vec<String> = vec![];
+    for forwarder in new_stake_forwarders.unwrap() {
+        let forwarder = deps.api.addr_validate(&forwarder)?;
+        stake_forwarders.push(forwarder.to_string());
+    }
+    STAKE_FORWARDERS.save(deps.storage, &stake_forwarders)?;
 
     Ok(Response::new()
-        .add_attribute("method", "disable")
-        .add_attribute("disable", true.to_string())
+        .add_attribute("method", "set_stake_forwarders")
+        .add_attribute("new_stake_forwarders", stake_forwarders.join(","))
     )
 }
 
-// if the nft staking contract is disabled and the contract owner want to activate again, 
-// execute enable function.
-pub fn enable(
+// set claim cliff period.
+// rewards are accrued from the first period, but cannot be claimed until the current period reaches the cliff.
+pub fn set_claim_cliff_period(
     mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
     config: Config,
+    new_claim_cliff_period: u64,
 ) -> Result<Response, ContractError> {
-    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
-
-    let disable = DISABLE.load(deps.storage)?;
-    if !disable {
-        return Err(ContractError::CannotEnable { disable: disable })
-    }
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    DISABLE.save(deps.storage, &!disable)?;
+    CLAIM_CLIFF_PERIOD.save(deps.storage, &new_claim_cliff_period)?;
+    increase_config_version(deps.branch())?;
 
     Ok(Response::new()
-        .add_attribute("method", "enable")
-        .add_attribute("previous_disable_state", disable.to_string())
-        .add_attribute("now_disable_state", (!disable).to_string())
+        .add_attribute("method", "set_claim_cliff_period")
+        .add_attribute("new_claim_cliff_period", new_claim_cliff_period.to_string())
     )
 }
 
-// withdraw rewards pool.
-// the nft staking contract's balances of token rewards which is value of requested amount are transferred to contract owner.
-pub fn withdraw_rewards_pool(
+// set auto disable threshold.
+// the contract is disabled automatically when the rewards pool balance falls below the threshold by claiming.
+pub fn set_auto_disable_threshold(
     mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
     config: Config,
-    amount: u128,
+    new_auto_disable_threshold: Option<u128>,
 ) -> Result<Response, ContractError> {
-    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    let disabled = check_disable(deps.branch())?;
-    let rewards_token_contract = config.clone().rewards_token_contract;
-    let owner = info.clone().sender;
+    match new_auto_disable_threshold {
+        Some(t) => AUTO_DISABLE_THRESHOLD.save(deps.storage, &t)?,
+        None => AUTO_DISABLE_THRESHOLD.remove(deps.storage),
+    }
 
-    check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_INSUFFICIENT, Some(amount.clone()))?;
-    let message = execute_token_contract_transfer(rewards_token_contract, owner.to_string(), amount.clone())?;
+    let threshold_attribute = match new_auto_disable_threshold {
+        Some(t) => t.to_string(),
+        None => "none".to_string(),
+    };
 
     Ok(Response::new()
-        .add_attribute("method", "withdraw_rewards_pool")
-        .add_attribute("disable", disabled.to_string())
-        .add_attribute("rewards_token_contract", config.rewards_token_contract)
-        .add_attribute("owner", info.sender.to_string())
-        .add_attribute("withdraw_amount", amount.to_string())
-        .add_messages(message)
+        .add_attribute("method", "set_auto_disable_threshold")
+        .add_attribute("new_auto_disable_threshold", threshold_attribute)
     )
 }
 
-// withdraw all rewards pool.
-// the nft staking contract's all balances are transferred to contract owner.
-pub fn withdraw_all_rewards_pool(
+// set whether claim recipient addresses must be approved by the staker.
+pub fn set_require_approved_recipient(
     mut deps: DepsMut,
     info: MessageInfo,
     env: Env,
     config: Config,
+    require_approved_recipient: bool,
 ) -> Result<Response, ContractError> {
-    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    let disabled = check_disable(deps.branch())?;
-    let rewards_token_contract = config.clone().rewards_token_contract;
-    let owner = info.clone().sender;
-    let address = env.contract.address.to_string();
+    REQUIRE_APPROVED_RECIPIENT.save(deps.storage, &require_approved_recipient)?;
 
-    // nft staking contract balances
-    let balance_response = query_rewards_token_balance(deps.as_ref(), address.clone(), rewards_token_contract.clone())?;
-    let amount = balance_response.balance.u128();
+    Ok(Response::new()
+        .add_attribute("method", "set_require_approved_recipient")
+        .add_attribute("require_approved_recipient", require_approved_recipient.to_string())
+    )
+}
+
+// set whether the nft can be staked before the nft staking contract starts.
+pub fn set_allow_prestake(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    allow_prestake: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    let message = execute_token_contract_transfer(rewards_token_contract, owner.to_string(), amount.clone())?;
+    ALLOW_PRESTAKE.save(deps.storage, &allow_prestake)?;
 
     Ok(Response::new()
-        .add_attribute("method", "withdraw_all_rewards_pool")
-        .add_attribute("disable", disabled.to_string())
-        .add_attribute("rewards_token_contract", config.rewards_token_contract)
-        .add_attribute("nft_staking_contract", address)
-        .add_attribute("owner", info.sender.to_string())
-        .add_attribute("withdraw_amount", amount.to_string())
-        .add_messages(message)
+        .add_attribute("method", "set_allow_prestake")
+        .add_attribute("allow_prestake", allow_prestake.to_string())
     )
 }
 
-// staking nft.
-// the staker can stake nft as cw721.
-pub fn stake_nft(
+// set whether anyone can add rewards pool by sending the rewards token.
+// the rewards pool is added only by the contract owner or the grantee if it is not set.
+pub fn set_allow_public_funding(
     mut deps: DepsMut,
-    env: Env,
     info: MessageInfo,
+    env: Env,
     config: Config,
-    msg: Cw721ReceiveMsg,
+    allow_public_funding: bool,
 ) -> Result<Response, ContractError> {
-    // check empty total supply rewards pool.
-    let total_rewards_pool = TOTAL_REWARDS_POOL.may_load(deps.branch().storage)?;
-    if total_rewards_pool.is_none() {
-        return Err(ContractError::EmptyRewardsPool {})
-    }
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    // check empty rewards pool of nft staking contract.
-    check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_EMPTY, None)?;
+    ALLOW_PUBLIC_FUNDING.save(deps.storage, &allow_public_funding)?;
 
-    // check rewards schedule.
-    let rewards_schedule = REWARDS_SCHEDULE.may_load(deps.branch().storage)?;
-    if rewards_schedule.is_none() {
-        return Err(ContractError::NoneRewardsSchedule {})
-    }
+    Ok(Response::new()
+        .add_attribute("method", "set_allow_public_funding")
+        .add_attribute("allow_public_funding", allow_public_funding.to_string())
+    )
+}
 
-    // check the nft must be sended from whitelisted nft contract.
-    if info.sender.to_string() != config.clone().white_listed_nft_contract {
-        return Err(ContractError::InvalidWhitelistedContract { 
-            white_listed_contract: config.clone().white_listed_nft_contract, 
-            requester: info.sender.to_string() 
-        })
-    }
+// set whether rewards must be claimed by claim_rewards before unstaking.
+// if it is set, unstake_nft does not pay remain rewards, claims and the unstake are separated for clean records.
+pub fn set_require_claim_before_unstake(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    require_claim_before_unstake: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    let start_timestamp = check_start_timestamp(deps.branch())?;
-    check_disable(deps.branch())?;
+    REQUIRE_CLAIM_BEFORE_UNSTAKE.save(deps.storage, &require_claim_before_unstake)?;
+    increase_config_version(deps.branch())?;
 
-    let staker = msg.sender;
-    let token_id = msg.token_id;
-    let send_nft_msg = msg.msg;
-    let timestamp = env.block.time.seconds();
-    let current_cycle = get_cycle(timestamp, start_timestamp, config.clone())?;
+    Ok(Response::new()
+        .add_attribute("method", "set_require_claim_before_unstake")
+        .add_attribute("require_claim_before_unstake", require_claim_before_unstake.to_string())
+    )
+}
 
-    // the nft for staking is managed by mapping staker's address and nft token ID.
-    // the staker stakes multi nft and can claim rewards for each nft.
-    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+// set whether claim_rewards finalizes the unstake of the token whose unbonding duration has elapsed.
+// if it is set, the nft is returned and the remain rewards are paid as unstake_nft instead of TokenIdIsUnbonding.
+pub fn set_auto_finalize_on_claim(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    auto_finalize_on_claim: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-    let update_histories_response = update_histories(deps.branch(), staker_tokenid_key.clone(), IS_STAKED, current_cycle)?;
+    AUTO_FINALIZE_ON_CLAIM.save(deps.storage, &auto_finalize_on_claim)?;
 
-    let token_infos = TOKEN_INFOS.may_load(deps.branch().storage, token_id.clone())?;
-    if !token_infos.is_none() {
+    Ok(Response::new()
+        .add_attribute("method", "set_auto_finalize_on_claim")
+        .add_attribute("auto_finalize_on_claim", auto_finalize_on_claim.to_string())
+    )
+}
 
-        // prevent duplication.
-        if token_infos.clone().unwrap().is_staked {
-            return Err(ContractError::AlreadyStaked {})
-        }
-        let withdraw_cycle = token_infos.unwrap().withdraw_cycle;
+// set whether the final partial period of the unbonding token is prorated by cycles.
+// if it is set, the completed cycles of the period of the requested unbond time are paid, otherwise the partial period is dropped.
+pub fn set_prorate_final_period(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    prorate_final_period: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
 
-        // cannot re-stake when current cycle of block time is same setup withdraw cycle
-        if current_cycle == withdraw_cycle {
-            return Err(ContractError::UnstakedTokenCooldown {})
-        }    
-    }
+    PRORATE_FINAL_PERIOD.save(deps.storage, &prorate_final_period)?;
+    increase_config_version(deps.branch())?;
 
-    let next_claims = NEXT_CLAIMS.may_load(deps.branch().storage, staker_tokenid_key.clone())?;
+    Ok(Response::new()
+        .add_attribute("method", "set_prorate_final_period")
+        .add_attribute("prorate_final_period", prorate_final_period.to_string())
+    )
+}
 
-    // initialise the next claim if it was the first stake for this staker or if 
-    // the next claim was re-initialised.
+// set whether the accrued rewards follow the nft.
+// if it is set, only the current staker of the token claims, and the deferred rewards of the previous staker are paid to the new staker on restake.
+pub fn set_rewards_follow_nft(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    rewards_follow_nft: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    REWARDS_FOLLOW_NFT.save(deps.storage, &rewards_follow_nft)?;
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_rewards_follow_nft")
+        .add_attribute("rewards_follow_nft", rewards_follow_nft.to_string())
+    )
+}
+
+// set the booster nft contract and the bps to increase rewards of the staker who stakes the booster nft.
+// the booster contract cannot be changed while booster nfts are staked, they are returned via the booster contract.
+pub fn set_booster(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    booster_contract: Option<String>,
+    booster_bps: u16,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config.clone())?;
+
+    if let Some(booster_contract) = booster_contract.clone() {
+        if booster_contract == config.white_listed_nft_contract {
+            return Err(ContractError::InvalidBoosterContract { booster_contract })
+        }
+    }
+
+    let is_booster_staked = BOOSTER_TOKENS.keys(deps.storage, None, None, Order::Ascending).next().is_some();
+    if booster_contract != config.booster_contract && is_booster_staked {
+        return Err(ContractError::BoosterContractInUse {})
+    }
+
+    let config_state = Config {
+        booster_contract: booster_contract.clone(),
+        booster_bps,
+        ..config
+    };
+    CONFIG_STATE.save(deps.storage, &config_state)?;
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_booster")
+        .add_attribute("booster_contract", booster_contract.unwrap_or_else(|| "none".to_string()))
+        .add_attribute("booster_bps", booster_bps.to_string())
+    )
+}
+
+// refresh whether the nft staking contract is the minter of the rewards token.
+// anyone is able to refresh it, because it only reflects the minter of the rewards token.
+pub fn refresh_reward_minter(
+    deps: DepsMut,
+    env: Env,
+    config: Config,
+) -> Result<Response, ContractError> {
+    let is_reward_minter = query_is_reward_minter(deps.as_ref(), env.contract.address.to_string(), config.rewards_token_contract)?;
+    IS_REWARD_MINTER.save(deps.storage, &is_reward_minter)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "refresh_reward_minter")
+        .add_attribute("is_reward_minter", is_reward_minter.to_string())
+    )
+}
+
+// set the multiplier bps of rewards of the token for manual adjustments, 10000 is 1x.
+// the multiplier is applied to every unclaimed period of the token as same as the rewards schedule.
+pub fn set_token_multiplier(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    token_id: String,
+    bps: u16,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    // the running max is kept before the multiplier is changed, so the multipliers set before it exists are included.
+    let max_token_multiplier_bps = max_token_multiplier_bps(deps.as_ref())?.max(bps);
+    MAX_TOKEN_MULTIPLIER_BPS.save(deps.storage, &max_token_multiplier_bps)?;
+
+    if bps == DEFAULT_TOKEN_MULTIPLIER_BPS {
+        TOKEN_MULTIPLIERS.remove(deps.storage, token_id.clone());
+    } else {
+        TOKEN_MULTIPLIERS.save(deps.storage, token_id.clone(), &bps)?;
+    }
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_token_multiplier")
+        .add_attribute("token_id", token_id)
+        .add_attribute("bps", bps.to_string())
+    )
+}
+
+// set the boost for the early stakers who staked before the boost deadline period.
+// the boost bps is added to the booster until the deadline period, and linearly decays to zero over the boost decay periods.
+pub fn set_early_staker_boost(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    boost_bps: u16,
+    boost_deadline_period: u64,
+    boost_decay_periods: u64,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    EARLY_STAKER_BOOST.save(deps.storage, &EarlyStakerBoost {
+        boost_bps,
+        boost_deadline_period,
+        boost_decay_periods,
+    })?;
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_early_staker_boost")
+        .add_attribute("boost_bps", boost_bps.to_string())
+        .add_attribute("boost_deadline_period", boost_deadline_period.to_string())
+        .add_attribute("boost_decay_periods", boost_decay_periods.to_string())
+    )
+}
+
+// set the secondary rewards token and the cutover period to rotate the rewards token.
+// the periods before the cutover period are paid in the rewards token, the periods from the cutover period are paid in the secondary rewards token.
+// the cutover is not able to be changed once the cutover period is reached.
+pub fn set_rewards_token_cutover(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    secondary_rewards_token: String,
+    cutover_period: u64,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env.clone(), config.clone())?;
+
+    let secondary_rewards_token = deps.api.addr_validate(&secondary_rewards_token)?.to_string();
+    if secondary_rewards_token == config.rewards_token_contract || secondary_rewards_token == env.contract.address {
+        return Err(ContractError::InvalidSecondaryRewardsToken {})
+    }
+
+    let current_period = match START_TIMESTAMP.may_load(deps.storage)? {
+        Some(start_timestamp) => get_current_period(env.block.time.seconds(), start_timestamp, config.clone())?,
+        None => 0,
+    };
+
+    let rewards_token_cutover = REWARDS_TOKEN_CUTOVER.may_load(deps.storage)?;
+    if let Some(rewards_token_cutover) = rewards_token_cutover {
+        if current_period >= rewards_token_cutover.cutover_period {
+            return Err(ContractError::RewardsTokenCutoverReached { cutover_period: rewards_token_cutover.cutover_period })
+        }
+    }
+
+    if cutover_period <= current_period {
+        return Err(ContractError::InvalidCutoverPeriod { cutover_period, current_period })
+    }
+
+    REWARDS_TOKEN_CUTOVER.save(deps.storage, &RewardsTokenCutover {
+        secondary_rewards_token: secondary_rewards_token.clone(),
+        cutover_period,
+    })?;
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_rewards_token_cutover")
+        .add_attribute("secondary_rewards_token", secondary_rewards_token)
+        .add_attribute("cutover_period", cutover_period.to_string())
+    )
+}
+
+// set whether the rewards token takes a transfer fee.
+// if it is set, the rewards pool can be added only by add_rewards_pool_from.
+pub fn set_fee_on_transfer_token(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    fee_on_transfer_token: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    FEE_ON_TRANSFER_TOKEN.save(deps.storage, &fee_on_transfer_token)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_fee_on_transfer_token")
+        .add_attribute("fee_on_transfer_token", fee_on_transfer_token.to_string())
+    )
+}
+
+// set max rewards which a token is able to get by one computation of rewards.
+// the rewards of periods exceeding the max value remain claimable in the next claims.
+// if it is none, rewards are not capped.
+pub fn set_max_reward_per_claim_per_token(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    new_max_reward_per_claim_per_token: Option<u128>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    match new_max_reward_per_claim_per_token {
+        Some(t) => MAX_REWARD_PER_CLAIM_PER_TOKEN.save(deps.storage, &t)?,
+        None => MAX_REWARD_PER_CLAIM_PER_TOKEN.remove(deps.storage),
+    }
+    increase_config_version(deps.branch())?;
+
+    let max_reward_attribute = match new_max_reward_per_claim_per_token {
+        Some(t) => t.to_string(),
+        None => "none".to_string(),
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "set_max_reward_per_claim_per_token")
+        .add_attribute("new_max_reward_per_claim_per_token", max_reward_attribute)
+    )
+}
+
+// set the minimum balance of the rewards pool to stake.
+// new nfts are not staked while the rewards pool is below the minimum.
+// if it is none, only the empty rewards pool is rejected.
+pub fn set_min_pool_balance_to_stake(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    min_pool_balance_to_stake: Option<u128>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    match min_pool_balance_to_stake {
+        Some(t) => MIN_POOL_BALANCE_TO_STAKE.save(deps.storage, &t)?,
+        None => MIN_POOL_BALANCE_TO_STAKE.remove(deps.storage),
+    }
+
+    let min_pool_balance_attribute = match min_pool_balance_to_stake {
+        Some(t) => t.to_string(),
+        None => "none".to_string(),
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "set_min_pool_balance_to_stake")
+        .add_attribute("min_pool_balance_to_stake", min_pool_balance_attribute)
+    )
+}
+
+// set the period which rewards emission is ended.
+// rewards are accrued until the previous period of the emission end period.
+// if it is none, rewards emission is not ended.
+pub fn set_emission_end_period(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    new_emission_end_period: Option<u64>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    match new_emission_end_period {
+        Some(t) => EMISSION_END_PERIOD.save(deps.storage, &t)?,
+        None => EMISSION_END_PERIOD.remove(deps.storage),
+    }
+    increase_config_version(deps.branch())?;
+
+    let emission_end_attribute = match new_emission_end_period {
+        Some(t) => t.to_string(),
+        None => "none".to_string(),
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "set_emission_end_period")
+        .add_attribute("new_emission_end_period", emission_end_attribute)
+    )
+}
+
+// set the withdraw timelock in seconds, the rewards pool is withdrawn immediately if it is not set.
+// if it is set, withdrawals are requested and executed after the timelock, so stakers are able to exit before.
+// the withdraw timelock is only able to be increased once it is set, otherwise the owner could remove it to drain the pool.
+pub fn set_withdraw_timelock_seconds(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    new_withdraw_timelock_seconds: Option<u64>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    let current = WITHDRAW_TIMELOCK_SECONDS.may_load(deps.storage)?;
+    if let Some(current) = current {
+        if new_withdraw_timelock_seconds.unwrap_or(0) < current {
+            return Err(ContractError::WithdrawTimelockDecreased { current })
+        }
+    }
+
+    let withdraw_timelock_attribute = match new_withdraw_timelock_seconds {
+        Some(t) => {
+            WITHDRAW_TIMELOCK_SECONDS.save(deps.storage, &t)?;
+            t.to_string()
+        },
+        None => "none".to_string(),
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "set_withdraw_timelock_seconds")
+        .add_attribute("new_withdraw_timelock_seconds", withdraw_timelock_attribute)
+    )
+}
+
+// set the emission mode of rewards.
+// in the shared per period mode, the reward per period is split among the staked nfts of each period.
+// the reward per period is removed in the flat mode and the rewards schedule is used.
+pub fn set_emission_mode(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    emission_mode: EmissionMode,
+    reward_per_period: Option<u128>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    if emission_mode == EmissionMode::SharedPerPeriod {
+        if reward_per_period.is_none() {
+            return Err(ContractError::EmptyRewardPerPeriod {})
+        }
+        REWARD_PER_PERIOD.save(deps.storage, &reward_per_period.unwrap())?;
+    } else {
+        REWARD_PER_PERIOD.remove(deps.storage);
+    }
+    EMISSION_MODE.save(deps.storage, &emission_mode)?;
+    increase_config_version(deps.branch())?;
+
+    let reward_per_period_attribute = match REWARD_PER_PERIOD.may_load(deps.storage)? {
+        Some(t) => t.to_string(),
+        None => "none".to_string(),
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "set_emission_mode")
+        .add_attribute("emission_mode", format!("{:?}", emission_mode))
+        .add_attribute("reward_per_period", reward_per_period_attribute)
+    )
+}
+
+// set the pool constrained emission, it is only able to be set before start.
+// the budget of the emission ledger starts from the rewards token balance of the nft staking contract.
+pub fn set_pool_constrained_emission(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    pool_constrained_emission: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env.clone(), config.clone())?;
+
+    if START_TIMESTAMP.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::AlreadyStarted {})
+    }
+
+    let mut budget: u128 = 0;
+    if pool_constrained_emission {
+        budget = query_rewards_token_balance(deps.as_ref(), env.contract.address.to_string(), config.rewards_token_contract)?.balance.u128();
+        EMISSION_LEDGER.save(deps.storage, &EmissionLedger { processed_period: 0, budget })?;
+    } else {
+        EMISSION_LEDGER.remove(deps.storage);
+    }
+    POOL_CONSTRAINED_EMISSION.save(deps.storage, &pool_constrained_emission)?;
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_pool_constrained_emission")
+        .add_attribute("pool_constrained_emission", pool_constrained_emission.to_string())
+        .add_attribute("emission_budget", budget.to_string())
+    )
+}
+
+// set the minimum cycles between the deposit cycle and the cycle of unstake.
+// the value is at least 1, but the value 1 allows the rewards of a full cycle by staking for a few seconds.
+pub fn set_min_unstake_cycles(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    new_min_unstake_cycles: u64,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info, env, config)?;
+
+    if new_min_unstake_cycles == 0 {
+        return Err(ContractError::InvalidMinUnstakeCycles {})
+    }
+    MIN_UNSTAKE_CYCLES.save(deps.storage, &new_min_unstake_cycles)?;
+    increase_config_version(deps.branch())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_min_unstake_cycles")
+        .add_attribute("new_min_unstake_cycles", new_min_unstake_cycles.to_string())
+    )
+}
+
+// nft staking contract start.
+// every calculating period and cycle are affected by start timestamp.
+pub fn start(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    idempotent: Option<bool>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    // the idempotent start returns the existing start timestamp for deployment scripts which ensure the start.
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if let Some(start_timestamp) = start_timestamp {
+        if idempotent.unwrap_or(false) {
+            return Ok(Response::new()
+                .add_attribute("method", "start")
+                .add_attribute("start_time_stamp", start_timestamp.to_string())
+                .add_attribute("already_started", true.to_string())
+            )
+        }
+        return Err(ContractError::AlreadyStarted {})
+    }
+    let now = env.block.time.seconds();
+    
+    START_TIMESTAMP.save(deps.storage, &now)?;
+
+    // the prestaked nfts are recorded as the staked count of the first period.
+    let number_of_staked_nfts = NUMBER_OF_STAKED_NFTS.load(deps.storage)?;
+    STAKED_COUNT_HISTORY.save(deps.storage, 1, &number_of_staked_nfts)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "start")
+        .add_attribute("start_time_stamp", now.to_string())
+    )
+}
+
+// nft staking contract halt.
+// after disabled, functions are stop.
+pub fn disable(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    DISABLE.save(deps.storage, &true)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "disable")
+        .add_attribute("disable", true.to_string())
+    )
+}
+
+// if the nft staking contract is disabled and the contract owner want to activate again, 
+// execute enable function.
+pub fn enable(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    let disable = DISABLE.load(deps.storage)?;
+    if !disable {
+        return Err(ContractError::CannotEnable { disable })
+    }
+
+    // not to re-open the contract into a broken state, the token contracts are probed again.
+    if VALIDATE_CONTRACTS.may_load(deps.storage)?.unwrap_or(false) {
+        validate_token_contracts(deps.as_ref(), config.clone())?;
+    }
+
+    DISABLE.save(deps.storage, &!disable)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "enable")
+        .add_attribute("previous_disable_state", disable.to_string())
+        .add_attribute("now_disable_state", (!disable).to_string())
+    )
+}
+
+// set the disable state to the requested state.
+// unlike disable and enable, it is idempotent and does not return error when the state is already requested state.
+pub fn set_disabled(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    disabled: bool,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    let disable = DISABLE.load(deps.storage)?;
+
+    // same as enable, the token contracts are probed before re-opening the contract.
+    if disable && !disabled && VALIDATE_CONTRACTS.may_load(deps.storage)?.unwrap_or(false) {
+        validate_token_contracts(deps.as_ref(), config.clone())?;
+    }
+
+    DISABLE.save(deps.storage, &disabled)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_disabled")
+        .add_attribute("sender", info.sender.to_string())
+        .add_attribute("previous_disable_state", disable.to_string())
+        .add_attribute("now_disable_state", disabled.to_string())
+    )
+}
+
+// withdraw rewards pool.
+// the nft staking contract's balances of token rewards which is value of requested amount are transferred to contract owner.
+pub fn withdraw_rewards_pool(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    amount: u128,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+    check_withdraw_timelock(deps.as_ref())?;
+
+    let disabled = check_disable(deps.branch())?;
+    let rewards_token_contract = config.clone().rewards_token_contract;
+    let recipient = withdraw_recipient(deps.as_ref(), info.clone(), recipient)?;
+
+    check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_INSUFFICIENT, Some(amount))?;
+    settle_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+    update_emission_budget(deps.branch(), 0, amount)?;
+    let message = execute_token_contract_transfer(rewards_token_contract, recipient.clone(), amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "withdraw_rewards_pool")
+        .add_attribute("disable", disabled.to_string())
+        .add_attribute("rewards_token_contract", config.rewards_token_contract)
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("recipient", recipient)
+        .add_attribute("withdraw_amount", amount.to_string())
+        .add_messages(message)
+    )
+}
+
+// withdraw all rewards pool.
+// the nft staking contract's all balances are transferred to contract owner,
+// or to the recipient when it is set.
+pub fn withdraw_all_rewards_pool(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+    check_withdraw_timelock(deps.as_ref())?;
+
+    let disabled = check_disable(deps.branch())?;
+    let rewards_token_contract = config.clone().rewards_token_contract;
+    let recipient = withdraw_recipient(deps.as_ref(), info.clone(), recipient)?;
+    let address = env.contract.address.to_string();
+
+    // nft staking contract balances
+    let balance_response = query_rewards_token_balance(deps.as_ref(), address.clone(), rewards_token_contract.clone())?;
+    let amount = balance_response.balance.u128();
+
+    settle_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+    update_emission_budget(deps.branch(), 0, amount)?;
+    let message = execute_token_contract_transfer(rewards_token_contract, recipient.clone(), amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "withdraw_all_rewards_pool")
+        .add_attribute("disable", disabled.to_string())
+        .add_attribute("rewards_token_contract", config.rewards_token_contract)
+        .add_attribute("nft_staking_contract", address)
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("recipient", recipient)
+        .add_attribute("withdraw_amount", amount.to_string())
+        .add_messages(message)
+    )
+}
+
+// remove next claims and staker histories which have no token info staked by the staker.
+// they are found by the orphaned_claims query. next claims after start_after are scanned up to limit
+// (at most MAX_PRUNE_ORPHANED_CLAIMS) per call, and the owner continues with the returned next start after.
+pub fn prune_orphaned_claims(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env, config)?;
+
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_ORPHANED_CLAIMS).min(MAX_PRUNE_ORPHANED_CLAIMS) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let scanned_keys: Vec<String> = NEXT_CLAIMS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .filter_map(|item| item.ok())
+        .collect();
+
+    let mut next_start_after: Option<String> = None;
+    if scanned_keys.len() == limit {
+        next_start_after = scanned_keys.last().cloned();
+    }
+
+    let keys: Vec<String> = scanned_keys
+        .into_iter()
+        .filter(|key| is_orphaned_claim(deps.as_ref(), key).unwrap_or(false))
+        .collect();
+
+    let mut response = Response::new()
+        .add_attribute("method", "prune_orphaned_claims");
+    for key in keys.iter() {
+        NEXT_CLAIMS.remove(deps.storage, key.clone());
+        STAKER_HISTORIES.remove(deps.storage, key.clone());
+        response = response.add_attribute("pruned_staker_tokenid_key", key.clone());
+    }
+
+    Ok(response
+        .add_attribute("pruned_claims", keys.len().to_string())
+        .add_attribute("next_start_after", next_start_after.unwrap_or_else(|| "none".to_string()))
+    )
+}
+
+// index the token ids of the token infos which are saved before the per-staker index exists.
+// token infos after start_after are scanned up to limit (at most MAX_INDEX_STAKER_TOKENS) per call,
+// and the owner continues with the returned next start after.
+pub fn index_staker_tokens(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env, config)?;
+
+    let limit = limit.unwrap_or(DEFAULT_INDEX_STAKER_TOKENS).min(MAX_INDEX_STAKER_TOKENS) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let scanned_tokens: Vec<(String, TokenInfo)> = TOKEN_INFOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(String, TokenInfo)>>>()?;
+
+    let mut next_start_after: Option<String> = None;
+    if scanned_tokens.len() == limit {
+        next_start_after = scanned_tokens.last().map(|(token_id, _)| token_id.clone());
+    }
+
+    let mut indexed_tokens: usize = 0;
+    for (token_id, token_info) in scanned_tokens {
+        if token_info.owner.is_empty() {
+            continue
+        }
+        STAKER_TOKENS.save(deps.storage, (token_info.owner, token_id), &true)?;
+        indexed_tokens += 1;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "index_staker_tokens")
+        .add_attribute("indexed_tokens", indexed_tokens.to_string())
+        .add_attribute("next_start_after", next_start_after.unwrap_or_else(|| "none".to_string()))
+    )
+}
+
+// decide the completed periods of the pool constrained emission which are not decided yet.
+// anyone is able to execute it, at most MAX_EMISSION_LEDGER_PERIODS periods are decided per call,
+// and it is repeated until the processed period reaches the period before the current period.
+pub fn process_emission_ledger(
+    mut deps: DepsMut,
+    env: Env,
+    config: Config,
+) -> Result<Response, ContractError> {
+    if !POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::PoolConstrainedEmissionDisabled {})
+    }
+    let start_timestamp = check_start_timestamp(deps.branch())?;
+
+    advance_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+    let ledger = EMISSION_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+    let current_period = get_current_period(env.block.time.seconds(), start_timestamp, config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "process_emission_ledger")
+        .add_attribute("processed_period", ledger.processed_period.to_string())
+        .add_attribute("current_period", current_period.to_string())
+        .add_attribute("budget", ledger.budget.to_string())
+    )
+}
+
+// forfeit the deferred rewards of the unbonded tokens which are not claimed for long.
+// the token is swept if its requested unbond time is older than older_than_periods periods, 
+// the forfeited rewards stay in the rewards pool and the swept token is reset as the unstaked token.
+// token infos after start_after are scanned up to limit (at most MAX_SWEEP_ABANDONED_REWARDS) per call,
+// and the owner continues with the returned next start after.
+pub fn sweep_abandoned_rewards(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    older_than_periods: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env.clone(), config.clone())?;
+    let start_timestamp = check_start_timestamp(deps.branch())?;
+
+    let now = env.block.time.seconds();
+    let current_period = get_current_period(now, start_timestamp, config.clone())?;
+    let limit = limit.unwrap_or(DEFAULT_SWEEP_ABANDONED_REWARDS).min(MAX_SWEEP_ABANDONED_REWARDS) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let scanned_tokens: Vec<(String, TokenInfo)> = TOKEN_INFOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .filter_map(|item| item.ok())
+        .collect();
+
+    let mut next_start_after: Option<String> = None;
+    if scanned_tokens.len() == limit {
+        next_start_after = scanned_tokens.last().map(|(token_id, _)| token_id.clone());
+    }
+
+    let mut response = Response::new()
+        .add_attribute("method", "sweep_abandoned_rewards");
+    let mut swept_tokens: usize = 0;
+    let mut forfeited_rewards: u128 = 0;
+    for (token_id, token_info) in scanned_tokens {
+        if token_info.bond_status != UNBONDED || token_info.owner.is_empty() {
+            continue
+        }
+
+        let unbond_period = get_current_period(token_info.req_unbond_time, start_timestamp, config.clone())?;
+        if current_period.saturating_sub(unbond_period) <= older_than_periods {
+            continue
+        }
+
+        let staker_tokenid_key = staker_tokenid_key(token_info.clone().owner, token_id.clone());
+        if !NEXT_CLAIMS.has(deps.storage, staker_tokenid_key.clone()) {
+            continue
+        }
+
+        let (forfeit_amount, _) = compute_remain_rewards(deps.branch(), staker_tokenid_key.clone(), now, start_timestamp, config.clone(), token_id.clone())?;
+        if forfeit_amount == 0 {
+            continue
+        }
+
+        // the swept token is cleared as same as the unbonded token whose deferred rewards are claimed.
+        NEXT_CLAIMS.remove(deps.storage, staker_tokenid_key.clone());
+        STAKER_HISTORIES.remove(deps.storage, staker_tokenid_key);
+        let token_info_unstaked = TokenInfo::unstake(false, token_info.clone().deposit_cycle, token_info.clone().withdraw_cycle, token_info.staked_nft_contract(config.clone()));
+        save_token_info(deps.storage, token_id.clone(), &token_info_unstaked)?;
+
+        swept_tokens += 1;
+        forfeited_rewards += forfeit_amount;
+        response = response
+            .add_attribute("swept_token_id", token_id)
+            .add_attribute("forfeit_amount", forfeit_amount.to_string());
+    }
+
+    Ok(response
+        .add_attribute("swept_tokens", swept_tokens.to_string())
+        .add_attribute("forfeited_rewards", forfeited_rewards.to_string())
+        .add_attribute("next_start_after", next_start_after.unwrap_or_else(|| "none".to_string()))
+    )
+}
+
+// request the withdrawal of rewards pool when the withdraw timelock is set.
+// the withdrawal is ready after the timelock and only one withdrawal is able to be pending.
+pub fn request_withdraw(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    amount: u128,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    let withdraw_timelock_seconds = WITHDRAW_TIMELOCK_SECONDS.may_load(deps.storage)?;
+    if withdraw_timelock_seconds.is_none() {
+        return Err(ContractError::WithdrawTimelockNotSet {})
+    }
+
+    if let Some(pending_withdrawal) = PENDING_WITHDRAWAL.may_load(deps.storage)? {
+        return Err(ContractError::WithdrawalAlreadyPending { ready_at: pending_withdrawal.ready_at })
+    }
+
+    check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_INSUFFICIENT, Some(amount))?;
+    let recipient = withdraw_recipient(deps.as_ref(), info.clone(), recipient)?;
+
+    let requested_at = env.block.time.seconds();
+    let ready_at = requested_at + withdraw_timelock_seconds.unwrap();
+    PENDING_WITHDRAWAL.save(deps.storage, &PendingWithdrawal {
+        amount,
+        recipient: recipient.clone(),
+        requested_at,
+        ready_at,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "request_withdraw")
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("recipient", recipient)
+        .add_attribute("withdraw_amount", amount.to_string())
+        .add_attribute("ready_at", ready_at.to_string())
+    )
+}
+
+// execute the pending withdrawal of rewards pool after the withdraw timelock.
+pub fn execute_withdraw(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env.clone(), config.clone())?;
+
+    let pending_withdrawal = PENDING_WITHDRAWAL.may_load(deps.storage)?.ok_or(ContractError::NoPendingWithdrawal {})?;
+    if env.block.time.seconds() < pending_withdrawal.ready_at {
+        return Err(ContractError::WithdrawTimelockNotEnded { ready_at: pending_withdrawal.ready_at })
+    }
+
+    check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_INSUFFICIENT, Some(pending_withdrawal.amount))?;
+    PENDING_WITHDRAWAL.remove(deps.storage);
+    settle_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+    update_emission_budget(deps.branch(), 0, pending_withdrawal.amount)?;
+
+    let message = execute_token_contract_transfer(config.clone().rewards_token_contract, pending_withdrawal.recipient.clone(), pending_withdrawal.amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_withdraw")
+        .add_attribute("rewards_token_contract", config.rewards_token_contract)
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("recipient", pending_withdrawal.recipient)
+        .add_attribute("withdraw_amount", pending_withdrawal.amount.to_string())
+        .add_messages(message)
+    )
+}
+
+// cancel the pending withdrawal of rewards pool.
+pub fn cancel_withdraw(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+) -> Result<Response, ContractError> {
+    check_contract_owner(deps.branch(), info.clone(), env, config)?;
+
+    let pending_withdrawal = PENDING_WITHDRAWAL.may_load(deps.storage)?.ok_or(ContractError::NoPendingWithdrawal {})?;
+    PENDING_WITHDRAWAL.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "cancel_withdraw")
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("canceled_amount", pending_withdrawal.amount.to_string())
+    )
+}
+
+// set the total rewards pool to the actual rewards token balance of the nft staking contract.
+// they are diverged by the transfer tax or direct transfers which do not pass add_rewards_pool.
+// the contract must be disabled to avoid the reconciliation during claims and unstakes.
+pub fn sync_rewards_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env.clone(), config.clone())?;
+
+    if !DISABLE.load(deps.storage)? {
+        return Err(ContractError::NotDisabled {})
+    }
+
+    let before = TOTAL_REWARDS_POOL.may_load(deps.storage)?.unwrap_or(0);
+    let balance_response = query_rewards_token_balance(deps.as_ref(), env.contract.address.to_string(), config.rewards_token_contract)?;
+    let after = balance_response.balance.u128();
+    TOTAL_REWARDS_POOL.save(deps.storage, &after)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "sync_rewards_pool")
+        .add_attribute("total_rewards_pool_before", before.to_string())
+        .add_attribute("total_rewards_pool_after", after.to_string())
+    )
+}
+
+// staking nft.
+// the staker can stake nft as cw721.
+pub fn stake_nft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    config: Config,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // the nft of the booster contract is staked as the booster.
+    if config.booster_contract.is_some() && info.sender == config.clone().booster_contract.unwrap() {
+        return stake_booster(deps, env, msg)
+    }
+
+    // check empty total supply rewards pool.
+    let total_rewards_pool = TOTAL_REWARDS_POOL.may_load(deps.branch().storage)?;
+    if total_rewards_pool.is_none() {
+        return Err(ContractError::EmptyRewardsPool {})
+    }
+
+    // check empty rewards pool of nft staking contract.
+    check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_EMPTY, None)?;
+    check_min_pool_balance_to_stake(deps.as_ref(), env.clone(), config.clone())?;
+    advance_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+
+    // check rewards schedule.
+    let rewards_schedule = REWARDS_SCHEDULE.may_load(deps.branch().storage)?;
+    if rewards_schedule.is_none() {
+        return Err(ContractError::NoneRewardsSchedule {})
+    }
+
+    // check the nft must be sended from whitelisted nft contract.
+    if info.sender != config.clone().white_listed_nft_contract {
+        return Err(ContractError::InvalidWhitelistedContract { 
+            white_listed_contract: config.clone().white_listed_nft_contract, 
+            requester: info.sender.to_string() 
+        })
+    }
+
+    // if prestake is allowed, the nft can be staked before start.
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    let prestake = start_timestamp.is_none();
+    if prestake && !ALLOW_PRESTAKE.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::NotStarted {})
+    }
+    check_disable(deps.branch())?;
+
+    let token_id = msg.token_id;
+    if BLOCKED_TOKENS.may_load(deps.storage, token_id.clone())?.unwrap_or(false) {
+        return Err(ContractError::TokenBlocked {})
+    }
+
+    let send_nft_msg = msg.msg;
+    let staker = stake_owner(deps.as_ref(), msg.sender, send_nft_msg.clone())?;
+    let timestamp = env.block.time.seconds();
+
+    // the deposit of prestaked nft is deferred to the first cycle, so accrual begins at start.
+    let mut current_cycle = 1;
+    if !prestake {
+        current_cycle = get_cycle(timestamp, start_timestamp.unwrap(), config.clone())?;
+    }
+
+    // the nft staked after the emission end period never accrues rewards.
+    let emission_end_period = EMISSION_END_PERIOD.may_load(deps.storage)?;
+    if let Some(emission_end_period) = emission_end_period {
+        if get_period(current_cycle, config.clone())? >= emission_end_period {
+            return Err(ContractError::EmissionEnded { emission_end_period })
+        }
+    }
+
+    // the nft for staking is managed by mapping staker's address and nft token ID.
+    // the staker stakes multi nft and can claim rewards for each nft.
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+    let token_infos = TOKEN_INFOS.may_load(deps.branch().storage, token_id.clone())?;
+    let mut deferred_rewards: u128 = 0;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut events = vec![];
+    if let Some(token_infos) = token_infos {
+
+        // prevent duplication.
+        if token_infos.is_staked {
+            return Err(ContractError::AlreadyStaked {})
+        }
+        let withdraw_cycle = token_infos.withdraw_cycle;
+
+        // cannot re-stake when current cycle of block time is same setup withdraw cycle
+        if current_cycle == withdraw_cycle {
+            return Err(ContractError::UnstakedTokenCooldown {})
+        }    
+
+        // the previous staker did not claim the deferred rewards yet.
+        (deferred_rewards, messages, events) = settle_deferred_rewards(deps.branch(), env.clone(), config.clone(), token_id.clone(), token_infos, staker.clone())?;
+    }
+
+    let update_histories_response = update_histories(deps.branch(), staker_tokenid_key.clone(), IS_STAKED, current_cycle)?;
+
+    let next_claims = NEXT_CLAIMS.may_load(deps.branch().storage, staker_tokenid_key.clone())?;
+
+    // initialise the next claim if it was the first stake for this staker or if 
+    // the next claim was re-initialised.
     // i.e. rewards were claimed until the last staker snapshot and the last staker snapshot is not staked.
     if next_claims.is_none() {
         let current_period = get_period(current_cycle, config.clone())?;
         let new_next_claim = NextClaim::new(current_period, 0);
 
-        NEXT_CLAIMS.save(deps.branch().storage, staker_tokenid_key.clone(), &new_next_claim)?;
+        NEXT_CLAIMS.save(deps.branch().storage, staker_tokenid_key.clone(), &new_next_claim)?;
+    }
+
+    let nft_contract = info.sender.to_string();
+    let new_token_info = TokenInfo::stake(staker.clone(), IS_STAKED, current_cycle, nft_contract.clone());
+    
+    save_token_info(deps.branch().storage, token_id.clone(), &new_token_info)?;
+    manage_number_nfts(deps.branch(), staker.clone(), nft_contract, true, env.block.time.seconds());
+
+    Ok(Response::new()
+        .add_attribute("method", "stake_nft")
+        .add_attribute("nft_owner", staker)
+        .add_attribute("current_cycle", current_cycle.to_string())
+        .add_attribute("prestake", prestake.to_string())
+        .add_attribute("staker_histories_stake", update_histories_response.staker_histories_stake.to_string())
+        .add_attribute("nft_exist", new_token_info.is_staked.to_string())
+        .add_attribute("send_nft_message", send_nft_msg.to_string())
+        .add_attribute("deferred_rewards", deferred_rewards.to_string())
+        .add_messages(messages)
+        .add_events(events)
+    )
+}
+
+// staking multiple nfts of the whitelisted nft contract by a transaction.
+// the staker approves the nft staking contract as the operator of the nft contract before,
+// and the nfts are transferred from the staker to the nft staking contract after the bookkeeping of stake_nft.
+// the owner of every nft is checked because the operator approval of other owners also allows the transfer.
+pub fn stake_many(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    config: Config,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    if token_ids.is_empty() {
+        return Err(ContractError::EmptyTokenIds {})
+    }
+    if token_ids.len() > MAX_STAKE_MANY_TOKENS {
+        return Err(ContractError::TooManyTokenIds { max: MAX_STAKE_MANY_TOKENS as u64 })
+    }
+
+    let staker = info.sender.to_string();
+    let nft_contract = config.clone().white_listed_nft_contract;
+    let nft_contract_info = MessageInfo { 
+        sender: Addr::unchecked(nft_contract.clone()), 
+        funds: vec![],
+    };
+
+    for token_id in token_ids.clone() {
+        let nft_owner = query_nft_owner(deps.as_ref(), nft_contract.clone(), token_id)?.owner;
+        if nft_owner != staker {
+            return Err(ContractError::InvalidNftOwner { 
+                requester: staker, 
+                nft_owner,
+            })
+        }
     }
 
-    let new_token_info = TokenInfo::stake(staker.clone(), IS_STAKED, current_cycle);
-    
-    TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &new_token_info)?;
-    manage_number_nfts(deps.branch(), true);
+    let mut response = Response::new()
+        .add_attribute("method", "stake_many")
+        .add_attribute("nft_owner", staker.clone());
+    for token_id in token_ids {
+        let msg = Cw721ReceiveMsg {
+            sender: staker.clone(),
+            token_id: token_id.clone(),
+            msg: to_binary("stake many")?,
+        };
+        let stake_response = stake_nft(deps.branch(), env.clone(), nft_contract_info.clone(), config.clone(), msg)?;
+        let message = execute_transfer_nft_stake(token_id.clone(), env.contract.address.to_string(), nft_contract.clone())?;
+
+        response = response
+            .add_attribute("staked_token_id", token_id)
+            .add_message(message)
+            .add_submessages(stake_response.messages)
+            .add_events(stake_response.events);
+    }
 
-    Ok(Response::new()
-        .add_attribute("method", "stake_nft")
-        .add_attribute("nft_owner", staker)
-        .add_attribute("current_cycle", current_cycle.to_string())
-        .add_attribute("staker_histories_stake", update_histories_response.staker_histories_stake.to_string())
-        .add_attribute("nft_exist", new_token_info.is_staked.to_string())
-        .add_attribute("send_nft_message", send_nft_msg.to_string())
-    )
+    Ok(response)
 }
 
 // unstaking nft
 // the staker can unbond the nft as cw721.
+// if claim_on_unstake is false, rewards are not paid out when the nft is unstaked.
+// the next claim remains and the staker claims rewards until the requested unbond time by claim_rewards later.
 pub fn unstake_nft(
     mut deps: DepsMut,
     env: Env,
@@ -508,121 +1878,233 @@ pub fn unstake_nft(
     config: Config,
     token_id: String,
     claim_recipient_address: Option<String>,
+    claim_on_unstake: Option<bool>,
 ) -> Result<Response, ContractError> {
     let staker = info.clone().sender.to_string();
     let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
     let token_info = TokenInfo::check_staker(deps.branch(), info.clone(), token_id.clone())?;
+    if token_info.burned {
+        return Err(ContractError::TokenBurned {})
+    }
 
     let start_timestamp = check_start_timestamp(deps.branch())?;
     let timestamp = env.block.time.seconds();
     let disable = check_disable(deps.branch())?;
+    settle_emission_ledger(deps.branch(), timestamp, config.clone())?;
     let is_staked = token_info.clone().is_staked;
+    let nft_contract = token_info.staked_nft_contract(config.clone());
     let mut messages: Vec<CosmosMsg> = vec![];
 
     // the bond status of requested nft that is "BONDED" is replaced to "UNBONDING".
+    // next claims are not touched, the requested unstake time is the checkpoint that compute_rewards caps the rewards at.
     if token_info.bond_status == BONDED {
         let token_info_unbonding = TokenInfo::unstake_unbonding(
             staker.clone(), 
             is_staked, 
             token_info.clone().deposit_cycle, 
             token_info.clone().withdraw_cycle,
-            timestamp.clone(),
+            timestamp,
+            nft_contract.clone(),
         );
-        TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &token_info_unbonding)?;
+        save_token_info(deps.branch().storage, token_id.clone(), &token_info_unbonding)?;
 
         return Ok(Response::new()
             .add_attribute("method", "unstake_nft")
             .add_attribute("request_unstake_time", timestamp.to_string())
             .add_attribute("bond_status", UNBONDING)
+            .add_attribute("phase", UNSTAKE_PHASE_START_UNBONDING)
+            .add_attribute("token_id", token_id.clone())
+            .add_attribute("owner", staker.clone())
+            .add_event(unstake_phase_event(UNSTAKE_PHASE_START_UNBONDING, token_id, staker))
         )
     }
 
     // the nft actually is unstaked that nft owner is changed to the staker, 
     // if the bond status of the nft is "UNBONDING" and current timestamp is bigger than 
     // sum of requsted unstake time and unbonding duration that is already set up.
-    check_unbonding_end(deps.as_ref(), token_info.clone(), timestamp.clone())?; 
+    check_unbonding_end(deps.as_ref(), token_info.clone(), timestamp)?; 
 
     let current_cycle = get_cycle(timestamp, start_timestamp, config.clone())?;
 
     // before unstake the nft by staker, rewards token balances are transfer to staker.
-    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.branch().storage)?;
-    let mut remain_rewards = true;
     let mut remain_rewards_value: u128 = 0;
-    let mut recipient: Option<String> = Some(staker.clone());
-    if !claim_recipient_address.is_none() {
-        recipient = claim_recipient_address;
-    }
+    let mut secondary_rewards_value: u128 = 0;
+    let mut events = vec![];
+    let recipient = claim_recipient(deps.as_ref(), env.clone(), staker_tokenid_key.clone(), staker.clone(), claim_recipient_address)?;
+    let mut claim_on_unstake = claim_on_unstake.unwrap_or(true);
 
     if !disable {
         // ensure that at least an entire cycle has elapsed before unstaking the token to avoid
         // an exploit where a full cycle would be claimable if staking just before the end
         // of a cycle and unstaking right after start of the new cycle.
-        if !(current_cycle - token_info.clone().deposit_cycle >= 2) {
+        // the deposit cycle is rewarded entirely, so the staker who stakes at the last second of cycle N
+        // and unstakes at the first second of cycle N+1 gets rewards of the full cycle N.
+        // with the default 2, the token cannot be unstaked until cycle N+2, it is staked during the entire cycle N+1.
+        // the difference is compared by cycles, not the elapsed seconds.
+        let min_unstake_cycles = MIN_UNSTAKE_CYCLES.may_load(deps.storage)?.unwrap_or(DEFAULT_MIN_UNSTAKE_CYCLES);
+        if current_cycle - token_info.clone().deposit_cycle < min_unstake_cycles {
             return Err(ContractError::TokenSteelFrozen {})
         }
 
+        // remain rewards cannot be paid before the claim cliff, so they are deferred and claimed after the cliff.
+        if claim_on_unstake && check_claim_cliff(deps.as_ref(), timestamp, start_timestamp, config.clone()).is_err() {
+            let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+            let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), max_compute_period, timestamp, start_timestamp, config.clone(), token_id.clone())?;
+            if claim.amount != 0 {
+                claim_on_unstake = false;
+            }
+        }
+
+        // rewards are deferred, they are claimed later by claim_rewards.
+        if !claim_on_unstake {
+            update_histories(deps.branch(), staker_tokenid_key.clone(), !is_staked, current_cycle)?;
+
+            // the unbonded token keeps the owner and the requested unbond time to cap the deferred rewards.
+            let token_info_unbonded = TokenInfo::unstake_unbonded(
+                staker.clone(), 
+                !is_staked, 
+                token_info.clone().deposit_cycle, 
+                current_cycle,
+                token_info.clone().req_unbond_time,
+                nft_contract.clone(),
+            );
+            save_token_info(deps.branch().storage, token_id.clone(), &token_info_unbonded)?;
+            manage_number_nfts(deps.branch(), staker.clone(), nft_contract.clone(), false, env.block.time.seconds());
+            increase_counter(deps.branch(), TOTAL_UNSTAKES)?;
+
+            return Ok(Response::new()
+                .add_attribute("method", "unstake_nft")
+                .add_attribute("request_unstake_time", timestamp.to_string())
+                .add_attribute("claim_on_unstake", claim_on_unstake.to_string())
+                .add_attribute("claim_remain_rewards", "0")
+                .add_attribute("phase", UNSTAKE_PHASE_FINALIZED)
+                .add_attribute("token_id", token_id.clone())
+                .add_attribute("owner", staker.clone())
+                .add_message(execute_transfer_nft_unstake(token_id.clone(), staker.clone(), nft_contract)?)
+                .add_event(unstake_phase_event(UNSTAKE_PHASE_FINALIZED, token_id, staker))
+            )
+        }
+
+        // remain rewards are not paid on unstake, the staker claims them by claim_rewards before requesting unbonding.
+        // the unbonding token cannot be claimed, so the staker who did not claim unstakes with deferred claim instead.
+        if REQUIRE_CLAIM_BEFORE_UNSTAKE.may_load(deps.storage)?.unwrap_or(false) {
+            let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+            let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), max_compute_period, timestamp, start_timestamp, config.clone(), token_id.clone())?;
+            if claim.amount != 0 {
+                return Err(ContractError::ClaimBeforeUnstake {})
+            }
+        }
+
         let token_info_unbonded = TokenInfo::unstake_unbonded(
             staker.clone(), 
             is_staked, 
             token_info.clone().deposit_cycle, 
             token_info.clone().withdraw_cycle,
             token_info.clone().req_unbond_time,
+            nft_contract.clone(),
         );
-        TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &token_info_unbonded)?;
-
-        while remain_rewards {
-            let compute_reward = compute_rewards(
-                deps.as_ref(), 
-                staker_tokenid_key.clone(), 
-                max_compute_period,
-                timestamp,
-                start_timestamp,
-                config.clone(),
-                token_id.clone()
-            )?;
-
-            if compute_reward.0.amount != 0 {
-                remain_rewards_value = remain_rewards_value + compute_reward.0.amount;
-                // next claim set last computed rewards.
-                NEXT_CLAIMS.save(deps.branch().storage, staker_tokenid_key.clone(), &compute_reward.1)?;
-            } else {
-                remain_rewards = false
-            }
-        }
+        save_token_info(deps.branch().storage, token_id.clone(), &token_info_unbonded)?;
+
+        (remain_rewards_value, secondary_rewards_value) = compute_remain_rewards(deps.branch(), staker_tokenid_key.clone(), timestamp, start_timestamp, config.clone(), token_id.clone())?;
         update_histories(deps.branch(), staker_tokenid_key.clone(), !is_staked, current_cycle)?;
 
         // clear the token owner to ensure it cannot be unstaked again without being re-staked.
         // set the withdrawal cycle to ensure it cannot be re-staked during the same cycle.
-        let token_info = TokenInfo::unstake(!is_staked, token_info.clone().deposit_cycle, current_cycle);
+        let token_info = TokenInfo::unstake(!is_staked, token_info.clone().deposit_cycle, current_cycle, nft_contract.clone());
 
-        TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &token_info)?;
+        save_token_info(deps.branch().storage, token_id.clone(), &token_info)?;
     }
 
     if remain_rewards_value != 0 {
         // check empty and sufficient rewards pool of nft staking contract.
         // for checking sufficient rewards pool, must input amount.
-        check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_BOTH, Some(remain_rewards_value.clone()))?;
-        let claim_message = execute_token_contract_transfer(config.clone().rewards_token_contract, recipient.clone().unwrap(), remain_rewards_value.clone())?;
-        let claim_cosmos_msg = claim_message
-            .get(0)
-            .unwrap()
-            .clone();
+        let mut claim_message = execute_rewards_transfer(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_BOTH, recipient.clone(), remain_rewards_value, secondary_rewards_value)?;
+        events = check_auto_disable(deps.branch(), env.clone(), config.clone(), remain_rewards_value - secondary_rewards_value)?;
+        increase_total_paid_out(deps.branch(), remain_rewards_value)?;
 
-        messages.push(claim_cosmos_msg)
+        messages.append(&mut claim_message)
     }
     
     // next claims of specified nft are eliminated.
     NEXT_CLAIMS.remove(deps.branch().storage, staker_tokenid_key.clone());
-    manage_number_nfts(deps.branch(), false);
+    manage_number_nfts(deps.branch(), staker.clone(), nft_contract.clone(), false, env.block.time.seconds());
+    increase_counter(deps.branch(), TOTAL_UNSTAKES)?;
 
-    messages.push(execute_transfer_nft_unstake(token_id, staker, config.white_listed_nft_contract)?);
+    messages.push(execute_transfer_nft_unstake(token_id.clone(), staker.clone(), nft_contract)?);
 
     Ok(Response::new()
         .add_attribute("method", "unstake_nft")
         .add_attribute("request_unstake_time", timestamp.to_string())
         .add_attribute("claim_remain_rewards", remain_rewards_value.to_string())
-        .add_attribute("recipient_remain_rewards", recipient.unwrap())
+        .add_attribute("recipient_remain_rewards", recipient)
+        .add_attribute("phase", UNSTAKE_PHASE_FINALIZED)
+        .add_attribute("token_id", token_id.clone())
+        .add_attribute("owner", staker.clone())
         .add_messages(messages)
+        .add_event(unstake_phase_event(UNSTAKE_PHASE_FINALIZED, token_id, staker))
+        .add_events(events)
+    )
+}
+
+// stake the booster nft.
+// the booster nft itself does not earn rewards, it increases rewards of other staked nfts of the staker.
+fn stake_booster(
+    mut deps: DepsMut,
+    env: Env,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    check_disable(deps.branch())?;
+
+    let token_id = msg.token_id;
+    let staker = stake_owner(deps.as_ref(), msg.sender, msg.msg)?;
+    if BOOSTER_TOKENS.has(deps.storage, token_id.clone()) {
+        return Err(ContractError::AlreadyStaked {})
+    }
+
+    BOOSTER_TOKENS.save(deps.storage, token_id.clone(), &staker)?;
+    let booster_count = manage_number_boosters(deps.branch(), staker.clone(), true, env.block.time.seconds())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "stake_booster")
+        .add_attribute("staker", staker)
+        .add_attribute("token_id", token_id)
+        .add_attribute("booster_count", booster_count.to_string())
+    )
+}
+
+// unstake the booster nft.
+// the booster nft is returned immediately without unbonding, the period of the unstake is not boosted.
+pub fn unstake_booster(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let staker = info.sender.to_string();
+    let booster_owner = BOOSTER_TOKENS.may_load(deps.storage, token_id.clone())?;
+    if booster_owner.is_none() {
+        return Err(ContractError::InvalidTokenId {})
+    }
+    let booster_owner = booster_owner.unwrap();
+    if booster_owner != staker {
+        return Err(ContractError::InvalidNftOwner {
+            requester: staker,
+            nft_owner: booster_owner,
+        })
+    }
+
+    BOOSTER_TOKENS.remove(deps.storage, token_id.clone());
+    let booster_count = manage_number_boosters(deps, staker.clone(), false, env.block.time.seconds())?;
+
+    let booster_contract = config.booster_contract.unwrap_or_default();
+
+    Ok(Response::new()
+        .add_attribute("method", "unstake_booster")
+        .add_attribute("staker", staker.clone())
+        .add_attribute("token_id", token_id.clone())
+        .add_attribute("booster_count", booster_count.to_string())
+        .add_message(execute_transfer_nft_unstake(token_id, staker, booster_contract)?)
     )
 }
 
@@ -630,6 +2112,8 @@ pub fn unstake_nft(
 // claims the claimable rewards for the specified max number of past periods, starting at the next claimable period.
 // claims can be done only for periods which have already ended.
 // the max number of periods to claim can be calibrated to chunk down claims in several transactions to accomodate gas constraints.
+// the parameters follow the fields of the claim message.
+#[allow(clippy::too_many_arguments)]
 pub fn claim_rewards(
     mut deps: DepsMut,
     info: MessageInfo,
@@ -638,10 +2122,15 @@ pub fn claim_rewards(
     token_id: String,
     config: Config,
     claim_recipient_address: Option<String>,
+    expected_config_version: Option<u64>,
 ) -> Result<Response, ContractError> {
     let start_timestamp = check_start_timestamp(deps.branch())?;
     check_disable(deps.branch())?;
 
+    // the claim is rejected if the config is changed after the client estimated rewards.
+    check_config_version(deps.as_ref(), expected_config_version)?;
+    advance_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+
     let staker = info.clone().sender.to_string();
     let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
 
@@ -651,10 +2140,23 @@ pub fn claim_rewards(
     }
 
     let token_info = check_token_info.unwrap();
+    if token_info.burned {
+        return Err(ContractError::TokenBurned {})
+    }
+
+    // the rewards which follow the nft are claimed by the current staker recorded in the token info.
+    if REWARDS_FOLLOW_NFT.may_load(deps.storage)?.unwrap_or(false) {
+        TokenInfo::check_staker(deps.branch(), info.clone(), token_id.clone())?;
+    }
 
     // although the time reaches unbonded status, the staker should not claim directly.
-    // the staker is able to get balances of rewards only execute unstake function.
+    // the staker is able to get balances of rewards only execute unstake function,
+    // which is run by the claim if auto finalize on claim is set and the unbonding duration has elapsed.
     if token_info.bond_status == UNBONDING {
+        let auto_finalize_on_claim = AUTO_FINALIZE_ON_CLAIM.may_load(deps.storage)?.unwrap_or(false);
+        if auto_finalize_on_claim && check_unbonding_end(deps.as_ref(), token_info, env.block.time.seconds()).is_ok() {
+            return unstake_nft(deps, env, info, config, token_id, claim_recipient_address, None)
+        }
         return Err(ContractError::TokenIdIsUnbonding {})
     }
 
@@ -664,9 +2166,24 @@ pub fn claim_rewards(
     }
     let next_claim = next_claim.unwrap();
 
+    // the unbonded token is claimable only when the rewards are deferred on unstake.
+    // the deferred rewards are claimed by the staker recorded in the token info until the requested unbond time.
+    if token_info.bond_status == UNBONDED && token_info.owner != staker {
+        return Err(ContractError::InvalidNftOwner {
+            requester: staker,
+            nft_owner: token_info.owner,
+        })
+    }
+
+    // if staker want to transfer send other address as request claim function, set claim recipient address. 
+    // the token reward recipient is used when the claim recipient address is not set.
+    let recipient = claim_recipient(deps.as_ref(), env.clone(), staker_tokenid_key.clone(), staker.clone(), claim_recipient_address)?;
+
     let now = env.block.time.seconds();
+    check_claim_cliff(deps.as_ref(), now, start_timestamp, config.clone())?;
+
     let claim: Claim;
-    let new_next_claim: NextClaim;
+    let mut new_next_claim: NextClaim;
     let compute_rewards = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), periods, now, start_timestamp, config.clone(), token_id.clone());
     match compute_rewards {
         Ok(t) => {
@@ -678,9 +2195,15 @@ pub fn claim_rewards(
         }
     }
 
+    // the token staked in the current period has no completed period, e.g. in the frozen unstake window.
+    let current_period = get_current_period(now, start_timestamp, config.clone())?;
+    if claim.periods == 0 && get_period(token_info.deposit_cycle, config.clone())? == current_period {
+        return Err(ContractError::NothingClaimableYet {})
+    }
+
     // check sufficient rewards pool of nft staking contract.
-    // for checking sufficient rewards pool, must input amount.
-    check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_INSUFFICIENT, Some(claim.amount.clone()))?;
+    // the rewards from the cutover period are transferred in the secondary rewards token.
+    let message = execute_rewards_transfer(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_INSUFFICIENT, recipient.clone(), claim.amount, claim.secondary_amount)?;
 
     // free up memory on already processed staker snapshots.
     let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone())?;
@@ -688,26 +2211,46 @@ pub fn claim_rewards(
         return Err(ContractError::HaveNotHistory {})
     }
     let mut staker_history = staker_history.unwrap();
-    while next_claim.staker_snapshot_index < new_next_claim.staker_snapshot_index {
-        let delete_index = next_claim.staker_snapshot_index + 1;
-        staker_history.remove(delete_index as usize);
+    if new_next_claim.staker_snapshot_index > 0 {
+        // snapshots before the snapshot of the next claim are never computed again.
+        staker_history.drain(..new_next_claim.staker_snapshot_index as usize);
         STAKER_HISTORIES.save(deps.storage, staker_tokenid_key.clone(), &staker_history)?;
+        new_next_claim.staker_snapshot_index = 0;
+    }
+
+    if next_claim.period == 0 {
+        return Err(ContractError::ClaimNotInitialized {})
     }
 
-    if claim.periods == 0 || next_claim.period == 0{
-        return Err(ContractError::InvalidClaim {})
+    // the carried excess of a capped period is claimable without a new period.
+    if claim.periods == 0 && claim.amount == 0 {
+        return Err(ContractError::NoClaimablePeriods {})
     }
 
     let mut exist_next_claim = true;
-    let last_staker_snapshot = staker_history[(staker_history.len() - 1) as usize];
+    let last_staker_snapshot = staker_history[staker_history.len() - 1];
     let last_claimed_cycle = (claim.start_period + claim.periods - 1) * config.period_length_in_cycles;
 
+    // the deferred rewards of the unbonded token are claimed until the requested unbond time.
+    let mut deferred_claimed = false;
+    if token_info.bond_status == UNBONDED {
+        let unbond_end_claim_period = get_unbond_end_claim_period(deps.as_ref(), token_info.clone(), start_timestamp, config.clone())?;
+        deferred_claimed = new_next_claim.period >= unbond_end_claim_period;
+    }
+
     // the claim reached the last staker snapshot and nothing is staked in the last staker snapshot.
-    if last_claimed_cycle >= last_staker_snapshot.start_cycle && last_staker_snapshot.is_staked == false {
+    // the next claim is kept while the carried excess of a capped period remains.
+    if new_next_claim.remainder == 0 && ((last_claimed_cycle >= last_staker_snapshot.start_cycle && !last_staker_snapshot.is_staked) || deferred_claimed) {
         
         // re-init the next claim.
         NEXT_CLAIMS.remove(deps.storage, staker_tokenid_key.clone());
         exist_next_claim = false;
+
+        // clear the token owner of the unbonded token.
+        if token_info.bond_status == UNBONDED {
+            let token_info_unstaked = TokenInfo::unstake(false, token_info.clone().deposit_cycle, token_info.clone().withdraw_cycle, token_info.staked_nft_contract(config.clone()));
+            save_token_info(deps.storage, token_id.clone(), &token_info_unstaked)?;
+        }
     } else {
         NEXT_CLAIMS.save(deps.storage, staker_tokenid_key.clone(), &new_next_claim)?;
     }
@@ -716,14 +2259,20 @@ pub fn claim_rewards(
         return Err(ContractError::NoAmountClaim {})
     }
     
-    // if staker want to transfer send other address as request claim function, set claim recipient address. 
-    let mut recipient = staker;
-    if !claim_recipient_address.is_none() {
-        recipient = claim_recipient_address.unwrap();
-    }
-
-    // transfer token amount of staked rewards.
-    let message = execute_token_contract_transfer(config.rewards_token_contract, recipient.clone(), claim.amount)?;
+    let events = check_auto_disable(deps.branch(), env.clone(), config.clone(), claim.amount - claim.secondary_amount)?;
+    increase_total_paid_out(deps.branch(), claim.amount)?;
+    increase_counter(deps.branch(), TOTAL_CLAIMS)?;
+
+    // the receipt of the claim is stored on-chain.
+    let claim_receipt_index = record_claim_receipt(deps.branch(), ClaimReceipt {
+        staker,
+        token_id,
+        amount: claim.amount,
+        recipient: recipient.clone(),
+        start_period: claim.start_period,
+        periods: claim.periods,
+        timestamp: now,
+    })?;
 
     Ok(Response::new()
         .add_attribute("method", "claim_rewards")
@@ -732,7 +2281,393 @@ pub fn claim_rewards(
         .add_attribute("claim_amount", claim.amount.to_string())
         .add_attribute("claim_recipient", recipient.to_string())
         .add_attribute("exist_next_claim", exist_next_claim.to_string())
+        .add_attribute("is_fully_caught_up", (new_next_claim.period == current_period).to_string())
+        .add_attribute("claim_receipt_index", claim_receipt_index.to_string())
         .add_messages(message)
+        .add_events(events)
+    )
+}
+
+// claim rewards of every bonded token staked by the sender.
+// the claim of each token is capped by the periods as same as the claim rewards function.
+// the token ids of the staker in the per-staker index are processed up to MAX_CLAIM_ALL_TOKENS per call,
+// and the staker continues with the returned next start after.
+pub fn claim_all_rewards(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    periods: u64,
+    config: Config,
+    claim_recipient_address: Option<String>,
+    start_after: Option<String>,
+) -> Result<Response, ContractError> {
+    let start_timestamp = check_start_timestamp(deps.branch())?;
+    check_disable(deps.branch())?;
+
+    let now = env.block.time.seconds();
+    check_claim_cliff(deps.as_ref(), now, start_timestamp, config.clone())?;
+
+    let staker = info.clone().sender.to_string();
+    let start = start_after.map(Bound::exclusive);
+
+    // the token ids of the staker are ranged by the per-staker index,
+    // one more token id is loaded to check whether the next page exists.
+    let mut token_ids: Vec<String> = STAKER_TOKENS
+        .prefix(staker.clone())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(MAX_CLAIM_ALL_TOKENS + 1)
+        .collect::<StdResult<Vec<String>>>()?;
+
+    let mut next_start_after: Option<String> = None;
+    if token_ids.len() > MAX_CLAIM_ALL_TOKENS {
+        token_ids.truncate(MAX_CLAIM_ALL_TOKENS);
+        next_start_after = token_ids.last().cloned();
+    }
+
+    let mut response = Response::new()
+        .add_attribute("method", "claim_all_rewards");
+    let mut total_claim_amount: u128 = 0;
+    let mut claimed_tokens: u64 = 0;
+    let mut last_processed_token_id: Option<String> = None;
+
+    for token_id in token_ids {
+        last_processed_token_id = Some(token_id.clone());
+
+        // unbonding, unbonded and burned tokens are skipped.
+        let token_info = match TOKEN_INFOS.may_load(deps.storage, token_id.clone())? {
+            Some(t) => t,
+            None => continue,
+        };
+        if token_info.owner != staker || token_info.bond_status != BONDED || token_info.burned {
+            continue
+        }
+
+        // the token which has nothing to claim is skipped.
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        if !NEXT_CLAIMS.has(deps.storage, staker_tokenid_key) {
+            continue
+        }
+        let res = match claim_rewards(deps.branch(), info.clone(), env.clone(), periods, token_id.clone(), config.clone(), claim_recipient_address.clone(), None) {
+            Ok(t) => t,
+            Err(ContractError::NoAmountClaim {}) | Err(ContractError::NoClaimablePeriods {}) | Err(ContractError::NothingClaimableYet {}) => continue,
+            Err(e) => return Err(e),
+        };
+        let claim_amount = res.attributes
+            .iter()
+            .find(|attr| attr.key == "claim_amount")
+            .map_or(0, |attr| attr.value.parse::<u128>().unwrap_or(0));
+        total_claim_amount += claim_amount;
+        claimed_tokens += 1;
+
+        response = response
+            .add_attribute("claimed_token_id", token_id.clone())
+            .add_attribute("claimed_amount", claim_amount.to_string())
+            .add_submessages(res.messages)
+            .add_events(res.events);
+
+        // the remaining tokens are not claimable after the contract is disabled automatically.
+        if DISABLE.load(deps.storage)? {
+            next_start_after = None;
+            break
+        }
+    }
+
+    if total_claim_amount == 0 && next_start_after.is_none() {
+        return Err(ContractError::NoAmountClaim {})
+    }
+
+    Ok(response
+        .add_attribute("claimed_tokens", claimed_tokens.to_string())
+        .add_attribute("total_claim_amount", total_claim_amount.to_string())
+        .add_attribute("last_processed_token_id", last_processed_token_id.unwrap_or_else(|| "none".to_string()))
+        .add_attribute("next_start_after", next_start_after.unwrap_or_else(|| "none".to_string()))
+    )
+}
+
+// block the token id from staking, e.g. stolen or flagged nfts.
+// the already staked token id is still able to be unstaked.
+pub fn block_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env, config)?;
+
+    BLOCKED_TOKENS.save(deps.storage, token_id.clone(), &true)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "block_token")
+        .add_attribute("token_id", token_id)
+    )
+}
+
+// unblock the token id, it is able to be staked again.
+pub fn unblock_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    config: Config,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info, env, config)?;
+
+    BLOCKED_TOKENS.remove(deps.storage, token_id.clone());
+
+    Ok(Response::new()
+        .add_attribute("method", "unblock_token")
+        .add_attribute("token_id", token_id)
+    )
+}
+
+// mark the staked nft which is burned on the cw721 side.
+// only the contract owner, not the grantee, confirms the token id no longer exists in the nft contract by querying OwnerOf,
+// then rewards accrued until now are transferred to the staker and the token stops accruing.
+pub fn mark_token_burned(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    config: Config,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_contract_owner_only(deps.as_ref(), info.clone(), env.clone(), config.clone())?;
+
+    let start_timestamp = check_start_timestamp(deps.branch())?;
+    check_disable(deps.branch())?;
+
+    // the accrued rewards are paid out, so the token cannot be marked before the claim cliff.
+    check_claim_cliff(deps.as_ref(), env.block.time.seconds(), start_timestamp, config.clone())?;
+    settle_emission_ledger(deps.branch(), env.block.time.seconds(), config.clone())?;
+
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if token_info.is_none() {
+        return Err(ContractError::InvalidTokenId {})
+    }
+    let token_info = token_info.unwrap();
+    if token_info.burned {
+        return Err(ContractError::TokenBurned {})
+    }
+    if !token_info.is_staked {
+        return Err(ContractError::UnstakedTokenId {})
+    }
+
+    // the token id must not exist in the nft contract.
+    let nft_contract = token_info.staked_nft_contract(config.clone());
+    // only the not found error means the token is burned, other query errors are returned.
+    match query_nft_owner(deps.as_ref(), nft_contract.clone(), token_id.clone()) {
+        Ok(owner_of) => return Err(ContractError::TokenNotBurned { owner: owner_of.owner }),
+        Err(e) => {
+            if !is_not_found_error(&e) {
+                return Err(ContractError::Std(e))
+            }
+        },
+    }
+
+    let staker = token_info.clone().owner;
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+    let timestamp = env.block.time.seconds();
+    let current_cycle = get_cycle(timestamp, start_timestamp, config.clone())?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    // pay out rewards accrued to date.
+    let (remain_rewards_value, secondary_rewards_value) = compute_remain_rewards(deps.branch(), staker_tokenid_key.clone(), timestamp, start_timestamp, config.clone(), token_id.clone())?;
+    if remain_rewards_value != 0 {
+        let mut claim_message = execute_rewards_transfer(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_BOTH, staker.clone(), remain_rewards_value, secondary_rewards_value)?;
+        increase_total_paid_out(deps.branch(), remain_rewards_value)?;
+        messages.append(&mut claim_message);
+    }
+
+    update_histories(deps.branch(), staker_tokenid_key.clone(), !IS_STAKED, current_cycle)?;
+
+    let token_info_burned = TokenInfo::burned(
+        staker.clone(), 
+        token_info.clone().deposit_cycle, 
+        current_cycle, 
+        token_info.clone().bond_status, 
+        token_info.clone().req_unbond_time,
+        nft_contract.clone(),
+    );
+    save_token_info(deps.storage, token_id.clone(), &token_info_burned)?;
+
+    NEXT_CLAIMS.remove(deps.storage, staker_tokenid_key);
+    manage_number_nfts(deps.branch(), staker.clone(), nft_contract, false, env.block.time.seconds());
+
+    Ok(Response::new()
+        .add_attribute("method", "mark_token_burned")
+        .add_attribute("token_id", token_id)
+        .add_attribute("nft_owner", staker)
+        .add_attribute("claim_remain_rewards", remain_rewards_value.to_string())
+        .add_messages(messages)
+    )
+}
+
+// set the default recipient of rewards for the staked nft.
+// only the staker of the nft can set, and the recipient is removed when it is none.
+pub fn set_token_reward_recipient(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    recipient: Option<String>,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    TokenInfo::check_staker(deps.branch(), info.clone(), token_id.clone())?;
+    let staker = info.sender.to_string();
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+    if recipient.is_none() {
+        TOKEN_RECIPIENTS.remove(deps.storage, staker_tokenid_key);
+
+        return Ok(Response::new()
+            .add_attribute("method", "set_token_reward_recipient")
+            .add_attribute("token_id", token_id)
+            .add_attribute("recipient", "none")
+        )
+    }
+
+    // the recipient never expires if the expiration is not set.
+    let recipient = deps.api.addr_validate(&recipient.unwrap())?;
+    let token_recipient = TokenRecipient::new(recipient.to_string(), expires);
+    TOKEN_RECIPIENTS.save(deps.storage, staker_tokenid_key, &token_recipient)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_token_reward_recipient")
+        .add_attribute("token_id", token_id)
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("expires", token_recipient.expires.to_string())
+    )
+}
+
+// approve the address which can receive rewards of the staked nft as claim recipient address.
+// only the staker of the nft can approve.
+pub fn approve_reward_recipient(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    TokenInfo::check_staker(deps.branch(), info.clone(), token_id.clone())?;
+    let staker = info.sender.to_string();
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+    let recipient = deps.api.addr_validate(&recipient)?.to_string();
+    let mut approved_recipients = APPROVED_RECIPIENTS.may_load(deps.storage, staker_tokenid_key.clone())?.unwrap_or_default();
+    if !approved_recipients.contains(&recipient) {
+        approved_recipients.push(recipient.clone());
+        APPROVED_RECIPIENTS.save(deps.storage, staker_tokenid_key, &approved_recipients)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "approve_reward_recipient")
+        .add_attribute("token_id", token_id)
+        .add_attribute("recipient", recipient)
+    )
+}
+
+// advance the next claim of the staked nft to the current period without paying.
+// unpaid rewards are forfeited and stay in the rewards pool, 
+// so the forfeit amount must match the forfeited rewards to prevent accidental loss.
+pub fn skip_to_current_period(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    config: Config,
+    token_id: String,
+    forfeit_amount: u128,
+) -> Result<Response, ContractError> {
+    let start_timestamp = check_start_timestamp(deps.branch())?;
+    check_disable(deps.branch())?;
+
+    let token_info = TokenInfo::check_staker(deps.branch(), info.clone(), token_id.clone())?;
+    if token_info.burned {
+        return Err(ContractError::TokenBurned {})
+    }
+
+    let staker = info.sender.to_string();
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+    if !NEXT_CLAIMS.has(deps.storage, staker_tokenid_key.clone()) {
+        return Err(ContractError::EmptyNextClaim {})
+    }
+
+    let now = env.block.time.seconds();
+    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+    let mut forfeited_rewards: u128 = 0;
+    let mut skipped_periods: u64 = 0;
+
+    // the next claim is advanced until no more periods remain, although the rewards of periods are zero.
+    loop {
+        let (claim, next_claim) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), max_compute_period, now, start_timestamp, config.clone(), token_id.clone())?;
+        if claim.periods == 0 && claim.amount == 0 {
+            break
+        }
+
+        forfeited_rewards += claim.amount;
+        skipped_periods += claim.periods;
+        NEXT_CLAIMS.save(deps.storage, staker_tokenid_key.clone(), &next_claim)?;
+    }
+
+    if forfeit_amount != forfeited_rewards {
+        return Err(ContractError::ForfeitAmountMismatch { 
+            forfeit_amount, 
+            forfeited_rewards, 
+        })
+    }
+
+    let next_claim = NEXT_CLAIMS.load(deps.storage, staker_tokenid_key)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "skip_to_current_period")
+        .add_attribute("token_id", token_id)
+        .add_attribute("skipped_periods", skipped_periods.to_string())
+        .add_attribute("forfeit_amount", forfeited_rewards.to_string())
+        .add_attribute("next_claim_period", next_claim.period.to_string())
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    match msg.id {
+        ADD_REWARDS_POOL_REPLY_ID => add_rewards_pool_reply(deps, env),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+// add the actual received amount to rewards pool after transfer from the owner.
+// the difference from the requested amount is taken by the rewards token as a transfer fee.
+pub fn add_rewards_pool_reply(
+    mut deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_REWARDS_DEPOSIT.may_load(deps.storage)?;
+    if pending.is_none() {
+        return Err(ContractError::NoPendingRewardsDeposit {})
+    }
+    let pending = pending.unwrap();
+    PENDING_REWARDS_DEPOSIT.remove(deps.storage);
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let balance_after = query_rewards_token_balance(deps.as_ref(), env.contract.address.to_string(), config.rewards_token_contract.clone())?.balance.u128();
+    let received_amount = balance_after.saturating_sub(pending.balance_before);
+    let transfer_fee = pending.requested_amount.saturating_sub(received_amount);
+
+    settle_emission_ledger(deps.branch(), env.block.time.seconds(), config)?;
+    update_emission_budget(deps.branch(), received_amount, 0)?;
+
+    let total_rewards_pool = TOTAL_REWARDS_POOL.load(deps.storage)?;
+    let rewards = total_rewards_pool + received_amount;
+
+    TOTAL_REWARDS_POOL.save(deps.storage, &rewards)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "add_rewards_pool_reply")
+        .add_attribute("requested_amount", pending.requested_amount.to_string())
+        .add_attribute("added_rewards", received_amount.to_string())
+        .add_attribute("transfer_fee", transfer_fee.to_string())
+        .add_attribute("total_rewards", rewards.to_string())
     )
 }
 
@@ -755,5 +2690,7 @@ pub fn migrate(
     // set the new version
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+    )
 }
\ No newline at end of file
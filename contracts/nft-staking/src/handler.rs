@@ -1,17 +1,30 @@
 use std::{ops::Add, str::FromStr};
 
-use cosmwasm_std::{DepsMut, Uint128, Addr, CosmosMsg, to_binary, WasmMsg, MessageInfo, QueryRequest, WasmQuery, Deps, Coin, Env};
-use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, BalanceResponse, Cw20ReceiveMsg};
-use cw721::{Cw721ExecuteMsg};
+use cosmwasm_std::{DepsMut, Uint128, Addr, CosmosMsg, to_binary, WasmMsg, MessageInfo, QueryRequest, WasmQuery, Deps, Coin, Env, StdResult, StdError, Binary, from_binary, Event, Order, Storage};
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, BalanceResponse, Cw20ReceiveMsg, TokenInfoResponse, MinterResponse};
+use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, OwnerOfResponse, ContractInfoResponse};
+use cw_storage_plus::{Bound, Item};
 
-use crate::{state::{Config, Snapshot, STAKER_HISTORIES, START_TIMESTAMP, DISABLE, NEXT_CLAIMS, Claim, REWARDS_SCHEDULE, NextClaim, NUMBER_OF_STAKED_NFTS, MAX_COMPUTE_PERIOD, GRANTS, TOKEN_INFOS, UNBONDING, TokenInfo, UNBONDING_DURATION, UNBONDED}, ContractError, msg::{UpdateHistoriesMsg}};
+use crate::{state::{Config, MIN_POOL_BALANCE_TO_STAKE, Snapshot, SnapshotRange, STAKER_HISTORIES, START_TIMESTAMP, DISABLE, NEXT_CLAIMS, Claim, REWARDS_SCHEDULE, NextClaim, NUMBER_OF_STAKED_NFTS, MAX_COMPUTE_PERIOD, GRANTS, TOKEN_INFOS, UNBONDING, TokenInfo, UNBONDING_DURATION, UNBONDED, STAKER_NFT_COUNTS, STAKER_NFT_COUNT_HISTORY, MIN_TOKENS_TO_EARN, STAKE_FORWARDERS, CLAIM_CLIFF_PERIOD, AUTO_DISABLE_THRESHOLD, TOKEN_RECIPIENTS, REQUIRE_APPROVED_RECIPIENT, APPROVED_RECIPIENTS, STAKED_COUNT_BY_COLLECTION, MAX_REWARD_PER_CLAIM_PER_TOKEN, EMISSION_END_PERIOD, TOTAL_PAID_OUT, EmissionMode, EMISSION_MODE, REWARD_PER_PERIOD, STAKED_COUNT_HISTORY, CONFIG_STATE, MIN_CYCLE_LENGTH_IN_SECONDS, BONDED, BOOSTERS, BOOSTER_COUNT_HISTORY, CONFIG_VERSION, WITHDRAW_TIMELOCK_SECONDS, ClaimReceipt, CLAIM_RECEIPTS, CLAIM_RECEIPT_COUNT, POOL_CONSTRAINED_EMISSION, EMISSION_LEDGER, UNFUNDED_PERIODS, PEAK_STAKED_COUNT_HISTORY, EmissionLedger, REWARDS_FOLLOW_NFT, REWARDS_TOKEN_CUTOVER, EarlyStakerBoost, EARLY_STAKER_BOOST, TOKEN_MULTIPLIERS, MAX_TOKEN_MULTIPLIER_BPS, MAX_EMISSION_LEDGER_PERIODS, PRORATE_FINAL_PERIOD, STAKER_TOKENS}, ContractError, msg::{UpdateHistoriesMsg, StakeNftMsg}};
 
 pub const CHECK_REWARDS_POOL_AIM_EMPTY: &str = "check_empty_rewards_pool";
 pub const CHECK_REWARDS_POOL_AIM_INSUFFICIENT: &str = "check_insufficient_rewards_pool";
 pub const CHECK_REWARDS_POOL_AIM_BOTH: &str = "both";
 pub const IS_STAKED: bool = true;
-const MIN_CYCLE_LENGTH: u64 = 10;
-const MIN_PERIOD: u64 = 2;
+pub const UNSTAKE_PHASE_START_UNBONDING: &str = "start_unbonding";
+pub const UNSTAKE_PHASE_FINALIZED: &str = "finalized";
+// max number of snapshots in the history of a staker_tokenid_key.
+pub const MAX_SNAPSHOTS_PER_TOKEN: usize = 100;
+pub const MIN_CYCLE_LENGTH: u64 = 10;
+const MIN_PERIOD: u64 = 1;
+const MAX_PERIOD_LENGTH: u64 = 1_000;
+const BPS_DENOMINATOR: u128 = 10_000;
+// the token multiplier bps of the token which is not set is 1x.
+pub const DEFAULT_TOKEN_MULTIPLIER_BPS: u16 = 10_000;
+// the querier error of the cw721 contract when the token info of the token id does not exist.
+// the type parameter of the token info is the extension of the collection, so it is not matched.
+const CW721_TOKEN_NOT_FOUND_PREFIX: &str = "Querier contract error: cw721_base::state::TokenInfo<";
+const CW721_TOKEN_NOT_FOUND_SUFFIX: &str = "> not found";
 
 // get current period.
 pub fn get_current_period(
@@ -53,11 +66,12 @@ pub fn get_cycle(
 // validate of cycle length.
 pub fn is_valid_cycle_length(
     cycle_length_in_seconds: u64,
+    min_cycle_length: u64,
 ) -> Result<bool, ContractError> {
-    // cycle length must be longer than MIN_CYCLE_LENGTH.  
-    if cycle_length_in_seconds < MIN_CYCLE_LENGTH {
-        return Err(ContractError::CycleLengthInvalid { 
-            min_cycle_length: MIN_CYCLE_LENGTH,
+    // cycle length must be longer than the minimum cycle length of the contract.  
+    if cycle_length_in_seconds < min_cycle_length {
+        Err(ContractError::CycleLengthInvalid { 
+            min_cycle_length,
             cycle_length_in_seconds 
         })
     } else {
@@ -66,16 +80,54 @@ pub fn is_valid_cycle_length(
     }    
 }
 
+// check the whitelisted nft contract is able to be replaced.
+// staked token which has no nft contract in the token info is returned via the whitelisted nft contract,
+// so the whitelist cannot be replaced while the token is staked.
+pub fn check_whitelist_replaceable(
+    deps: Deps,
+) -> Result<(), ContractError> {
+    let blocking_token = TOKEN_INFOS
+        .range(deps.storage, None, None, Order::Ascending)
+        .find(|item| match item {
+            Ok((_, token_info)) => !token_info.owner.is_empty() && token_info.nft_contract.is_empty(),
+            Err(_) => true,
+        })
+        .transpose()?;
+
+    if let Some((token_id, _)) = blocking_token {
+        return Err(ContractError::WhitelistReplaceBlocked { token_id })
+    }
+
+    Ok(())
+}
+
+// get the minimum cycle length of the contract.
+// MIN_CYCLE_LENGTH is used for the contract which is instantiated before the minimum is stored.
+pub fn get_min_cycle_length(
+    deps: Deps,
+) -> StdResult<u64> {
+    let min_cycle_length = MIN_CYCLE_LENGTH_IN_SECONDS.may_load(deps.storage)?;
+
+    Ok(min_cycle_length.unwrap_or(MIN_CYCLE_LENGTH))
+}
+
 // validate of period length.
 pub fn is_valid_period_length(
     period_length_in_cycles: u64,
 ) -> Result<bool, ContractError> {
     // period length must be longer than MIN_PERIOD.
+    // the period of the single cycle is allowed for claiming every cycle, the period math is same as longer periods.
+    // period length must not be longer than MAX_PERIOD_LENGTH to bound the claim loops.
     if period_length_in_cycles < MIN_PERIOD {
-        return Err(ContractError::PeriodLengthInvalid { 
+        Err(ContractError::PeriodLengthInvalid { 
             min_period: MIN_PERIOD,
             period_length_in_cycles 
         })
+    } else if period_length_in_cycles > MAX_PERIOD_LENGTH {
+        Err(ContractError::PeriodLengthTooLong { 
+            max_period_length: MAX_PERIOD_LENGTH,
+            period_length_in_cycles 
+        })
     } else {
         let res = true;
         Ok(res)
@@ -85,13 +137,11 @@ pub fn is_valid_period_length(
 // make contract message info.
 pub fn contract_info(
     msg: Cw20ReceiveMsg,
-) -> Result<MessageInfo, ()>{
-    let contract_owner_info = MessageInfo {
-        sender: Addr::unchecked(msg.sender.clone()),
+) -> MessageInfo {
+    MessageInfo {
+        sender: Addr::unchecked(msg.sender),
         funds: [Coin::default()].to_vec(),
-    }; 
-
-    Ok(contract_owner_info)
+    }
 }
 
 // mapping staker and nft token id in order to use state key.
@@ -99,16 +149,40 @@ pub fn staker_tokenid_key(
     staker: String,
     token_id: String,
 ) -> String {
-    let staker_tokenid_key = staker.add("@").add(&token_id.clone());
-    return staker_tokenid_key
+    
+    staker.add("@").add(&token_id.clone())
+}
+
+// check the next claim of staker_tokenid_key is orphaned, the token info does not exist or is owned by the other staker.
+// the unbonded token with deferred rewards keeps the staker as the owner, so its next claim is not orphaned.
+pub fn is_orphaned_claim(
+    deps: Deps,
+    staker_tokenid_key: &str,
+) -> StdResult<bool> {
+    let (staker, token_id) = match staker_tokenid_key.split_once('@') {
+        Some(t) => t,
+        None => return Ok(true),
+    };
+
+    match TOKEN_INFOS.may_load(deps.storage, token_id.to_string())? {
+        Some(token_info) => Ok(token_info.owner != staker),
+        None => Ok(true),
+    }
 }
 
 // check message sender is contract owner.
+// granted address is rejected by OwnerOnly to be distinguished from unauthorized address.
 pub fn check_contract_owner_only (
+    deps: Deps,
     info: MessageInfo, 
+    env: Env,
     config: Config,
 ) -> Result<bool, ContractError> {
-    if config.owner != info.sender.to_string() {
+    if config.owner != info.sender {
+        let grants = GRANTS.may_load(deps.storage, info.sender.to_string())?;
+        if grants.is_some() && !grants.unwrap().expires.is_expired(&env.block) {
+            return Err(ContractError::OwnerOnly {})
+        }
         return Err(ContractError::Unauthorized {})
     }
 
@@ -116,6 +190,7 @@ pub fn check_contract_owner_only (
 }
 
 // check message sender is contract owner or granted address.
+// grants are suspended while the contract is disabled, only the contract owner recovers and enables the contract.
 pub fn check_contract_owner(
     deps: DepsMut,
     info: MessageInfo,
@@ -123,19 +198,109 @@ pub fn check_contract_owner(
     config: Config,
 ) -> Result<bool, ContractError> {
     // contract owner.
-    if config.owner == info.sender.to_string() {
+    if config.owner == info.sender {
         return Ok(true)    
     }
 
     // granted address by adding contract owner.
     let grants = GRANTS.may_load(deps.storage, info.sender.to_string())?;
-    if !grants.is_none() && !grants.unwrap().expires.is_expired(&env.block) {
+    if grants.is_some() && !grants.unwrap().expires.is_expired(&env.block) {
+        if DISABLE.may_load(deps.storage)?.unwrap_or(false) {
+            return Err(ContractError::GrantSuspended {})
+        }
         return Ok(true)
     }
 
     Err(ContractError::Unauthorized {})
 }
 
+// check the withdraw timelock is not set, the rewards pool is withdrawn directly only without the timelock.
+pub fn check_withdraw_timelock(
+    deps: Deps,
+) -> Result<(), ContractError> {
+    if WITHDRAW_TIMELOCK_SECONDS.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::WithdrawTimelockEnabled {})
+    }
+
+    Ok(())
+}
+
+// return the validated withdraw recipient, the message sender is used when it is not set.
+pub fn withdraw_recipient(
+    deps: Deps,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<String, ContractError> {
+    if recipient.is_none() {
+        return Ok(info.sender.to_string())
+    }
+
+    let recipient = deps.api.addr_validate(&recipient.unwrap())?;
+    Ok(recipient.to_string())
+}
+
+// return the recipient of the claimed rewards.
+// the claim recipient address overrides the reward recipient of the token,
+// and the staker receives rewards when both are not set.
+// if approved recipients are required, the claim recipient address must be approved by the staker in advance.
+pub fn claim_recipient(
+    deps: Deps,
+    env: Env,
+    staker_tokenid_key: String,
+    staker: String,
+    claim_recipient_address: Option<String>,
+) -> Result<String, ContractError> {
+    if let Some(claim_recipient_address) = claim_recipient_address {
+        let recipient = deps.api.addr_validate(&claim_recipient_address)?.to_string();
+
+        let require_approved_recipient = REQUIRE_APPROVED_RECIPIENT.may_load(deps.storage)?.unwrap_or(false);
+        if require_approved_recipient && recipient != staker {
+            let approved_recipients = APPROVED_RECIPIENTS.may_load(deps.storage, staker_tokenid_key)?.unwrap_or_default();
+            if !approved_recipients.contains(&recipient) {
+                return Err(ContractError::RecipientNotApproved {})
+            }
+        }
+
+        return Ok(recipient)
+    }
+
+    // the expired token reward recipient is ignored and the rewards revert to the staker.
+    let token_recipient = TOKEN_RECIPIENTS.may_load(deps.storage, staker_tokenid_key)?;
+    if let Some(token_recipient) = token_recipient {
+        if !token_recipient.expires.is_expired(&env.block) {
+            return Ok(token_recipient.recipient)
+        }
+    }
+
+    Ok(staker)
+}
+
+// return the owner of the staked nft.
+// the sender is the owner unless the send nft message has stake_for address.
+// if stake forwarders are set, only the forwarders can stake on behalf of other address.
+pub fn stake_owner(
+    deps: Deps,
+    sender: String,
+    send_nft_msg: Binary,
+) -> Result<String, ContractError> {
+    let stake_nft_msg: Option<StakeNftMsg> = from_binary(&send_nft_msg).ok();
+    if stake_nft_msg.is_none() || stake_nft_msg.clone().unwrap().stake_for.is_none() {
+        return Ok(sender)
+    }
+
+    let stake_for = deps.api.addr_validate(&stake_nft_msg.unwrap().stake_for.unwrap())?.to_string();
+    if stake_for == sender {
+        return Ok(sender)
+    }
+
+    let stake_forwarders = STAKE_FORWARDERS.may_load(deps.storage)?;
+    if stake_forwarders.is_some() && !stake_forwarders.unwrap().contains(&sender) {
+        return Err(ContractError::UnauthorizedForwarder { forwarder: sender })
+    }
+
+    Ok(stake_for)
+}
+
 // check the contract is started and return start timestamp.
 pub fn check_start_timestamp(
     deps: DepsMut,
@@ -153,13 +318,71 @@ pub fn check_disable(
     deps: DepsMut,
 ) -> Result<bool, ContractError> {
     let disable = DISABLE.load(deps.storage)?;
-    if disable == true {
+    if disable {
         return Err(ContractError::Disabled {})
     }
 
     Ok(disable)
 }
 
+// disable the nft staking contract automatically,
+// if the rewards pool balance after the outflow falls below the auto disable threshold.
+pub fn check_auto_disable(
+    deps: DepsMut,
+    env: Env,
+    config: Config,
+    amount: u128,
+) -> Result<Vec<Event>, ContractError> {
+    let auto_disable_threshold = AUTO_DISABLE_THRESHOLD.may_load(deps.storage)?;
+    if auto_disable_threshold.is_none() {
+        return Ok(vec![])
+    }
+    let auto_disable_threshold = auto_disable_threshold.unwrap();
+
+    let address = env.contract.address.to_string();
+    let balance_response = query_rewards_token_balance(deps.as_ref(), address, config.rewards_token_contract)?;
+    let remain_balance = balance_response.balance.u128().saturating_sub(amount);
+    if remain_balance >= auto_disable_threshold {
+        return Ok(vec![])
+    }
+
+    DISABLE.save(deps.storage, &true)?;
+
+    Ok(vec![Event::new("auto_disabled")
+        .add_attribute("auto_disable_threshold", auto_disable_threshold.to_string())
+        .add_attribute("remain_balance", remain_balance.to_string())
+    ])
+}
+
+// the event classifies the transition of unstake for indexers.
+// the phase is start_unbonding when the unbonding is requested, and finalized when the nft is returned.
+pub fn unstake_phase_event(
+    phase: &str,
+    token_id: String,
+    owner: String,
+) -> Event {
+    Event::new("unstake_nft")
+        .add_attribute("phase", phase)
+        .add_attribute("token_id", token_id)
+        .add_attribute("owner", owner)
+}
+
+// check the current period reaches the claim cliff period.
+pub fn check_claim_cliff(
+    deps: Deps,
+    now: u64,
+    start_timestamp: u64,
+    config: Config,
+) -> Result<bool, ContractError> {
+    let claim_cliff_period = CLAIM_CLIFF_PERIOD.may_load(deps.storage)?.unwrap_or(0);
+    let current_period = get_current_period(now, start_timestamp, config)?;
+    if current_period < claim_cliff_period {
+        return Err(ContractError::BeforeCliff { cliff: claim_cliff_period })
+    }
+
+    Ok(true)
+}
+
 // check unbonding status.
 pub fn check_unbonding_end(
     deps: Deps,   
@@ -186,11 +409,10 @@ pub fn check_rewards_pool_balance(
     let rewards_token_contract = config.clone().rewards_token_contract;
     let balance_response = query_rewards_token_balance(deps.as_ref(), address.clone(), rewards_token_contract.clone())?;
 
-    if aim == CHECK_REWARDS_POOL_AIM_EMPTY || aim == CHECK_REWARDS_POOL_AIM_BOTH {
-        if balance_response.balance == Uint128::from_str("0").unwrap() {
+    if (aim == CHECK_REWARDS_POOL_AIM_EMPTY || aim == CHECK_REWARDS_POOL_AIM_BOTH)
+        && balance_response.balance == Uint128::from_str("0").unwrap() {
             return Err(ContractError::EmptyRewardsPool {})
         } 
-    } 
     
     if aim == CHECK_REWARDS_POOL_AIM_INSUFFICIENT || aim == CHECK_REWARDS_POOL_AIM_BOTH {
         let amount = amount.unwrap();
@@ -205,6 +427,26 @@ pub fn check_rewards_pool_balance(
     Ok(())
 }
 
+// check the rewards pool balance is not below the minimum to stake.
+pub fn check_min_pool_balance_to_stake(
+    deps: Deps,
+    env: Env,
+    config: Config,
+) -> Result<(), ContractError> {
+    let minimum = MIN_POOL_BALANCE_TO_STAKE.may_load(deps.storage)?;
+    if let Some(minimum) = minimum {
+        let balance_response = query_rewards_token_balance(deps, env.contract.address.to_string(), config.rewards_token_contract)?;
+        if balance_response.balance.u128() < minimum {
+            return Err(ContractError::PoolBelowStakingMinimum {
+                balance: balance_response.balance.u128(),
+                minimum,
+            })
+        }
+    }
+
+    Ok(())
+}
+
 // execute token transfer.
 pub fn execute_token_contract_transfer(
     rewards_token_contract: String,
@@ -227,6 +469,60 @@ pub fn execute_token_contract_transfer(
     Ok(messages)
 }
 
+// check the rewards pools and transfer the rewards which are split at the rewards token cutover.
+// the amount includes the secondary amount, which is paid in the secondary rewards token.
+pub fn execute_rewards_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    config: Config,
+    aim: &str,
+    recipient: String,
+    amount: u128,
+    secondary_amount: u128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    let primary_amount = amount - secondary_amount;
+    if primary_amount != 0 {
+        check_rewards_pool_balance(deps.branch(), env.clone(), config.clone(), aim, Some(primary_amount))?;
+        messages.append(&mut execute_token_contract_transfer(config.rewards_token_contract, recipient.clone(), primary_amount)?);
+    }
+
+    if secondary_amount != 0 {
+        let secondary_rewards_token = REWARDS_TOKEN_CUTOVER.load(deps.storage)?.secondary_rewards_token;
+        let balance = query_rewards_token_balance(deps.as_ref(), env.contract.address.to_string(), secondary_rewards_token.clone())?.balance.u128();
+        if balance < secondary_amount {
+            return Err(ContractError::InsufficientSecondaryRewardsPool { 
+                balance, 
+                amount: secondary_amount,
+            })
+        }
+        messages.append(&mut execute_token_contract_transfer(secondary_rewards_token, recipient, secondary_amount)?);
+    }
+
+    Ok(messages)
+}
+
+// execute transfer from for pulling rewards from the owner to nft staking contract.
+pub fn execute_token_contract_transfer_from(
+    rewards_token_contract: String,
+    owner: String,
+    recipient: String,
+    amount: u128,
+) -> Result<CosmosMsg, ContractError> {
+    let transfer_from: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: rewards_token_contract,
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom { 
+            owner,
+            recipient, 
+            amount: Uint128::from(amount),
+        })?,
+        funds: vec![]
+    });
+
+    Ok(transfer_from)
+}
+
 // execute transfer nft for replacing owner when unstake.
 pub fn execute_transfer_nft_unstake(
     token_id: String,
@@ -246,6 +542,25 @@ pub fn execute_transfer_nft_unstake(
     Ok(transfer_from)
 }
 
+// execute transfer nft from the staker to the nft staking contract when stake many.
+// the nft staking contract must be approved by the staker.
+pub fn execute_transfer_nft_stake(
+    token_id: String,
+    nft_staking_contract: String,
+    nft_contract: String,
+) -> Result<CosmosMsg, ContractError> {
+    let transfer: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: nft_contract,
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft { 
+            recipient: nft_staking_contract, 
+            token_id, 
+        })?,
+        funds: vec![]
+    });
+
+    Ok(transfer)
+}
+
 // query rewards token balance.
 pub fn query_rewards_token_balance(
     deps: Deps,
@@ -263,6 +578,82 @@ pub fn query_rewards_token_balance(
     Ok(balance_response)
 }
 
+// check the nft staking contract is the minter of the rewards token.
+// the rewards token which does not respond to the minter query or has no minter is not minted by the contract.
+pub fn query_is_reward_minter(
+    deps: Deps,
+    nft_staking_contract: String,
+    rewards_token_contract: String,
+) -> Result<bool, ContractError> {
+    let minter: StdResult<Option<MinterResponse>> = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
+        contract_addr: rewards_token_contract,
+        msg: to_binary(&Cw20QueryMsg::Minter {})?,
+    }));
+
+    Ok(matches!(minter, Ok(Some(t)) if t.minter == nft_staking_contract))
+}
+
+// probe the rewards token contract and the whitelisted nft contract.
+// the contracts which are not respond to the token info and contract info query are rejected.
+pub fn validate_token_contracts(
+    deps: Deps,
+    config: Config,
+) -> Result<(), ContractError> {
+    let token_info: StdResult<TokenInfoResponse> = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
+        contract_addr: config.rewards_token_contract.clone(),
+        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+    }));
+    if let Err(e) = token_info {
+        return Err(ContractError::RewardsTokenContractUnavailable {
+            rewards_token_contract: config.rewards_token_contract,
+            reason: e.to_string(),
+        })
+    }
+
+    let contract_info: StdResult<ContractInfoResponse> = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
+        contract_addr: config.white_listed_nft_contract.clone(),
+        msg: to_binary(&Cw721QueryMsg::ContractInfo {})?,
+    }));
+    if let Err(e) = contract_info {
+        return Err(ContractError::WhitelistedNftContractUnavailable {
+            white_listed_nft_contract: config.white_listed_nft_contract,
+            reason: e.to_string(),
+        })
+    }
+
+    Ok(())
+}
+
+// query owner of the nft token id in the nft contract.
+pub fn query_nft_owner(
+    deps: Deps,
+    nft_contract: String,
+    token_id: String,
+) -> StdResult<OwnerOfResponse> {
+    let owner_of_response: OwnerOfResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
+        contract_addr: nft_contract,
+        msg: to_binary(&Cw721QueryMsg::OwnerOf { 
+            token_id, 
+            include_expired: None,
+        })?,
+    }))?;
+
+    Ok(owner_of_response)
+}
+
+// check the query error means the queried token does not exist in the cw721 contract.
+// the not found error of the cw721 contract is wrapped as the generic error by the querier,
+// so only the exact message of the missing cw721 token info is matched, other errors which contain "not found" are not.
+pub fn is_not_found_error(
+    error: &StdError,
+) -> bool {
+    match error {
+        StdError::NotFound { .. } => true,
+        StdError::GenericErr { msg, .. } => msg.starts_with(CW721_TOKEN_NOT_FOUND_PREFIX) && msg.ends_with(CW721_TOKEN_NOT_FOUND_SUFFIX),
+        _ => false,
+    }
+}
+
 // update history of staker at the current cycle with a new difference in stake.
 pub fn update_histories(
     mut deps: DepsMut,
@@ -284,16 +675,23 @@ pub fn update_histories(
 }
 
 // update history
+// the history restarts when there is no next claim, because the previous snapshots are never computed again.
+// the history is bounded by MAX_SNAPSHOTS_PER_TOKEN, adjacent snapshots of the same stake are coalesced when it is exceeded.
 pub fn update_staker_history(
     deps: DepsMut,
     is_staked: bool,
     current_cycle: u64,
     staker_tokenid_key: String,
 ) -> Result<u64, ContractError> {
-    let staker_history_state = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone()).unwrap();
+    let staker_history_state = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone())?;
+    let next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?;
 
-    if !staker_history_state.is_none() {
-        let staker_history = staker_history_state.clone().unwrap();
+    let new_snapshot = Snapshot {
+        is_staked,
+        start_cycle: current_cycle,
+    };
+
+    if let Some(mut staker_history) = staker_history_state {
         let history_length = staker_history.len();
 
         // there is an existing snapshot.
@@ -302,90 +700,327 @@ pub fn update_staker_history(
 
         if snapshot.start_cycle == current_cycle {
             // update the snapshot if it starts on the current cycle.
-            let new_snapshot = Snapshot {
-                is_staked,
-                start_cycle: current_cycle,
-            };
-
-            staker_history.clone().append(&mut vec![new_snapshot]);
+            staker_history[snapshot_index as usize] = new_snapshot;
             STAKER_HISTORIES.save(deps.storage, staker_tokenid_key, &staker_history)?;
 
             return Ok(snapshot_index)
         } 
-    }
 
-    let new_snapshot = Snapshot {
-        is_staked,
-        start_cycle: current_cycle,
-    };
+        if let Some(mut next_claim) = next_claim {
+            if staker_history.len() >= MAX_SNAPSHOTS_PER_TOKEN {
+                staker_history = coalesce_staker_history(staker_history, &mut next_claim);
+                if staker_history.len() >= MAX_SNAPSHOTS_PER_TOKEN {
+                    return Err(ContractError::TooManySnapshots {})
+                }
+                NEXT_CLAIMS.save(deps.storage, staker_tokenid_key.clone(), &next_claim)?;
+            }
+
+            // add a new snapshot in the history.
+            staker_history.push(new_snapshot);
+            STAKER_HISTORIES.save(deps.storage, staker_tokenid_key, &staker_history)?;
+
+            return Ok((staker_history.len() - 1) as u64)
+        }
+    }
 
     let staker_history = vec![new_snapshot];
 
-    // add a new snapshot in the history.
+    // add a new snapshot in the new history.
     STAKER_HISTORIES.save(deps.storage, staker_tokenid_key, &staker_history)?;
 
     Ok(0)
     
 }
 
-// calculate the amount of rewards for a staker over a capped number of periods.
-pub fn compute_rewards(
+// merge adjacent snapshots which have same stake into the earlier one.
+// the snapshot index of the next claim is moved to the merged snapshot.
+pub fn coalesce_staker_history(
+    staker_history: Vec<Snapshot>,
+    next_claim: &mut NextClaim,
+) -> Vec<Snapshot> {
+    let next_claim_index = next_claim.staker_snapshot_index as usize;
+    let mut coalesced: Vec<Snapshot> = vec![];
+    let mut removed_before_next_claim: u64 = 0;
+
+    for (i, snapshot) in staker_history.iter().enumerate() {
+        if !coalesced.is_empty() && coalesced[coalesced.len() - 1].is_staked == snapshot.is_staked {
+            if i <= next_claim_index {
+                removed_before_next_claim += 1;
+            }
+            continue
+        }
+        coalesced.push(*snapshot);
+    }
+    next_claim.staker_snapshot_index -= removed_before_next_claim;
+
+    coalesced
+}
+
+// get the period until which rewards of the unbonding or unbonded token are claimable, exclusive.
+// the period of the requested unbond time is included if the final period is prorated.
+pub fn get_unbond_end_claim_period(
     deps: Deps,
-    staker_tokenid_key: String,
-    periods: u64,
-    now: u64,
+    token_info: TokenInfo,
     start_timestamp: u64,
     config: Config,
-    token_id: String,
-) -> Result<(Claim, NextClaim), ContractError> {
-    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
-    if periods > max_compute_period {
-        return Err(ContractError::InvalidMaxPeriod { 
-            periods: periods, 
-            max_compute_period, 
-        })
-    }
-    let mut claim = Claim::default();
-    let mut next_claim = NextClaim::default();
-
-    // computing 0 periods.
-    if periods == 0 {
-        return Ok((claim, next_claim))
+) -> Result<u64, ContractError> {
+    let unbond_period = get_current_period(token_info.req_unbond_time, start_timestamp, config)?;
+    if PRORATE_FINAL_PERIOD.may_load(deps.storage)?.unwrap_or(false) {
+        return Ok(unbond_period + 1)
     }
 
-    next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone()).unwrap().unwrap();
-    claim.start_period = next_claim.period;
+    Ok(unbond_period)
+}
 
-    // nothing has been staked yet.
-    if claim.start_period == 0 {
-        return Ok((claim, next_claim))
+// get the cycle until which the unbonding or unbonded token accrues rewards, exclusive.
+// it is none if the final period is not prorated, then the whole periods before the period of the requested unbond time are computed.
+pub fn get_prorated_end_cycle(
+    deps: Deps,
+    token_info: TokenInfo,
+    start_timestamp: u64,
+    config: Config,
+) -> Result<Option<u64>, ContractError> {
+    if token_info.bond_status != UNBONDING && token_info.bond_status != UNBONDED {
+        return Ok(None)
+    }
+    if !PRORATE_FINAL_PERIOD.may_load(deps.storage)?.unwrap_or(false) {
+        return Ok(None)
     }
 
-    let mut end_claim_period = get_current_period(now, start_timestamp, config.clone())?;
+    Ok(Some(get_cycle(token_info.req_unbond_time, start_timestamp, config)?))
+}
 
-    let token_info = TOKEN_INFOS.load(deps.storage, token_id)?;
+// get the period until which rewards are claimable, exclusive.
+pub fn get_end_claim_period(
+    deps: Deps,
+    token_info: TokenInfo,
+    now: u64,
+    start_timestamp: u64,
+    config: Config,
+) -> Result<u64, ContractError> {
+    let mut end_claim_period = get_current_period(now, start_timestamp, config.clone())?;
     
     // resitrict constantly supplied rewards after the staker requests unbond.
     // the current period to compute rewards is replaced to requested unbond time.
     if token_info.bond_status == UNBONDING || token_info.bond_status == UNBONDED {
-        end_claim_period = get_current_period(token_info.req_unbond_time, start_timestamp, config.clone())?;
+        end_claim_period = get_unbond_end_claim_period(deps, token_info, start_timestamp, config)?;
     }
 
-    // current period is not claimable.
-    if next_claim.period == end_claim_period {
-        return Ok((claim, next_claim))
+    // rewards are not accrued from the emission end period.
+    let emission_end_period = EMISSION_END_PERIOD.may_load(deps.storage)?;
+    if let Some(emission_end_period) = emission_end_period {
+        end_claim_period = end_claim_period.min(emission_end_period);
     }
 
-    // retrieve the next snapshots if they exist.
-    let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone()).unwrap().unwrap();
+    // the pool constrained emission simulates the undecided periods up to the max number of the emission ledger.
+    if POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false) {
+        let ledger = EMISSION_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+        end_claim_period = end_claim_period.min(ledger.processed_period + 1 + MAX_EMISSION_LEDGER_PERIODS);
+    }
 
-    let s_state_data = staker_history[next_claim.clone().staker_snapshot_index as usize].clone();
-    let mut staker_snapshot = Snapshot::new(s_state_data.is_staked, s_state_data.start_cycle);
+    Ok(end_claim_period)
+}
 
-    let mut next_staker_snapshot = Snapshot::default();
-    if next_claim.staker_snapshot_index != staker_history.clone().len() as u64 - 1 {
-        let s_data = &staker_history.clone()[(next_claim.staker_snapshot_index + 1) as usize];
-        next_staker_snapshot = Snapshot::new(s_data.is_staked, s_data.start_cycle);
+// check the staker stakes at least min_tokens_to_earn nfts for the full period to earn rewards of the period.
+pub fn is_earnable_at_period(
+    deps: Deps,
+    staker: String,
+    period: u64,
+) -> StdResult<bool> {
+    let min_tokens_to_earn = MIN_TOKENS_TO_EARN.may_load(deps.storage)?.unwrap_or(0);
+    if min_tokens_to_earn == 0 {
+        return Ok(true)
+    }
+
+    let current_count = STAKER_NFT_COUNTS.may_load(deps.storage, staker.clone())?.unwrap_or(0);
+    let staker_nft_count = STAKER_NFT_COUNT_HISTORY.at_period(deps.storage, staker, period, current_count)?;
+
+    Ok(staker_nft_count >= min_tokens_to_earn)
+}
+
+// get the booster bps applied to rewards of the staker in the period.
+// it is zero if the staker does not stake any booster nft for the full period.
+pub fn booster_bps_of(
+    deps: Deps,
+    staker: String,
+    period: u64,
+    config: Config,
+) -> StdResult<u128> {
+    let current_count = BOOSTERS.may_load(deps.storage, staker.clone())?.unwrap_or(0);
+    let booster_count = BOOSTER_COUNT_HISTORY.at_period(deps.storage, staker, period, current_count)?;
+    if booster_count == 0 {
+        return Ok(0)
+    }
+
+    Ok(config.booster_bps as u128)
+}
+
+// get the decayed early staker boost bps of the period for the token deposited in the deposit period.
+// the token deposited from the boost deadline period does not get the boost.
+pub fn early_staker_boost_bps(
+    early_staker_boost: Option<EarlyStakerBoost>,
+    deposit_period: u64,
+    period: u64,
+) -> u128 {
+    let early_staker_boost = match early_staker_boost {
+        Some(t) => t,
+        None => return 0,
+    };
+    if deposit_period >= early_staker_boost.boost_deadline_period {
+        return 0
+    }
+
+    let boost_bps = early_staker_boost.boost_bps as u128;
+    let decay_end_period = early_staker_boost.boost_deadline_period + early_staker_boost.boost_decay_periods;
+    if period <= early_staker_boost.boost_deadline_period {
+        return boost_bps
+    }
+    if period >= decay_end_period {
+        return 0
+    }
+
+    boost_bps * (decay_end_period - period) as u128 / early_staker_boost.boost_decay_periods as u128
+}
+
+// get the multiplier bps of rewards of the token id.
+pub fn token_multiplier_bps(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<u128> {
+    let token_multiplier_bps = TOKEN_MULTIPLIERS.may_load(deps.storage, token_id)?.unwrap_or(DEFAULT_TOKEN_MULTIPLIER_BPS);
+
+    Ok(token_multiplier_bps as u128)
+}
+
+// multiply the rewards by the token multiplier bps.
+pub fn apply_token_multiplier(
+    amount: u128,
+    token_multiplier_bps: u128,
+) -> u128 {
+    amount * token_multiplier_bps / BPS_DENOMINATOR
+}
+
+// increase the rewards by the booster bps.
+pub fn apply_booster(
+    amount: u128,
+    booster_bps: u128,
+) -> u128 {
+    amount * (BPS_DENOMINATOR + booster_bps) / BPS_DENOMINATOR
+}
+
+// calculate the amount of rewards for a staker over a capped number of periods.
+pub fn compute_rewards(
+    deps: Deps,
+    staker_tokenid_key: String,
+    periods: u64,
+    now: u64,
+    start_timestamp: u64,
+    config: Config,
+    token_id: String,
+) -> Result<(Claim, NextClaim), ContractError> {
+    compute_rewards_from(deps, staker_tokenid_key, None, periods, now, start_timestamp, config, token_id)
+}
+
+// compute rewards from the given next claim instead of the saved next claim.
+// it is used to simulate consecutive claims without saving the next claim of each claim.
+// the parameters are the reward context of compute_rewards with the next claim to start from.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_rewards_from(
+    deps: Deps,
+    staker_tokenid_key: String,
+    from_next_claim: Option<NextClaim>,
+    periods: u64,
+    now: u64,
+    start_timestamp: u64,
+    config: Config,
+    token_id: String,
+) -> Result<(Claim, NextClaim), ContractError> {
+    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+    if periods > max_compute_period {
+        return Err(ContractError::InvalidMaxPeriod { 
+            periods, 
+            max_compute_period, 
+        })
+    }
+    let mut claim = Claim::default();
+    let mut next_claim = NextClaim::default();
+
+    // computing 0 periods.
+    if periods == 0 {
+        return Ok((claim, next_claim))
+    }
+
+    let token_multiplier_bps = token_multiplier_bps(deps, token_id.clone())?;
+    let token_info = TOKEN_INFOS.load(deps.storage, token_id)?;
+
+    // burned token does not accrue rewards anymore.
+    if token_info.burned {
+        return Ok((claim, next_claim))
+    }
+
+    // the early staker boost is added to the booster by the period.
+    let early_staker_boost = EARLY_STAKER_BOOST.may_load(deps.storage)?;
+    let deposit_period = get_period(token_info.deposit_cycle, config.clone())?;
+
+    // the cycles from the requested unbond time do not accrue rewards in the prorated final period.
+    let prorated_end_cycle = get_prorated_end_cycle(deps, token_info.clone(), start_timestamp, config.clone())?;
+
+    next_claim = match from_next_claim {
+        Some(t) => t,
+        None => NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone()).unwrap().unwrap(),
+    };
+    claim.start_period = next_claim.period;
+
+    // nothing has been staked yet.
+    if claim.start_period == 0 {
+        return Ok((claim, next_claim))
+    }
+
+    // rewards of a computation are capped by periods.
+    // the period exceeding the max reward is not computed and remains claimable in the next computation.
+    // if even the first period exceeds the max reward, the rewards of the period are capped at the max reward
+    // and the excess is carried on the next claim.
+    let max_reward = MAX_REWARD_PER_CLAIM_PER_TOKEN.may_load(deps.storage)?;
+
+    // the rewards of the periods from the cutover period are paid in the secondary rewards token.
+    let cutover_period = REWARDS_TOKEN_CUTOVER.may_load(deps.storage)?.map(|t| t.cutover_period);
+
+    // the carried excess of the previous period is paid before computing the next periods.
+    if next_claim.remainder > 0 {
+        let carried = next_claim.remainder;
+        claim.amount = max_reward.map_or(carried, |t| carried.min(t));
+        next_claim.remainder = carried - claim.amount;
+        // the carried excess belongs to the period before the next claim.
+        if cutover_period.is_some_and(|t| next_claim.period > t) {
+            claim.secondary_amount = claim.amount;
+        }
+        if next_claim.remainder > 0 {
+            return Ok((claim, next_claim))
+        }
+    }
+
+    let end_claim_period = get_end_claim_period(deps, token_info.clone(), now, start_timestamp, config.clone())?;
+
+    // current period is not claimable.
+    if next_claim.period >= end_claim_period {
+        return Ok((claim, next_claim))
+    }
+
+    // retrieve the next snapshots if they exist.
+    let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone()).unwrap().unwrap();
+
+    // the snapshot index of the next claim must point to the staker history.
+    if next_claim.staker_snapshot_index as usize >= staker_history.len() {
+        return Err(ContractError::CorruptedNextClaim {})
+    }
+
+    let s_state_data = staker_history[next_claim.clone().staker_snapshot_index as usize];
+    let mut staker_snapshot = Snapshot::new(s_state_data.is_staked, s_state_data.start_cycle);
+
+    let mut next_staker_snapshot = Snapshot::default();
+    if next_claim.staker_snapshot_index != staker_history.clone().len() as u64 - 1 {
+        let s_data = &staker_history.clone()[(next_claim.staker_snapshot_index + 1) as usize];
+        next_staker_snapshot = Snapshot::new(s_data.is_staked, s_data.start_cycle);
     }
 
     // exclues the current period.
@@ -398,14 +1033,34 @@ pub fn compute_rewards(
     // next_claim.period will be updated to this value after exiting the loop.
     let end_claim_period = next_claim.period + claim.periods;
 
+    // the rewards schedule is loaded once for every period.
+    let reward_per_cycle = REWARDS_SCHEDULE.may_load(deps.storage)?;
+    if reward_per_cycle.is_none() {
+        return Err(ContractError::InvalidRewardsSchedule {})
+    }
+    let reward_per_cycle = reward_per_cycle.unwrap();
+
+    // in the shared per period mode, the reward per period is split by the staked count of each period.
+    let emission_mode = EMISSION_MODE.may_load(deps.storage)?.unwrap_or(EmissionMode::Flat);
+    let reward_per_period = REWARD_PER_PERIOD.may_load(deps.storage)?.unwrap_or(0);
+
+    let unfunded = unfunded_periods(deps, next_claim.period, end_claim_period, config.clone())?;
+    let carried_amount = claim.amount;
+
     // iterate over periods.
     while next_claim.period != end_claim_period {
+        let prev_amount = claim.amount;
+        let prev_staker_snapshot_index = next_claim.staker_snapshot_index;
+
         let next_period_start_cycle = next_claim.period * config.clone().period_length_in_cycles + 1;
-        let reward_per_cycle = REWARDS_SCHEDULE.may_load(deps.storage).unwrap();
-        if reward_per_cycle.is_none() {
-            return Err(ContractError::InvalidRewardsSchedule {})
+
+        let mut staked_count: u128 = 0;
+        if emission_mode == EmissionMode::SharedPerPeriod {
+            staked_count = staked_count_at_period(deps, next_claim.period)?;
         }
-        let reward_per_cycle = reward_per_cycle.unwrap();
+        let period_booster_bps = booster_bps_of(deps, token_info.clone().owner, next_claim.period, config.clone())?
+            + early_staker_boost_bps(early_staker_boost.clone(), deposit_period, next_claim.period);
+        let is_earnable = is_earnable_at_period(deps, token_info.clone().owner, next_claim.period)?;
 
         let mut start_cycle = next_period_start_cycle - config.clone().period_length_in_cycles;
         let mut end_cycle = 0;
@@ -421,16 +1076,26 @@ pub fn compute_rewards(
             // find the range-to-claim ending cycle, where the current staker snapshot and the current period no longer overlap.
             // the end cycle is exclusive of the range-to-claim and represents the beginning cycle of the next range-to-claim.
             end_cycle = next_period_start_cycle;
-            if staker_snapshot.is_staked && reward_per_cycle != 0 {
-                let snapshot_reward = (end_cycle - start_cycle) as u128 * reward_per_cycle;
-                claim.amount = claim.amount.add(snapshot_reward)
+            let accrual_end_cycle = prorated_end_cycle.map_or(end_cycle, |t| t.min(end_cycle)).max(start_cycle);
+            if staker_snapshot.is_staked && is_earnable {
+                let snapshot_reward = match emission_mode {
+                    EmissionMode::Flat => (accrual_end_cycle - start_cycle) as u128 * reward_per_cycle,
+                    EmissionMode::SharedPerPeriod => {
+                        if staked_count == 0 {
+                            0
+                        } else {
+                            (accrual_end_cycle - start_cycle) as u128 * reward_per_period / (config.period_length_in_cycles as u128 * staked_count)
+                        }
+                    },
+                };
+                claim.amount = claim.amount.add(apply_token_multiplier(apply_booster(snapshot_reward, period_booster_bps), token_multiplier_bps))
             }
 
             // advance the current staker snapshot to the next (if any) 
             // if its cycle range has been fully processed and if the next snapshot starts at most on next period first cycle.
             if next_staker_snapshot.start_cycle == end_cycle {
                 staker_snapshot = next_staker_snapshot;
-                next_claim.staker_snapshot_index = next_claim.staker_snapshot_index + 1;
+                next_claim.staker_snapshot_index += 1;
 
                 if next_claim.staker_snapshot_index != (staker_history.len() - 1) as u64 {
                     next_staker_snapshot = staker_history[(next_claim.staker_snapshot_index + 1) as usize];
@@ -439,22 +1104,633 @@ pub fn compute_rewards(
                 }
             } 
         }
-        next_claim.period = next_claim.period + 1;   
+
+        // the unfunded period of the pool constrained emission does not accrue rewards.
+        if unfunded.contains(&next_claim.period) {
+            claim.amount = prev_amount;
+        }
+
+        if max_reward.is_some_and(|t| claim.amount > t) {
+            if next_claim.period == claim.start_period && carried_amount == 0 {
+                let capped_amount = max_reward.unwrap_or(claim.amount);
+                next_claim.remainder = claim.amount - capped_amount;
+                claim.amount = capped_amount;
+            } else {
+                claim.amount = prev_amount;
+                next_claim.staker_snapshot_index = prev_staker_snapshot_index;
+                break
+            }
+        }
+        if cutover_period.is_some_and(|t| next_claim.period >= t) {
+            claim.secondary_amount = claim.secondary_amount + claim.amount - prev_amount;
+        }
+        next_claim.period += 1;   
     }
+    claim.periods = next_claim.period - claim.start_period;
 
     Ok((claim, next_claim))
 
 }
 
-// manage the number of staked nfts which nft staking contract owns.
+// calculate rewards accrued in the in-progress period, prorated to the second.
+// it is only an estimate for display, the rewards are not claimable until the period completes.
+// the max reward per claim per token is not applied.
+// the parameters are the reward context shared with compute_rewards.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_in_progress_rewards(
+    deps: Deps,
+    staker_tokenid_key: String,
+    token_info: TokenInfo,
+    now: u64,
+    start_timestamp: u64,
+    config: Config,
+    token_id: String,
+) -> Result<u128, ContractError> {
+    // the unbonding, unbonded and burned tokens do not accrue rewards in the in-progress period.
+    if token_info.bond_status != BONDED || token_info.burned {
+        return Ok(0)
+    }
+
+    let current_period = get_current_period(now, start_timestamp, config.clone())?;
+    let emission_end_period = EMISSION_END_PERIOD.may_load(deps.storage)?;
+    if emission_end_period.is_some() && emission_end_period.unwrap() <= current_period {
+        return Ok(0)
+    }
+
+    if !is_earnable_at_period(deps, token_info.clone().owner, current_period)? {
+        return Ok(0)
+    }
+    let deposit_period = get_period(token_info.deposit_cycle, config.clone())?;
+    let booster_bps = booster_bps_of(deps, token_info.owner, current_period, config.clone())?
+        + early_staker_boost_bps(EARLY_STAKER_BOOST.may_load(deps.storage)?, deposit_period, current_period);
+
+    let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key)?;
+    if staker_history.is_none() {
+        return Ok(0)
+    }
+    let staker_history = staker_history.unwrap();
+    let last_staker_snapshot = staker_history[staker_history.len() - 1];
+    if !last_staker_snapshot.is_staked {
+        return Ok(0)
+    }
+
+    // accrual starts from the later of the current period start and the last staking.
+    let period_start_cycle = (current_period - 1) * config.period_length_in_cycles + 1;
+    let mut start_cycle = period_start_cycle;
+    if last_staker_snapshot.start_cycle > start_cycle {
+        start_cycle = last_staker_snapshot.start_cycle;
+    }
+    let start_time = start_timestamp + (start_cycle - 1) * config.cycle_length_in_seconds;
+    if now <= start_time {
+        return Ok(0)
+    }
+    let elapsed = (now - start_time) as u128;
+
+    let reward_per_cycle = REWARDS_SCHEDULE.may_load(deps.storage)?;
+    if reward_per_cycle.is_none() {
+        return Err(ContractError::InvalidRewardsSchedule {})
+    }
+
+    let amount = match EMISSION_MODE.may_load(deps.storage)?.unwrap_or(EmissionMode::Flat) {
+        EmissionMode::Flat => elapsed * reward_per_cycle.unwrap() / config.cycle_length_in_seconds as u128,
+        EmissionMode::SharedPerPeriod => {
+            let staked_count = staked_count_at_period(deps, current_period)?;
+            if staked_count == 0 {
+                0
+            } else {
+                let reward_per_period = REWARD_PER_PERIOD.may_load(deps.storage)?.unwrap_or(0);
+                let period_length_in_seconds = (config.period_length_in_cycles * config.cycle_length_in_seconds) as u128;
+                elapsed * reward_per_period / (period_length_in_seconds * staked_count)
+            }
+        },
+    };
+
+    Ok(apply_token_multiplier(apply_booster(amount, booster_bps), token_multiplier_bps(deps, token_id)?))
+}
+
+// calculate rewards which the token earned in exactly one period against the staker history.
+// the period which is not completed yet or from the requested unbond time is not earned, it returns zero.
+// snapshots which are freed by claims cannot be recomputed, so periods before the history return zero.
+// the max reward per claim per token is applied to a claim, not to a period, so it is not applied.
+// the parameters are the reward context shared with compute_rewards.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_reward_for_period(
+    deps: Deps,
+    staker_tokenid_key: String,
+    token_info: TokenInfo,
+    period: u64,
+    now: u64,
+    start_timestamp: u64,
+    config: Config,
+    token_id: String,
+) -> Result<u128, ContractError> {
+    if token_info.burned || period == 0 {
+        return Ok(0)
+    }
+
+    let end_claim_period = get_end_claim_period(deps, token_info.clone(), now, start_timestamp, config.clone())?;
+    if period >= end_claim_period {
+        return Ok(0)
+    }
+    if !unfunded_periods(deps, period, period + 1, config.clone())?.is_empty() {
+        return Ok(0)
+    }
+
+    if !is_earnable_at_period(deps, token_info.clone().owner, period)? {
+        return Ok(0)
+    }
+    let deposit_period = get_period(token_info.deposit_cycle, config.clone())?;
+    let prorated_end_cycle = get_prorated_end_cycle(deps, token_info.clone(), start_timestamp, config.clone())?;
+    let booster_bps = booster_bps_of(deps, token_info.owner, period, config.clone())?
+        + early_staker_boost_bps(EARLY_STAKER_BOOST.may_load(deps.storage)?, deposit_period, period);
+
+    let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key)?;
+    if staker_history.is_none() {
+        return Ok(0)
+    }
+    let staker_history = staker_history.unwrap();
+    let token_multiplier_bps = token_multiplier_bps(deps, token_id)?;
+
+    let reward_per_cycle = REWARDS_SCHEDULE.may_load(deps.storage)?;
+    if reward_per_cycle.is_none() {
+        return Err(ContractError::InvalidRewardsSchedule {})
+    }
+    let reward_per_cycle = reward_per_cycle.unwrap();
+
+    let emission_mode = EMISSION_MODE.may_load(deps.storage)?.unwrap_or(EmissionMode::Flat);
+    let reward_per_period = REWARD_PER_PERIOD.may_load(deps.storage)?.unwrap_or(0);
+    let mut staked_count: u128 = 0;
+    if emission_mode == EmissionMode::SharedPerPeriod {
+        staked_count = staked_count_at_period(deps, period)?;
+    }
+
+    // the end cycle is exclusive as same as compute_rewards.
+    let period_start_cycle = (period - 1) * config.period_length_in_cycles + 1;
+    let period_end_cycle = prorated_end_cycle.map_or(period * config.period_length_in_cycles + 1, |t| t.min(period * config.period_length_in_cycles + 1));
+    let mut amount: u128 = 0;
+
+    // the last snapshot lasts until the period ends.
+    for snapshot_range in snapshot_ranges(&staker_history) {
+        if !snapshot_range.is_staked {
+            continue
+        }
+
+        let end_cycle = snapshot_range.end_cycle.map_or(period_end_cycle, |t| t.min(period_end_cycle));
+        let start_cycle = snapshot_range.start_cycle.max(period_start_cycle);
+        if start_cycle >= end_cycle {
+            continue
+        }
+
+        let snapshot_reward = match emission_mode {
+            EmissionMode::Flat => (end_cycle - start_cycle) as u128 * reward_per_cycle,
+            EmissionMode::SharedPerPeriod => {
+                if staked_count == 0 {
+                    0
+                } else {
+                    (end_cycle - start_cycle) as u128 * reward_per_period / (config.period_length_in_cycles as u128 * staked_count)
+                }
+            },
+        };
+        amount += apply_token_multiplier(apply_booster(snapshot_reward, booster_bps), token_multiplier_bps);
+    }
+
+    Ok(amount)
+}
+
+// expand snapshots to the ranges of cycles, each snapshot lasts until the next snapshot starts.
+pub fn snapshot_ranges(
+    staker_history: &[Snapshot],
+) -> Vec<SnapshotRange> {
+    staker_history
+        .iter()
+        .enumerate()
+        .map(|(i, staker_snapshot)| SnapshotRange {
+            start_cycle: staker_snapshot.start_cycle,
+            end_cycle: staker_history.get(i + 1).map(|next_snapshot| next_snapshot.start_cycle),
+            is_staked: staker_snapshot.is_staked,
+        })
+        .collect()
+}
+
+// compute all remained rewards of staker_tokenid_key by max compute period unit.
+// next claim is saved every computing, so the returned value is the total amount to transfer,
+// with the part of the amount which is paid in the secondary rewards token.
+pub fn compute_remain_rewards(
+    deps: DepsMut,
+    staker_tokenid_key: String,
+    now: u64,
+    start_timestamp: u64,
+    config: Config,
+    token_id: String,
+) -> Result<(u128, u128), ContractError> {
+    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+    let mut remain_rewards = true;
+    let mut remain_rewards_value: u128 = 0;
+    let mut secondary_rewards_value: u128 = 0;
+
+    while remain_rewards {
+        let compute_reward = compute_rewards(
+            deps.as_ref(), 
+            staker_tokenid_key.clone(), 
+            max_compute_period,
+            now,
+            start_timestamp,
+            config.clone(),
+            token_id.clone()
+        )?;
+
+        if compute_reward.0.amount != 0 {
+            remain_rewards_value += compute_reward.0.amount;
+            secondary_rewards_value += compute_reward.0.secondary_amount;
+            // next claim set last computed rewards.
+            NEXT_CLAIMS.save(deps.storage, staker_tokenid_key.clone(), &compute_reward.1)?;
+        } else {
+            remain_rewards = false
+        }
+    }
+
+    Ok((remain_rewards_value, secondary_rewards_value))
+}
+
+// pay out the deferred rewards of the previous staker when the unbonded token is staked again.
+// the rewards are capped at the requested unbond time because the token info is still unbonded.
+// if the rewards follow the nft, they are paid to the new staker instead of the previous staker.
+pub fn settle_deferred_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    config: Config,
+    token_id: String,
+    token_info: TokenInfo,
+    new_staker: String,
+) -> Result<(u128, Vec<CosmosMsg>, Vec<Event>), ContractError> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut events: Vec<Event> = vec![];
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if token_info.bond_status != UNBONDED || token_info.owner.is_empty() || start_timestamp.is_none() {
+        return Ok((0, messages, events))
+    }
+
+    let staker = token_info.owner;
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+    if NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?.is_none() {
+        return Ok((0, messages, events))
+    }
+
+    let (deferred_rewards, secondary_rewards) = compute_remain_rewards(deps.branch(), staker_tokenid_key.clone(), env.block.time.seconds(), start_timestamp.unwrap(), config.clone(), token_id)?;
+    NEXT_CLAIMS.remove(deps.storage, staker_tokenid_key.clone());
+
+    if deferred_rewards != 0 {
+        // the rewards which follow the nft are paid to the new staker.
+        let mut recipient = new_staker;
+        if !REWARDS_FOLLOW_NFT.may_load(deps.storage)?.unwrap_or(false) {
+            recipient = claim_recipient(deps.as_ref(), env.clone(), staker_tokenid_key, staker, None)?;
+        }
+        messages = execute_rewards_transfer(deps.branch(), env.clone(), config.clone(), CHECK_REWARDS_POOL_AIM_BOTH, recipient, deferred_rewards, secondary_rewards)?;
+        events = check_auto_disable(deps.branch(), env, config.clone(), deferred_rewards - secondary_rewards)?;
+        increase_total_paid_out(deps.branch(), deferred_rewards)?;
+    }
+
+    Ok((deferred_rewards, messages, events))
+}
+
+// increase the config version after the config is saved.
+pub fn increase_config_version(
+    deps: DepsMut,
+) -> Result<u64, ContractError> {
+    let config_version = CONFIG_VERSION.may_load(deps.storage)?.unwrap_or(0) + 1;
+    CONFIG_VERSION.save(deps.storage, &config_version)?;
+
+    Ok(config_version)
+}
+
+// check the config version which the client expects, it is not checked if it is not requested.
+pub fn check_config_version(
+    deps: Deps,
+    expected_config_version: Option<u64>,
+) -> Result<(), ContractError> {
+    if let Some(expected) = expected_config_version {
+        let current = CONFIG_VERSION.may_load(deps.storage)?.unwrap_or(0);
+        if expected != current {
+            return Err(ContractError::ConfigChanged { expected, current })
+        }
+    }
+
+    Ok(())
+}
+
+// accumulate rewards which are transferred to stakers or their recipients.
+pub fn increase_total_paid_out(
+    deps: DepsMut,
+    amount: u128,
+) -> Result<u128, ContractError> {
+    let total_paid_out = TOTAL_PAID_OUT.may_load(deps.storage)?.unwrap_or(0) + amount;
+    TOTAL_PAID_OUT.save(deps.storage, &total_paid_out)?;
+
+    Ok(total_paid_out)
+}
+
+// increase the counter of the operational metrics.
+pub fn increase_counter(
+    deps: DepsMut,
+    counter: Item<u64>,
+) -> Result<u64, ContractError> {
+    let count = counter.may_load(deps.storage)?.unwrap_or(0) + 1;
+    counter.save(deps.storage, &count)?;
+
+    Ok(count)
+}
+
+// append the receipt of the claim with the next monotonic index.
+pub fn record_claim_receipt(
+    deps: DepsMut,
+    receipt: ClaimReceipt,
+) -> Result<u64, ContractError> {
+    let index = CLAIM_RECEIPT_COUNT.may_load(deps.storage)?.unwrap_or(0) + 1;
+    CLAIM_RECEIPTS.save(deps.storage, index, &receipt)?;
+    CLAIM_RECEIPT_COUNT.save(deps.storage, &index)?;
+
+    Ok(index)
+}
+
+// save the token info and keep the per-staker index of the token ids by the owner of the token info.
+pub fn save_token_info(
+    storage: &mut dyn Storage,
+    token_id: String,
+    token_info: &TokenInfo,
+) -> StdResult<()> {
+    let prev_token_info = TOKEN_INFOS.may_load(storage, token_id.clone())?;
+    if let Some(prev_token_info) = prev_token_info {
+        if !prev_token_info.owner.is_empty() && prev_token_info.owner != token_info.owner {
+            STAKER_TOKENS.remove(storage, (prev_token_info.owner, token_id.clone()));
+        }
+    }
+    if !token_info.owner.is_empty() {
+        STAKER_TOKENS.save(storage, (token_info.owner.clone(), token_id.clone()), &true)?;
+    }
+
+    TOKEN_INFOS.save(storage, token_id, token_info)
+}
+
+// manage the number of staked nfts which nft staking contract owns, in total, by staker and by nft contract.
 pub fn manage_number_nfts(
     deps: DepsMut,
+    staker: String,
+    nft_contract: String,
     is_increase: bool,
+    now: u64,
 ) {
     let number_of_staked_nfts = NUMBER_OF_STAKED_NFTS.load(deps.storage).unwrap();
+    let staker_nft_count = STAKER_NFT_COUNTS.may_load(deps.storage, staker.clone()).unwrap().unwrap_or(0);
+    let collection_count = STAKED_COUNT_BY_COLLECTION.may_load(deps.storage, nft_contract.clone()).unwrap().unwrap_or(0);
     if is_increase {
         NUMBER_OF_STAKED_NFTS.save(deps.storage, &(number_of_staked_nfts + 1)).unwrap();
+        STAKER_NFT_COUNTS.save(deps.storage, staker.clone(), &(staker_nft_count + 1)).unwrap();
+        STAKED_COUNT_BY_COLLECTION.save(deps.storage, nft_contract, &(collection_count + 1)).unwrap();
     } else {
         NUMBER_OF_STAKED_NFTS.save(deps.storage, &(number_of_staked_nfts - 1)).unwrap();
+        if staker_nft_count <= 1 {
+            STAKER_NFT_COUNTS.remove(deps.storage, staker.clone());
+        } else {
+            STAKER_NFT_COUNTS.save(deps.storage, staker.clone(), &(staker_nft_count - 1)).unwrap();
+        }
+        if collection_count <= 1 {
+            STAKED_COUNT_BY_COLLECTION.remove(deps.storage, nft_contract);
+        } else {
+            STAKED_COUNT_BY_COLLECTION.save(deps.storage, nft_contract, &(collection_count - 1)).unwrap();
+        }
     }
+
+    // the number of staked nfts is recorded by period to split the reward per period.
+    // the last recorded number in the period is used as the staked count of the period.
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage).unwrap();
+    if let Some(start_timestamp) = start_timestamp {
+        let config = CONFIG_STATE.load(deps.storage).unwrap();
+        let current_period = get_current_period(now, start_timestamp, config).unwrap();
+        let number_of_staked_nfts = NUMBER_OF_STAKED_NFTS.load(deps.storage).unwrap();
+
+        // the peak includes the number before the first change in the period.
+        if POOL_CONSTRAINED_EMISSION.may_load(deps.storage).unwrap().unwrap_or(false) {
+            let prev_peak = match PEAK_STAKED_COUNT_HISTORY.may_load(deps.storage, current_period).unwrap() {
+                Some(t) => t,
+                None => staked_count_at_period(deps.as_ref(), current_period).unwrap(),
+            };
+            PEAK_STAKED_COUNT_HISTORY.save(deps.storage, current_period, &prev_peak.max(number_of_staked_nfts)).unwrap();
+        }
+        STAKED_COUNT_HISTORY.save(deps.storage, current_period, &number_of_staked_nfts).unwrap();
+
+        // the number of staked nfts of the staker is recorded by period to check min_tokens_to_earn of each period.
+        let new_staker_nft_count = STAKER_NFT_COUNTS.may_load(deps.storage, staker.clone()).unwrap().unwrap_or(0);
+        STAKER_NFT_COUNT_HISTORY.record(deps.storage, staker, current_period, staker_nft_count, new_staker_nft_count).unwrap();
+    }
+}
+
+// update the number of staked booster nfts of the staker.
+// the number is recorded by period after start, so the booster boosts only the periods it is staked for the full period.
+pub fn manage_number_boosters(
+    deps: DepsMut,
+    staker: String,
+    is_increase: bool,
+    now: u64,
+) -> Result<u64, ContractError> {
+    let booster_count = BOOSTERS.may_load(deps.storage, staker.clone())?.unwrap_or(0);
+    let new_booster_count = if is_increase { booster_count + 1 } else { booster_count.saturating_sub(1) };
+    if new_booster_count == 0 {
+        BOOSTERS.remove(deps.storage, staker.clone());
+    } else {
+        BOOSTERS.save(deps.storage, staker.clone(), &new_booster_count)?;
+    }
+
+    if let Some(start_timestamp) = START_TIMESTAMP.may_load(deps.storage)? {
+        let config = CONFIG_STATE.load(deps.storage)?;
+        let current_period = get_current_period(now, start_timestamp, config)?;
+        BOOSTER_COUNT_HISTORY.record(deps.storage, staker, current_period, booster_count, new_booster_count)?;
+    }
+
+    Ok(new_booster_count)
+}
+
+// get the number of staked nfts of the period.
+// the periods which have no record use the number of the latest recorded period before.
+pub fn staked_count_at_period(
+    deps: Deps,
+    period: u64,
+) -> StdResult<u128> {
+    let staked_count = STAKED_COUNT_HISTORY
+        .range(deps.storage, None, Some(Bound::inclusive(period)), Order::Descending)
+        .next()
+        .transpose()?;
+
+    Ok(staked_count.map(|(_, count)| count).unwrap_or(0))
+}
+
+// the max amount which every staked nft can earn in the period, it is deducted from the budget of the pool constrained emission.
+// the peak staked count, the booster, the early staker boost and the max token multiplier of every nft are assumed, so the actual emission is not over the amount.
+pub fn period_emission(
+    deps: Deps,
+    period: u64,
+    max_token_multiplier_bps: u16,
+    config: Config,
+) -> Result<u128, ContractError> {
+    let emission_end_period = EMISSION_END_PERIOD.may_load(deps.storage)?;
+    if emission_end_period.is_some() && emission_end_period.unwrap() <= period {
+        return Ok(0)
+    }
+
+    let staked_count = staked_count_at_period(deps, period)?;
+    let peak_staked_count = PEAK_STAKED_COUNT_HISTORY.may_load(deps.storage, period)?.unwrap_or(staked_count);
+
+    let emission = match EMISSION_MODE.may_load(deps.storage)?.unwrap_or(EmissionMode::Flat) {
+        EmissionMode::Flat => {
+            let reward_per_cycle = REWARDS_SCHEDULE.may_load(deps.storage)?.unwrap_or(0);
+            reward_per_cycle * config.period_length_in_cycles as u128 * peak_staked_count
+        },
+        EmissionMode::SharedPerPeriod => {
+            if staked_count == 0 {
+                0
+            } else {
+                let reward_per_period = REWARD_PER_PERIOD.may_load(deps.storage)?.unwrap_or(0);
+                (reward_per_period * peak_staked_count).div_ceil(staked_count)
+            }
+        },
+    };
+
+    let booster_bps = if config.booster_contract.is_none() { 0 } else { config.booster_bps as u128 };
+    let early_staker_boost_bps = early_staker_boost_bps(EARLY_STAKER_BOOST.may_load(deps.storage)?, 0, period);
+    Ok(apply_token_multiplier(apply_booster(emission, booster_bps + early_staker_boost_bps), max_token_multiplier_bps as u128))
+}
+
+// get the max token multiplier bps assumed for every nft by the pool constrained emission.
+// the contract which set multipliers before the running max is kept takes the max of the multipliers.
+pub fn max_token_multiplier_bps(
+    deps: Deps,
+) -> StdResult<u16> {
+    if let Some(max_token_multiplier_bps) = MAX_TOKEN_MULTIPLIER_BPS.may_load(deps.storage)? {
+        return Ok(max_token_multiplier_bps)
+    }
+
+    let max_token_multiplier_bps = TOKEN_MULTIPLIERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|t| t.ok())
+        .map(|(_, bps)| bps)
+        .fold(DEFAULT_TOKEN_MULTIPLIER_BPS, |max, bps| max.max(bps));
+
+    Ok(max_token_multiplier_bps)
+}
+
+// decide the completed periods after the processed period of the ledger in order.
+// the period is funded if the budget covers the period emission, otherwise it is recorded as unfunded.
+// at most MAX_EMISSION_LEDGER_PERIODS periods are decided per call, the rest is decided by the next calls.
+pub fn advance_emission_ledger(
+    deps: DepsMut,
+    now: u64,
+    config: Config,
+) -> Result<(), ContractError> {
+    if !POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false) {
+        return Ok(())
+    }
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(())
+    }
+
+    let current_period = get_current_period(now, start_timestamp.unwrap(), config.clone())?;
+    let mut ledger = EMISSION_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+    let end_period = current_period.min(ledger.processed_period + 1 + MAX_EMISSION_LEDGER_PERIODS);
+    let max_token_multiplier_bps = max_token_multiplier_bps(deps.as_ref())?;
+    while ledger.processed_period + 1 < end_period {
+        let period = ledger.processed_period + 1;
+        let emission = period_emission(deps.as_ref(), period, max_token_multiplier_bps, config.clone())?;
+        if emission <= ledger.budget {
+            ledger.budget -= emission;
+        } else {
+            UNFUNDED_PERIODS.save(deps.storage, period, &true)?;
+        }
+        ledger.processed_period = period;
+    }
+    EMISSION_LEDGER.save(deps.storage, &ledger)?;
+
+    Ok(())
+}
+
+// advance the emission ledger and check every completed period is decided.
+// the deposit, the withdrawal and the payout of the remain rewards change the budget or pay the undecided periods,
+// so they are rejected until the ledger is processed to the current period.
+pub fn settle_emission_ledger(
+    mut deps: DepsMut,
+    now: u64,
+    config: Config,
+) -> Result<(), ContractError> {
+    advance_emission_ledger(deps.branch(), now, config.clone())?;
+    if !POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false) {
+        return Ok(())
+    }
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(())
+    }
+
+    let current_period = get_current_period(now, start_timestamp.unwrap(), config)?;
+    let ledger = EMISSION_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+    if ledger.processed_period + 1 < current_period {
+        return Err(ContractError::EmissionLedgerNotSettled {
+            processed_period: ledger.processed_period,
+            current_period,
+        })
+    }
+
+    Ok(())
+}
+
+// change the budget of the pool constrained emission by the deposit or the withdrawal.
+// the completed periods must be decided by advance_emission_ledger before.
+pub fn update_emission_budget(
+    deps: DepsMut,
+    deposit: u128,
+    withdrawal: u128,
+) -> Result<(), ContractError> {
+    if !POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false) {
+        return Ok(())
+    }
+    let mut ledger = EMISSION_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+    ledger.budget = (ledger.budget + deposit).saturating_sub(withdrawal);
+    EMISSION_LEDGER.save(deps.storage, &ledger)?;
+
+    Ok(())
+}
+
+// get unfunded periods from the from period to the to period (exclusive) of the pool constrained emission.
+// the periods after the processed period of the ledger are simulated by the budget without saving, for queries.
+// at most MAX_EMISSION_LEDGER_PERIODS periods are simulated, the end claim period is capped by the same number.
+pub fn unfunded_periods(
+    deps: Deps,
+    from: u64,
+    to: u64,
+    config: Config,
+) -> Result<Vec<u64>, ContractError> {
+    if !POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false) || from >= to {
+        return Ok(vec![])
+    }
+    let ledger: EmissionLedger = EMISSION_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+
+    let mut periods: Vec<u64> = vec![];
+    let processed_end = to.min(ledger.processed_period + 1);
+    if from < processed_end {
+        periods = UNFUNDED_PERIODS
+            .keys(deps.storage, Some(Bound::inclusive(from)), Some(Bound::exclusive(processed_end)), Order::Ascending)
+            .collect::<StdResult<Vec<u64>>>()?;
+    }
+
+    let mut budget = ledger.budget;
+    let max_token_multiplier_bps = max_token_multiplier_bps(deps)?;
+    let simulated_end = to.min(ledger.processed_period + 1 + MAX_EMISSION_LEDGER_PERIODS);
+    for period in (ledger.processed_period + 1)..simulated_end {
+        let emission = period_emission(deps, period, max_token_multiplier_bps, config.clone())?;
+        if emission <= budget {
+            budget -= emission;
+        } else if period >= from {
+            periods.push(period);
+        }
+    }
+
+    Ok(periods)
 }
\ No newline at end of file
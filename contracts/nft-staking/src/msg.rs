@@ -7,16 +7,24 @@ use cw721_base::Extension;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{state::{Snapshot, TokenInfo, Claim, NextClaim, Grant, UNBONDING_DURATION, BONDED, UNBONDING, UNBONDED}, ContractError};
+use crate::{state::{Snapshot, SnapshotRange, TokenInfo, Claim, NextClaim, Grant, UNBONDING_DURATION, BONDED, UNBONDING, UNBONDED, EmissionMode, PendingWithdrawal, ClaimReceipt, RewardsTokenCutover, EarlyStakerBoost}, ContractError};
 
 pub const SUCCESS: &str = "success";
 
+// the way the estimated rewards are realized.
+pub const CLAIMABLE_VIA_CLAIM: &str = "claim";
+pub const CLAIMABLE_VIA_UNSTAKE: &str = "unstake";
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub cycle_length_in_seconds: u64,
     pub period_length_in_cycles: u64,
     pub white_listed_nft_contract: String,
     pub rewards_token_contract: String,
+    pub validate_contracts: Option<bool>,
+    pub min_cycle_length_override: Option<u64>,
+    // the owner instead of the instantiater, e.g. a multisig when a deploy key instantiates.
+    pub owner: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -30,32 +38,186 @@ pub enum ExecuteMsg {
     Revoke {
         address: String,
     },
+    ExtendGrant {
+        address: String,
+        expires: Option<Expiration>,
+    },
+    ReplaceWhitelistedContract {
+        old: String,
+        new: String,
+    },
     AddRewardsForPeriods {
         rewards_per_cycle: u128,
     },
+    SetRewardsPerPeriod {
+        rewards_per_period: u128,
+    },
     Receive(Cw20ReceiveMsg),
+    AddRewardsPoolFrom {
+        amount: u128,
+    },
     SetMaxComputePeriod {
         new_max_compute_period: u64,
     },
     SetUnbondingDuration {
         new_unbonding_duration: u64,
     },
-    Start {},
+    SetMinTokensToEarn {
+        new_min_tokens_to_earn: u64,
+    },
+    SetStakeForwarders {
+        new_stake_forwarders: Option<Vec<String>>,
+    },
+    SetClaimCliffPeriod {
+        new_claim_cliff_period: u64,
+    },
+    SetAutoDisableThreshold {
+        new_auto_disable_threshold: Option<u128>,
+    },
+    SetRequireApprovedRecipient {
+        require_approved_recipient: bool,
+    },
+    SetAllowPrestake {
+        allow_prestake: bool,
+    },
+    SetAllowPublicFunding {
+        allow_public_funding: bool,
+    },
+    SetRequireClaimBeforeUnstake {
+        require_claim_before_unstake: bool,
+    },
+    SetAutoFinalizeOnClaim {
+        auto_finalize_on_claim: bool,
+    },
+    SetProrateFinalPeriod {
+        prorate_final_period: bool,
+    },
+    SetRewardsFollowNft {
+        rewards_follow_nft: bool,
+    },
+    SetBooster {
+        booster_contract: Option<String>,
+        booster_bps: u16,
+    },
+    SetTokenMultiplier {
+        token_id: String,
+        bps: u16,
+    },
+    RefreshRewardMinter {},
+    SetEarlyStakerBoost {
+        boost_bps: u16,
+        boost_deadline_period: u64,
+        boost_decay_periods: u64,
+    },
+    SetRewardsTokenCutover {
+        secondary_rewards_token: String,
+        cutover_period: u64,
+    },
+    SetFeeOnTransferToken {
+        fee_on_transfer_token: bool,
+    },
+    SetMaxRewardPerClaimPerToken {
+        new_max_reward_per_claim_per_token: Option<u128>,
+    },
+    SetMinPoolBalanceToStake {
+        min_pool_balance_to_stake: Option<u128>,
+    },
+    SetEmissionEndPeriod {
+        new_emission_end_period: Option<u64>,
+    },
+    SetEmissionMode {
+        emission_mode: EmissionMode,
+        reward_per_period: Option<u128>,
+    },
+    SetMinUnstakeCycles {
+        new_min_unstake_cycles: u64,
+    },
+    SetPoolConstrainedEmission {
+        pool_constrained_emission: bool,
+    },
+    SetWithdrawTimelockSeconds {
+        new_withdraw_timelock_seconds: Option<u64>,
+    },
+    Start {
+        idempotent: Option<bool>,
+    },
     Disable {},
     Enable {},
+    SetDisabled {
+        disabled: bool,
+    },
     WithdrawRewardsPool {
         amount: u128,
+        recipient: Option<String>,
+    },
+    WithdrawAllRewardsPool {
+        recipient: Option<String>,
+    },
+    RequestWithdraw {
+        amount: u128,
+        recipient: Option<String>,
     },
-    WithdrawAllRewardsPool {},
+    ExecuteWithdraw {},
+    CancelWithdraw {},
+    SyncRewardsPool {},
+    PruneOrphanedClaims {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    SweepAbandonedRewards {
+        older_than_periods: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    IndexStakerTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    ProcessEmissionLedger {},
     ReceiveNft(Cw721ReceiveMsg),
+    StakeMany {
+        token_ids: Vec<String>,
+    },
+    UnstakeBooster {
+        token_id: String,
+    },
     UnstakeNft {
         token_id: String,
         claim_recipient_address: Option<String>,
+        claim_on_unstake: Option<bool>,
     },
     ClaimRewards {
         periods: u64,
         token_id: String,
         claim_recipient_address: Option<String>,
+        expected_config_version: Option<u64>,
+    },
+    ClaimAllRewards {
+        periods: u64,
+        claim_recipient_address: Option<String>,
+        start_after: Option<String>,
+    },
+    MarkTokenBurned {
+        token_id: String,
+    },
+    BlockToken {
+        token_id: String,
+    },
+    UnblockToken {
+        token_id: String,
+    },
+    SetTokenRewardRecipient {
+        token_id: String,
+        recipient: Option<String>,
+        expires: Option<Expiration>,
+    },
+    ApproveRewardRecipient {
+        token_id: String,
+        recipient: String,
+    },
+    SkipToCurrentPeriod {
+        token_id: String,
+        forfeit_amount: u128,
     },
 }
 
@@ -63,19 +225,50 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     GetConfig {},
+    FullSettings {},
+    Version {},
     GetCurrentCycleAndPeriod {},
     GetAllGrants {},
     GetRewardsSchedule {},
+    RawRewardsSchedule {},
     GetMaxComputePeriod {},
     GetUnbondingDuration {},
+    GetMinTokensToEarn {},
+    GetStakeForwarders {},
+    GetClaimCliffPeriod {},
+    GetAutoDisableThreshold {},
+    GetMaxRewardPerClaimPerToken {},
+    GetEmissionEndPeriod {},
+    GetEmissionMode {},
+    GetRewardsTokenCutover {},
+    GetEarlyStakerBoost {},
+    TokenMultiplier {
+        token_id: String,
+    },
+    IsRewardMinter {},
+    WhitelistedContracts {},
+    GetMinCycleLength {},
+    GetMinUnstakeCycles {},
     StartTime {},
     Disable {},
     TotalRewardsPool {},
+    TotalPaidOut {},
+    Metrics {},
     WithdrawRewardsPoolAmount {},
+    PendingWithdrawal {},
+    PendingWithdrawals {},
+    EmissionLedger {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     StakerHistory {
         staker: String,
         token_id: String,
     },
+    StakerHistoryExpanded {
+        staker: String,
+        token_id: String,
+    },
     TokenInfo {
         token_id: String,
     },
@@ -84,17 +277,101 @@ pub enum QueryMsg {
         staker: String,
         token_id: String,
     },
+    EstimateAllRewards {
+        periods: u64,
+        staker: String,
+        start_after: Option<String>,
+        max_tokens: Option<u32>,
+    },
+    AccruedUpToNow {
+        staker: String,
+        token_id: String,
+    },
+    RewardForPeriod {
+        staker: String,
+        token_id: String,
+        period: u64,
+    },
+    ProjectStakeRewards {
+        periods: u64,
+    },
+    EstimatedApr {},
     NextClaim {
         staker: String,
         token_id: String,
     },
+    NextClaimsBatch {
+        keys: Vec<(String, String)>,
+    },
     NumberOfStakedNfts {},
+    StakedCountByCollection {},
+    StakedCountAtPeriod {
+        period: u64,
+    },
+    Boosters {
+        staker: String,
+    },
     StakedAllNftInfo {
         token_id: String,
     },
     StakedNftsByOwner {
         staker: String,
-    }
+        start_after: Option<String>,
+        limit: Option<u32>,
+        verify: Option<bool>,
+    },
+    StakedTokenIdsByOwner {
+        staker: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    IsTokenBurned {
+        token_id: String,
+    },
+    IsTokenBlocked {
+        token_id: String,
+    },
+    ClaimReceipts {
+        staker: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    AccrualDiff {
+        staker: String,
+        token_id: String,
+    },
+    TokenRewardRecipient {
+        staker: String,
+        token_id: String,
+    },
+    FinalizableTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    OrphanedClaims {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    IsFinalizable {
+        token_id: String,
+    },
+    UnstakeAvailableAt {
+        token_id: String,
+    },
+    ClaimPlan {
+        staker: String,
+        token_id: String,
+    },
+    MaxClaimablePeriods {
+        staker: String,
+        token_id: String,
+    },
+    StakerCollectionSummary {
+        staker: String,
+        nft_contract: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 // msgs
@@ -107,22 +384,39 @@ pub struct SetConfigMsg {
     pub rewards_token_contract: Option<String>,
 }
 
+// payload of the cw721 send nft message.
+// the stake_for address is recorded as the owner of the staked nft instead of the sender.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakeNftMsg {
+    pub stake_for: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct TokenInfoMsg {
     pub token_id: String,
     pub token_info: TokenInfo,
+    // whether the cw721 owner of the token is the nft staking contract, none if it is not verified.
+    pub verified: Option<bool>,
 }
 
 impl Default for TokenInfoMsg {
     fn default() -> Self {
         TokenInfoMsg { 
             token_id: String::from_str("").unwrap(), 
-            token_info: TokenInfo::default() 
+            token_info: TokenInfo::default(),
+            verified: None,
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimReceiptMsg {
+    pub index: u64,
+    pub receipt: ClaimReceipt,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -141,6 +435,50 @@ pub struct ConfigResponse {
     pub period_length_in_cycles: u64,
     pub white_listed_nft_contract: String,
     pub rewards_token_contract: String,
+    pub booster_contract: Option<String>,
+    pub booster_bps: u16,
+    pub config_version: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FullSettingsResponse {
+    pub owner: String,
+    pub cycle_length_in_seconds: u64,
+    pub period_length_in_cycles: u64,
+    pub white_listed_nft_contract: String,
+    pub whitelisted_contracts: Vec<String>,
+    pub rewards_token_contract: String,
+    pub booster_contract: Option<String>,
+    pub booster_bps: u16,
+    pub min_cycle_length: u64,
+    pub rewards_per_cycle: Option<u128>,
+    pub max_compute_period: u64,
+    pub unbonding_duration: u64,
+    pub min_tokens_to_earn: u64,
+    pub stake_forwarders: Option<Vec<String>>,
+    pub claim_cliff_period: u64,
+    pub auto_disable_threshold: Option<u128>,
+    pub require_approved_recipient: bool,
+    pub allow_prestake: bool,
+    pub allow_public_funding: bool,
+    pub require_claim_before_unstake: bool,
+    pub auto_finalize_on_claim: bool,
+    pub prorate_final_period: bool,
+    pub rewards_follow_nft: bool,
+    pub fee_on_transfer_token: bool,
+    pub max_reward_per_claim_per_token: Option<u128>,
+    pub min_pool_balance_to_stake: Option<u128>,
+    pub emission_end_period: Option<u64>,
+    pub emission_mode: EmissionMode,
+    pub reward_per_period: Option<u128>,
+    pub min_unstake_cycles: u64,
+    pub withdraw_timelock_seconds: Option<u64>,
+    pub pool_constrained_emission: bool,
+    pub validate_contracts: bool,
+    pub start: bool,
+    pub start_time: u64,
+    pub disable: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -230,11 +568,154 @@ impl RewardsScheduleResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MaxComputePeriodResponse {
     pub max_compute_period: u64,
+    pub res_msg: String,
+}
+
+impl MaxComputePeriodResponse {
+    pub fn new(
+        max_compute_period: u64
+    ) -> Self {
+        MaxComputePeriodResponse { 
+            max_compute_period, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn defaulted(
+        max_compute_period: u64
+    ) -> Self {
+        MaxComputePeriodResponse { 
+            max_compute_period, 
+            res_msg: ContractError::DefaultedSetting { setting: "max_compute_period".to_string(), default: max_compute_period.to_string() }.to_string()
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct UnbondingDurationResponse {
     pub unbonding_duration: u64,
+    pub res_msg: String,
+}
+
+impl UnbondingDurationResponse {
+    pub fn new(
+        unbonding_duration: u64
+    ) -> Self {
+        UnbondingDurationResponse { 
+            unbonding_duration, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn defaulted(
+        unbonding_duration: u64
+    ) -> Self {
+        UnbondingDurationResponse { 
+            unbonding_duration, 
+            res_msg: ContractError::DefaultedSetting { setting: "unbonding_duration".to_string(), default: unbonding_duration.to_string() }.to_string()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinTokensToEarnResponse {
+    pub min_tokens_to_earn: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeForwardersResponse {
+    pub stake_forwarders: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimCliffPeriodResponse {
+    pub claim_cliff_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoDisableThresholdResponse {
+    pub auto_disable_threshold: Option<u128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MaxRewardPerClaimPerTokenResponse {
+    pub max_reward_per_claim_per_token: Option<u128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalPaidOutResponse {
+    pub total_paid_out: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MetricsResponse {
+    pub total_claims: u64,
+    pub total_unstakes: u64,
+    pub total_paid_out: u128,
+    pub number_of_staked_nfts: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsRewardMinterResponse {
+    pub is_reward_minter: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenMultiplierResponse {
+    pub token_id: String,
+    pub bps: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarlyStakerBoostResponse {
+    pub early_staker_boost: Option<EarlyStakerBoost>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardsTokenCutoverResponse {
+    pub rewards_token_cutover: Option<RewardsTokenCutover>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionEndPeriodResponse {
+    pub emission_end_period: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingWithdrawalResponse {
+    pub withdraw_timelock_seconds: Option<u64>,
+    pub pending_withdrawal: Option<PendingWithdrawal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingWithdrawalsResponse {
+    pub pending_withdrawals: Vec<PendingWithdrawal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionLedgerResponse {
+    pub pool_constrained_emission: bool,
+    pub processed_period: u64,
+    pub budget: u128,
+    pub unfunded_periods: Vec<u64>,
+    // the period to be used as start_after of the next page, none if all unfunded periods are returned.
+    pub next_start_after: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinUnstakeCyclesResponse {
+    pub min_unstake_cycles: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinCycleLengthResponse {
+    pub min_cycle_length: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionModeResponse {
+    pub emission_mode: EmissionMode,
+    pub reward_per_period: Option<u128>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -347,7 +828,7 @@ impl NextClaimResponse {
         next_claim: NextClaim
     ) -> Self {
         NextClaimResponse { 
-            next_claim: next_claim, 
+            next_claim, 
             res_msg: SUCCESS.to_string() 
         }
     }
@@ -389,6 +870,35 @@ impl StakerHistoryResponse {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakerHistoryExpandedResponse {
+    pub staker_tokenid_key: String,
+    pub staker_history_ranges: Vec<SnapshotRange>,
+    pub res_msg: String,
+}
+
+impl StakerHistoryExpandedResponse {
+    pub fn new(
+        staker_tokenid_key: String,
+        staker_history_ranges: Vec<SnapshotRange>,
+    ) -> Self {
+        StakerHistoryExpandedResponse { 
+            staker_tokenid_key, 
+            staker_history_ranges, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn have_not_history(staker_tokenid_key: String) -> Self {
+        StakerHistoryExpandedResponse { 
+            staker_tokenid_key, 
+            staker_history_ranges: vec![], 
+            res_msg: ContractError::HaveNotHistory {}.to_string() 
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct TokenInfosResponse {
@@ -419,7 +929,8 @@ impl TokenInfosResponse {
                 token_info.clone().is_staked, 
                 token_info.clone().deposit_cycle, 
                 token_info.clone().withdraw_cycle, 
-                token_info.clone().req_unbond_time
+                token_info.clone().req_unbond_time,
+                token_info.clone().nft_contract,
             );
             status_unbonded = Some(true);
         }
@@ -469,6 +980,9 @@ impl TokenInfosResponse {
 pub struct EstimateRewardsResponse {
     pub req_staker_tokenid_key: String,
     pub claim: Claim,
+    pub claimable_via: String,
+    // the estimate is computed although the contract is disabled, it is claimable once the contract is enabled.
+    pub disabled: bool,
     pub res_msg: String,
 }
 
@@ -476,21 +990,27 @@ impl EstimateRewardsResponse {
     pub fn new(
         req_staker_tokenid_key: String,
         claim: Claim,
+        claimable_via: String,
+        disabled: bool,
     ) -> Self {
         EstimateRewardsResponse { 
             req_staker_tokenid_key, 
             claim, 
+            claimable_via,
+            disabled,
             res_msg: SUCCESS.to_string()
         }
     }
 
-    pub fn invalid_claim(
+    pub fn claim_not_initialized(
         req_staker_tokenid_key: String
     ) -> Self {
         EstimateRewardsResponse { 
             req_staker_tokenid_key, 
             claim: Claim::default(), 
-            res_msg: ContractError::InvalidClaim {}.to_string() 
+            claimable_via: "".to_string(),
+            disabled: false,
+            res_msg: ContractError::ClaimNotInitialized {}.to_string() 
         }
     }
 
@@ -500,17 +1020,21 @@ impl EstimateRewardsResponse {
         EstimateRewardsResponse { 
             req_staker_tokenid_key, 
             claim: Claim::default(), 
+            claimable_via: "".to_string(),
+            disabled: false,
             res_msg: ContractError::NotStarted {}.to_string()
         }
     }
 
-    pub fn disabled(
+    pub fn no_rewards_schedule(
         req_staker_tokenid_key: String
     ) -> Self {
         EstimateRewardsResponse { 
             req_staker_tokenid_key, 
             claim: Claim::default(), 
-            res_msg: ContractError::Disabled {}.to_string() 
+            claimable_via: "".to_string(),
+            disabled: false,
+            res_msg: ContractError::NoRewardsSchedule {}.to_string()
         }
     }
 
@@ -521,6 +1045,8 @@ impl EstimateRewardsResponse {
         EstimateRewardsResponse { 
             req_staker_tokenid_key, 
             claim: Claim::default(), 
+            claimable_via: "".to_string(),
+            disabled: false,
             res_msg: e.to_string() 
         }
     }
@@ -528,41 +1054,489 @@ impl EstimateRewardsResponse {
 
 }
 
+// the in progress amount is an estimate for display and is not claimable until the current period completes.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct NumberOfStakedNftsResponse {
-    pub number_of_staked_nfts: u128,
+pub struct AccruedUpToNowResponse {
+    pub req_staker_tokenid_key: String,
+    pub claimable_amount: u128,
+    pub in_progress_amount: u128,
+    pub accrued_amount: u128,
     pub res_msg: String,
 }
 
-impl NumberOfStakedNftsResponse {
+impl AccruedUpToNowResponse {
     pub fn new(
-        number_of_staked_nfts: u128
+        req_staker_tokenid_key: String,
+        claimable_amount: u128,
+        in_progress_amount: u128,
     ) -> Self {
-        NumberOfStakedNftsResponse { 
-            number_of_staked_nfts, 
+        AccruedUpToNowResponse { 
+            req_staker_tokenid_key, 
+            claimable_amount, 
+            in_progress_amount, 
+            accrued_amount: claimable_amount + in_progress_amount, 
             res_msg: SUCCESS.to_string()
         }
     }
 
-    pub fn not_started() -> Self {
-        NumberOfStakedNftsResponse { number_of_staked_nfts: 0, res_msg: ContractError::NotStarted {}.to_string() }
+    pub fn not_started(
+        req_staker_tokenid_key: String
+    ) -> Self {
+        AccruedUpToNowResponse { 
+            req_staker_tokenid_key, 
+            claimable_amount: 0, 
+            in_progress_amount: 0, 
+            accrued_amount: 0, 
+            res_msg: ContractError::NotStarted {}.to_string()
+        }
     }
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct StakedAllNftInfoResponse<T> {
-    pub all_nft_info: AllNftInfoResponse<T>,
-    pub res_msg: String,
-}
 
-impl StakedAllNftInfoResponse<Extension> {
-    pub fn new(
-        all_nft_info: AllNftInfoResponse<Extension>
+    pub fn empty_next_claim(
+        req_staker_tokenid_key: String
+    ) -> Self {
+        AccruedUpToNowResponse { 
+            req_staker_tokenid_key, 
+            claimable_amount: 0, 
+            in_progress_amount: 0, 
+            accrued_amount: 0, 
+            res_msg: ContractError::EmptyNextClaim {}.to_string()
+        }
+    }
+
+    pub fn with_err(
+        req_staker_tokenid_key: String,
+        e: ContractError,
+    ) -> Self {
+        AccruedUpToNowResponse { 
+            req_staker_tokenid_key, 
+            claimable_amount: 0, 
+            in_progress_amount: 0, 
+            accrued_amount: 0, 
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardForPeriodResponse {
+    pub req_staker_tokenid_key: String,
+    pub period: u64,
+    pub amount: u128,
+    pub res_msg: String,
+}
+
+impl RewardForPeriodResponse {
+    pub fn new(
+        req_staker_tokenid_key: String,
+        period: u64,
+        amount: u128,
+    ) -> Self {
+        RewardForPeriodResponse { 
+            req_staker_tokenid_key, 
+            period, 
+            amount, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn not_started(
+        req_staker_tokenid_key: String,
+        period: u64,
+    ) -> Self {
+        RewardForPeriodResponse { 
+            req_staker_tokenid_key, 
+            period, 
+            amount: 0, 
+            res_msg: ContractError::NotStarted {}.to_string()
+        }
+    }
+
+    pub fn invalid_token_id(
+        req_staker_tokenid_key: String,
+        period: u64,
+    ) -> Self {
+        RewardForPeriodResponse { 
+            req_staker_tokenid_key, 
+            period, 
+            amount: 0, 
+            res_msg: ContractError::InvalidTokenId {}.to_string()
+        }
+    }
+
+    pub fn with_err(
+        req_staker_tokenid_key: String,
+        period: u64,
+        e: ContractError,
+    ) -> Self {
+        RewardForPeriodResponse { 
+            req_staker_tokenid_key, 
+            period, 
+            amount: 0, 
+            res_msg: e.to_string()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProjectStakeRewardsResponse {
+    pub periods: u64,
+    pub rewards_per_cycle: u128,
+    pub period_length_in_cycles: u64,
+    pub projected_rewards: u128,
+    pub res_msg: String,
+}
+
+impl ProjectStakeRewardsResponse {
+    pub fn new(
+        periods: u64,
+        rewards_per_cycle: u128,
+        period_length_in_cycles: u64,
+    ) -> Self {
+        ProjectStakeRewardsResponse { 
+            periods, 
+            rewards_per_cycle, 
+            period_length_in_cycles, 
+            projected_rewards: rewards_per_cycle * period_length_in_cycles as u128 * periods as u128, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn invalid_rewards_schedule(
+        periods: u64,
+        period_length_in_cycles: u64,
+    ) -> Self {
+        ProjectStakeRewardsResponse { 
+            periods, 
+            rewards_per_cycle: 0, 
+            period_length_in_cycles, 
+            projected_rewards: 0, 
+            res_msg: ContractError::InvalidRewardsSchedule {}.to_string()
+        }
+    }
+}
+
+// rewards are in the rewards token, clients compute the apr against their own valuation of the nft.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct EstimatedAprResponse {
+    pub emission_mode: EmissionMode,
+    pub cycles_per_year: u64,
+    pub number_of_staked_nfts: u128,
+    pub rewards_per_nft_per_year: u128,
+    pub res_msg: String,
+}
+
+impl EstimatedAprResponse {
+    pub fn new(
+        emission_mode: EmissionMode,
+        cycles_per_year: u64,
+        number_of_staked_nfts: u128,
+        rewards_per_nft_per_year: u128,
+    ) -> Self {
+        EstimatedAprResponse { 
+            emission_mode, 
+            cycles_per_year, 
+            number_of_staked_nfts, 
+            rewards_per_nft_per_year, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn invalid_rewards_schedule(
+        emission_mode: EmissionMode,
+        cycles_per_year: u64,
+        number_of_staked_nfts: u128,
+    ) -> Self {
+        EstimatedAprResponse { 
+            emission_mode, 
+            cycles_per_year, 
+            number_of_staked_nfts, 
+            rewards_per_nft_per_year: 0, 
+            res_msg: ContractError::InvalidRewardsSchedule {}.to_string()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BoostersResponse {
+    pub staker: String,
+    pub booster_count: u64,
+    pub booster_bps: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MaxClaimablePeriodsResponse {
+    pub req_staker_tokenid_key: String,
+    pub max_claimable_periods: u64,
+    pub res_msg: String,
+}
+
+impl MaxClaimablePeriodsResponse {
+    pub fn new(
+        req_staker_tokenid_key: String,
+        max_claimable_periods: u64,
+    ) -> Self {
+        MaxClaimablePeriodsResponse { 
+            req_staker_tokenid_key, 
+            max_claimable_periods, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn not_started(
+        req_staker_tokenid_key: String
+    ) -> Self {
+        MaxClaimablePeriodsResponse { 
+            req_staker_tokenid_key, 
+            max_claimable_periods: 0, 
+            res_msg: ContractError::NotStarted {}.to_string()
+        }
+    }
+
+    pub fn with_err(
+        req_staker_tokenid_key: String,
+        e: ContractError,
+    ) -> Self {
+        MaxClaimablePeriodsResponse { 
+            req_staker_tokenid_key, 
+            max_claimable_periods: 0, 
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimPlanChunk {
+    pub periods: u64,
+    pub estimated_amount: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimPlanResponse {
+    pub req_staker_tokenid_key: String,
+    pub total_claimable_periods: u64,
+    // each chunk is a claim of up to max compute period.
+    pub chunks: Vec<ClaimPlanChunk>,
+    // true if the claimable periods remain after the reported chunks.
+    pub has_more: bool,
+    pub res_msg: String,
+}
+
+impl ClaimPlanResponse {
+    pub fn new(
+        req_staker_tokenid_key: String,
+        total_claimable_periods: u64,
+        chunks: Vec<ClaimPlanChunk>,
+        has_more: bool,
+    ) -> Self {
+        ClaimPlanResponse { 
+            req_staker_tokenid_key, 
+            total_claimable_periods, 
+            chunks,
+            has_more,
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn not_started(
+        req_staker_tokenid_key: String
+    ) -> Self {
+        ClaimPlanResponse { 
+            req_staker_tokenid_key, 
+            total_claimable_periods: 0, 
+            chunks: vec![],
+            has_more: false,
+            res_msg: ContractError::NotStarted {}.to_string()
+        }
+    }
+
+    pub fn with_err(
+        req_staker_tokenid_key: String,
+        e: ContractError,
+    ) -> Self {
+        ClaimPlanResponse { 
+            req_staker_tokenid_key, 
+            total_claimable_periods: 0, 
+            chunks: vec![],
+            has_more: false,
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AccrualDiffResponse {
+    pub req_staker_tokenid_key: String,
+    pub next_claim_period: u64,
+    pub current_period: u64,
+    pub unclaimed_periods: u64,
+    pub estimate: Claim,
+    pub bond_status: String,
+    pub capped_by_unbonding: bool,
+    pub res_msg: String,
+}
+
+impl AccrualDiffResponse {
+    pub fn new(
+        req_staker_tokenid_key: String,
+        next_claim_period: u64,
+        current_period: u64,
+        unclaimed_periods: u64,
+        estimate: Claim,
+        bond_status: String,
+        capped_by_unbonding: bool,
+    ) -> Self {
+        AccrualDiffResponse { 
+            req_staker_tokenid_key, 
+            next_claim_period, 
+            current_period, 
+            unclaimed_periods, 
+            estimate, 
+            bond_status, 
+            capped_by_unbonding, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn empty_next_claim(
+        req_staker_tokenid_key: String
+    ) -> Self {
+        AccrualDiffResponse::with_err(req_staker_tokenid_key, ContractError::EmptyNextClaim {})
+    }
+
+    pub fn not_started(
+        req_staker_tokenid_key: String
+    ) -> Self {
+        AccrualDiffResponse::with_err(req_staker_tokenid_key, ContractError::NotStarted {})
+    }
+
+    pub fn with_err(
+        req_staker_tokenid_key: String,
+        e: ContractError,
+    ) -> Self {
+        AccrualDiffResponse { 
+            req_staker_tokenid_key, 
+            next_claim_period: 0, 
+            current_period: 0, 
+            unclaimed_periods: 0, 
+            estimate: Claim::default(), 
+            bond_status: "".to_string(), 
+            capped_by_unbonding: false, 
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakedCountByCollectionResponse {
+    pub staked_counts: Vec<(String, u64)>,
+    pub res_msg: String,
+}
+
+impl StakedCountByCollectionResponse {
+    pub fn new(
+        staked_counts: Vec<(String, u64)>,
+    ) -> Self {
+        StakedCountByCollectionResponse { 
+            staked_counts, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn with_err(e: StdError) -> Self {
+        StakedCountByCollectionResponse { 
+            staked_counts: vec![], 
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakedCountAtPeriodResponse {
+    pub period: u64,
+    pub staked_count: u128,
+    pub res_msg: String,
+}
+
+impl StakedCountAtPeriodResponse {
+    pub fn new(
+        period: u64,
+        staked_count: u128,
+    ) -> Self {
+        StakedCountAtPeriodResponse { 
+            period, 
+            staked_count, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn with_err(
+        period: u64,
+        e: StdError,
+    ) -> Self {
+        StakedCountAtPeriodResponse { 
+            period, 
+            staked_count: 0, 
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct NumberOfStakedNftsResponse {
+    pub number_of_staked_nfts: u128,
+    pub res_msg: String,
+}
+
+impl NumberOfStakedNftsResponse {
+    pub fn new(
+        number_of_staked_nfts: u128
+    ) -> Self {
+        NumberOfStakedNftsResponse { 
+            number_of_staked_nfts, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn not_started() -> Self {
+        NumberOfStakedNftsResponse { number_of_staked_nfts: 0, res_msg: ContractError::NotStarted {}.to_string() }
+    }
+
+    pub fn defaulted() -> Self {
+        NumberOfStakedNftsResponse { 
+            number_of_staked_nfts: 0, 
+            res_msg: ContractError::DefaultedSetting { setting: "number_of_staked_nfts".to_string(), default: "0".to_string() }.to_string()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakedAllNftInfoResponse<T> {
+    pub all_nft_info: AllNftInfoResponse<T>,
+    // true if the query to the nft contract is failed, the all_nft_info is empty and not valid.
+    pub is_error: bool,
+    pub res_msg: String,
+}
+
+impl StakedAllNftInfoResponse<Extension> {
+    pub fn new(
+        all_nft_info: AllNftInfoResponse<Extension>
     ) -> Self {
         StakedAllNftInfoResponse { 
             all_nft_info, 
+            is_error: false,
             res_msg: SUCCESS.to_string()
         }
     }
@@ -573,6 +1547,7 @@ impl StakedAllNftInfoResponse<Extension> {
     ) -> Self {
         StakedAllNftInfoResponse { 
             all_nft_info, 
+            is_error: true,
             res_msg: e.to_string() 
         }
     }
@@ -582,15 +1557,19 @@ impl StakedAllNftInfoResponse<Extension> {
 #[serde(rename_all = "snake_case")]
 pub struct StakedNftsByOwnerResponse {
     pub staked_nfts: Vec<TokenInfoMsg>,
+    // the token id to be used as start_after of the next page, none if all staked nfts are returned.
+    pub next_start_after: Option<String>,
     pub res_msg: String,
 }
 
 impl StakedNftsByOwnerResponse {
     pub fn new(
         staked_nfts: Vec<TokenInfoMsg>,
+        next_start_after: Option<String>,
     ) -> Self {
         StakedNftsByOwnerResponse { 
             staked_nfts, 
+            next_start_after,
             res_msg: SUCCESS.to_string() 
         }
     }
@@ -601,11 +1580,413 @@ impl StakedNftsByOwnerResponse {
     ) -> Self {
         StakedNftsByOwnerResponse { 
             staked_nfts, 
+            next_start_after: None,
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct EstimateAllRewardsResponse {
+    pub staker: String,
+    pub estimates: Vec<EstimateRewardsResponse>,
+    // sum of the estimated rewards of the tokens in this page.
+    pub total_amount: u128,
+    // the token id to be used as start_after of the next page, none if all tokens are estimated.
+    pub next_start_after: Option<String>,
+    pub has_more: bool,
+    pub res_msg: String,
+}
+
+impl EstimateAllRewardsResponse {
+    pub fn new(
+        staker: String,
+        estimates: Vec<EstimateRewardsResponse>,
+        total_amount: u128,
+        next_start_after: Option<String>,
+    ) -> Self {
+        EstimateAllRewardsResponse { 
+            staker, 
+            estimates, 
+            total_amount, 
+            has_more: next_start_after.is_some(),
+            next_start_after, 
+            res_msg: SUCCESS.to_string()
+        }
+    }
+
+    pub fn with_err(staker: String, e: StdError) -> Self {
+        EstimateAllRewardsResponse { 
+            staker, 
+            estimates: vec![], 
+            total_amount: 0, 
+            next_start_after: None, 
+            has_more: false, 
+            res_msg: e.to_string()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakedTokenIdsByOwnerResponse {
+    pub token_ids: Vec<String>,
+    // the token id to be used as start_after of the next page, none if all token ids are returned.
+    pub next_start_after: Option<String>,
+    pub res_msg: String,
+}
+
+impl StakedTokenIdsByOwnerResponse {
+    pub fn new(
+        token_ids: Vec<String>,
+        next_start_after: Option<String>,
+    ) -> Self {
+        StakedTokenIdsByOwnerResponse { 
+            token_ids, 
+            next_start_after,
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn with_err(e: StdError) -> Self {
+        StakedTokenIdsByOwnerResponse { 
+            token_ids: vec![], 
+            next_start_after: None,
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakerCollectionSummaryResponse {
+    pub staker: String,
+    pub nft_contract: String,
+    pub staked_count: u64,
+    pub total_estimated_rewards: u128,
+    // true if the total estimated rewards do not cover every period because of the max compute period.
+    pub truncated: bool,
+    // the token id to be used as start_after of the next page, none if all tokens of the staker are summarized.
+    pub next_start_after: Option<String>,
+    pub res_msg: String,
+}
+
+impl StakerCollectionSummaryResponse {
+    pub fn new(
+        staker: String,
+        nft_contract: String,
+        staked_count: u64,
+        total_estimated_rewards: u128,
+        truncated: bool,
+        next_start_after: Option<String>,
+    ) -> Self {
+        StakerCollectionSummaryResponse { 
+            staker, 
+            nft_contract, 
+            staked_count, 
+            total_estimated_rewards, 
+            truncated, 
+            next_start_after,
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn not_started(
+        staker: String,
+        nft_contract: String,
+    ) -> Self {
+        StakerCollectionSummaryResponse { 
+            staker, 
+            nft_contract, 
+            staked_count: 0, 
+            total_estimated_rewards: 0, 
+            truncated: false, 
+            next_start_after: None,
+            res_msg: ContractError::NotStarted {}.to_string() 
+        }
+    }
+
+    pub fn with_err(
+        staker: String,
+        nft_contract: String,
+        res_msg: String,
+    ) -> Self {
+        StakerCollectionSummaryResponse { 
+            staker, 
+            nft_contract, 
+            staked_count: 0, 
+            total_estimated_rewards: 0, 
+            truncated: false, 
+            next_start_after: None,
+            res_msg,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FinalizableTokensResponse {
+    pub tokens: Vec<TokenInfoMsg>,
+    // the token id to be used as start_after of the next page, none if all tokens are returned.
+    pub next_start_after: Option<String>,
+    pub res_msg: String,
+}
+
+impl FinalizableTokensResponse {
+    pub fn new(
+        tokens: Vec<TokenInfoMsg>,
+        next_start_after: Option<String>,
+    ) -> Self {
+        FinalizableTokensResponse { 
+            tokens, 
+            next_start_after,
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn with_err(e: StdError) -> Self {
+        FinalizableTokensResponse { 
+            tokens: vec![], 
+            next_start_after: None,
             res_msg: e.to_string() 
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimReceiptsResponse {
+    pub receipts: Vec<ClaimReceiptMsg>,
+    // the receipt index to be used as start_after of the next page, none if all receipts are returned.
+    pub next_start_after: Option<u64>,
+    pub res_msg: String,
+}
+
+impl ClaimReceiptsResponse {
+    pub fn new(
+        receipts: Vec<ClaimReceiptMsg>,
+        next_start_after: Option<u64>,
+    ) -> Self {
+        ClaimReceiptsResponse { 
+            receipts, 
+            next_start_after,
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn with_err(e: StdError) -> Self {
+        ClaimReceiptsResponse { 
+            receipts: vec![], 
+            next_start_after: None,
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OrphanedClaimsResponse {
+    pub staker_tokenid_keys: Vec<String>,
+    // the staker_tokenid_key to be used as start_after of the next page, none if all orphaned claims are returned.
+    pub next_start_after: Option<String>,
+    pub res_msg: String,
+}
+
+impl OrphanedClaimsResponse {
+    pub fn new(
+        staker_tokenid_keys: Vec<String>,
+        next_start_after: Option<String>,
+    ) -> Self {
+        OrphanedClaimsResponse { 
+            staker_tokenid_keys, 
+            next_start_after,
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn with_err(e: StdError) -> Self {
+        OrphanedClaimsResponse { 
+            staker_tokenid_keys: vec![], 
+            next_start_after: None,
+            res_msg: e.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct IsFinalizableResponse {
+    pub token_id: String,
+    pub finalizable: bool,
+    // the earliest block time when unstake returns the nft, none if the token is not unbonding.
+    pub finalize_at: Option<u64>,
+    pub res_msg: String,
+}
+
+impl IsFinalizableResponse {
+    pub fn new(
+        token_id: String,
+        finalizable: bool,
+        finalize_at: Option<u64>,
+    ) -> Self {
+        IsFinalizableResponse { 
+            token_id, 
+            finalizable, 
+            finalize_at, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn invalid_token_id(
+        token_id: String,
+    ) -> Self {
+        IsFinalizableResponse { 
+            token_id, 
+            finalizable: false, 
+            finalize_at: None, 
+            res_msg: ContractError::InvalidTokenId {}.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UnstakeAvailableAtResponse {
+    pub token_id: String,
+    // the block time when the frozen cycles have passed, the unbonding duration is applied separately.
+    pub available_at: u64,
+    pub available_now: bool,
+    pub res_msg: String,
+}
+
+impl UnstakeAvailableAtResponse {
+    pub fn new(
+        token_id: String,
+        available_at: u64,
+        available_now: bool,
+    ) -> Self {
+        UnstakeAvailableAtResponse { 
+            token_id, 
+            available_at, 
+            available_now, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn not_started(
+        token_id: String,
+    ) -> Self {
+        UnstakeAvailableAtResponse { 
+            token_id, 
+            available_at: 0, 
+            available_now: false, 
+            res_msg: ContractError::NotStarted {}.to_string() 
+        }
+    }
+
+    pub fn invalid_token_id(
+        token_id: String,
+    ) -> Self {
+        UnstakeAvailableAtResponse { 
+            token_id, 
+            available_at: 0, 
+            available_now: false, 
+            res_msg: ContractError::InvalidTokenId {}.to_string() 
+        }
+    }
+
+    pub fn unstaked_token_id(
+        token_id: String,
+    ) -> Self {
+        UnstakeAvailableAtResponse { 
+            token_id, 
+            available_at: 0, 
+            available_now: false, 
+            res_msg: ContractError::UnstakedTokenId {}.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenRewardRecipientResponse {
+    pub req_staker_tokenid_key: String,
+    pub recipient: Option<String>,
+    pub expires: Option<Expiration>,
+    pub expired: bool,
+    pub res_msg: String,
+}
+
+impl TokenRewardRecipientResponse {
+    pub fn new(
+        req_staker_tokenid_key: String,
+        recipient: Option<String>,
+        expires: Option<Expiration>,
+        expired: bool,
+    ) -> Self {
+        TokenRewardRecipientResponse { 
+            req_staker_tokenid_key, 
+            recipient, 
+            expires,
+            expired,
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenBlockedResponse {
+    pub token_id: String,
+    pub blocked: bool,
+    pub res_msg: String,
+}
+
+impl TokenBlockedResponse {
+    pub fn new(
+        token_id: String,
+        blocked: bool,
+    ) -> Self {
+        TokenBlockedResponse { 
+            token_id, 
+            blocked, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenBurnedResponse {
+    pub token_id: String,
+    pub burned: bool,
+    pub res_msg: String,
+}
+
+impl TokenBurnedResponse {
+    pub fn new(
+        token_id: String,
+        burned: bool,
+    ) -> Self {
+        TokenBurnedResponse { 
+            token_id, 
+            burned, 
+            res_msg: SUCCESS.to_string() 
+        }
+    }
+
+    pub fn invalid_token_id(
+        token_id: String,
+    ) -> Self {
+        TokenBurnedResponse { 
+            token_id, 
+            burned: false, 
+            res_msg: ContractError::InvalidTokenId {}.to_string() 
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct MigrateMsg {}
@@ -1,12 +1,30 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_binary, Binary, Env, StdResult, Deps, QueryRequest, WasmQuery, StdError, Order};
+use cw2::get_contract_version;
 use cw20::Expiration;
+use cw_storage_plus::Bound;
 use cw721::{Cw721QueryMsg, AllNftInfoResponse, OwnerOfResponse, NftInfoResponse, Approval};
 use cw721_base::Extension;
-use crate::handler::{compute_rewards, staker_tokenid_key, query_rewards_token_balance, get_cycle, get_period};
-use crate::msg::{QueryMsg, ConfigResponse, StartTimeResponse, TotalRewardsPoolResponse, StakerHistoryResponse, TokenInfosResponse, RewardsScheduleResponse, EstimateRewardsResponse, NextClaimResponse, WithdrawRewardsPoolResponse, DisableResponse, NumberOfStakedNftsResponse, StakedAllNftInfoResponse, MaxComputePeriodResponse, StakedNftsByOwnerResponse, TokenInfoMsg, GetGrantsResponse, UnbondingDurationResponse, GetCurrentCycleAndPeriodResponse};
-use crate::state::{CONFIG_STATE, REWARDS_SCHEDULE, START_TIMESTAMP, DISABLE, TOTAL_REWARDS_POOL, STAKER_HISTORIES, TOKEN_INFOS, NEXT_CLAIMS, NUMBER_OF_STAKED_NFTS, MAX_COMPUTE_PERIOD, GRANTS, Grant, UNBONDING_DURATION};
+use crate::handler::{compute_rewards, staker_tokenid_key, query_rewards_token_balance, get_cycle, get_period, get_current_period, staked_count_at_period, get_min_cycle_length, get_end_claim_period, compute_in_progress_rewards, compute_reward_for_period, snapshot_ranges, compute_rewards_from, is_orphaned_claim, query_nft_owner, DEFAULT_TOKEN_MULTIPLIER_BPS, get_unbond_end_claim_period};
+use crate::msg::{QueryMsg, ConfigResponse, StartTimeResponse, TotalRewardsPoolResponse, StakerHistoryResponse, StakerHistoryExpandedResponse, TokenInfosResponse, RewardsScheduleResponse, EstimateRewardsResponse, EstimateAllRewardsResponse, AccruedUpToNowResponse, RewardForPeriodResponse, ProjectStakeRewardsResponse, EstimatedAprResponse, BoostersResponse, MaxClaimablePeriodsResponse, ClaimPlanResponse, ClaimPlanChunk, NextClaimResponse, WithdrawRewardsPoolResponse, PendingWithdrawalResponse, PendingWithdrawalsResponse, EmissionLedgerResponse, RewardsTokenCutoverResponse, EarlyStakerBoostResponse, TokenMultiplierResponse, IsRewardMinterResponse, MetricsResponse, DisableResponse, NumberOfStakedNftsResponse, StakedAllNftInfoResponse, MaxComputePeriodResponse, StakedNftsByOwnerResponse, StakedTokenIdsByOwnerResponse, TokenInfoMsg, GetGrantsResponse, UnbondingDurationResponse, GetCurrentCycleAndPeriodResponse, TokenBurnedResponse, TokenBlockedResponse, MinTokensToEarnResponse, FullSettingsResponse, AccrualDiffResponse, StakeForwardersResponse, ClaimCliffPeriodResponse, AutoDisableThresholdResponse, MaxRewardPerClaimPerTokenResponse, EmissionEndPeriodResponse, EmissionModeResponse, MinCycleLengthResponse, MinUnstakeCyclesResponse, TotalPaidOutResponse, TokenRewardRecipientResponse, StakedCountByCollectionResponse, StakedCountAtPeriodResponse, FinalizableTokensResponse, ClaimReceiptsResponse, ClaimReceiptMsg, OrphanedClaimsResponse, IsFinalizableResponse, UnstakeAvailableAtResponse, StakerCollectionSummaryResponse, CLAIMABLE_VIA_CLAIM, CLAIMABLE_VIA_UNSTAKE, SUCCESS};
+use crate::state::{MIN_POOL_BALANCE_TO_STAKE, PRORATE_FINAL_PERIOD, IS_REWARD_MINTER, TOKEN_MULTIPLIERS, EARLY_STAKER_BOOST, AUTO_FINALIZE_ON_CLAIM, TOTAL_CLAIMS, TOTAL_UNSTAKES, REWARDS_TOKEN_CUTOVER, REWARDS_FOLLOW_NFT, POOL_CONSTRAINED_EMISSION, EMISSION_LEDGER, UNFUNDED_PERIODS, CONFIG_STATE, REWARDS_SCHEDULE, START_TIMESTAMP, DISABLE, TOTAL_REWARDS_POOL, STAKER_HISTORIES, TOKEN_INFOS, NEXT_CLAIMS, NUMBER_OF_STAKED_NFTS, MAX_COMPUTE_PERIOD, GRANTS, Grant, UNBONDING_DURATION, MIN_TOKENS_TO_EARN, UNBONDING, UNBONDED, STAKE_FORWARDERS, CLAIM_CLIFF_PERIOD, AUTO_DISABLE_THRESHOLD, TOKEN_RECIPIENTS, REQUIRE_APPROVED_RECIPIENT, STAKED_COUNT_BY_COLLECTION, ALLOW_PRESTAKE, FEE_ON_TRANSFER_TOKEN, MAX_REWARD_PER_CLAIM_PER_TOKEN, EMISSION_END_PERIOD, TOTAL_PAID_OUT, VALIDATE_CONTRACTS, EMISSION_MODE, REWARD_PER_PERIOD, MIN_UNSTAKE_CYCLES, DEFAULT_MIN_UNSTAKE_CYCLES, DEFAULT_MAX_COMPUTE_PERIOD, DEFAULT_UNBONDING_DURATION, ALLOW_PUBLIC_FUNDING, BOOSTERS, EmissionMode, REQUIRE_CLAIM_BEFORE_UNSTAKE, CONFIG_VERSION, WITHDRAW_TIMELOCK_SECONDS, PENDING_WITHDRAWAL, BLOCKED_TOKENS, CLAIM_RECEIPTS, STAKER_TOKENS};
+
+// settings of pagination.
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+// max number of staked nfts which are verified by the cw721 owner in a query.
+const MAX_VERIFY_LIMIT: u32 = 10;
+
+// each token of the estimate all rewards computes up to the requested periods, so the number of tokens is kept small.
+const DEFAULT_ESTIMATE_ALL_TOKENS: u32 = 5;
+const MAX_ESTIMATE_ALL_TOKENS: u32 = 10;
+
+// each chunk of the claim plan computes up to max compute period, so the number of chunks is capped.
+const MAX_CLAIM_PLAN_CHUNKS: usize = 5;
+
+// a year is 365 days for the estimated apr.
+const SECONDS_PER_YEAR: u64 = 31_536_000;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(
@@ -16,22 +34,67 @@ pub fn query(
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => to_binary(&get_config(deps)?),
+        QueryMsg::FullSettings {} => to_binary(&full_settings(deps)?),
+        QueryMsg::Version {} => to_binary(&get_contract_version(deps.storage)?),
         QueryMsg::GetCurrentCycleAndPeriod {} => to_binary(&get_current_cycle_and_period(deps, env)?),
         QueryMsg::GetAllGrants {} => to_binary(&get_all_grants(deps)?),
         QueryMsg::GetRewardsSchedule {} => to_binary(&get_rewards_schedule(deps)?),
+        QueryMsg::RawRewardsSchedule {} => to_binary(&REWARDS_SCHEDULE.may_load(deps.storage)?),
         QueryMsg::GetMaxComputePeriod {} => to_binary(&get_max_compute_period(deps)?),
         QueryMsg::GetUnbondingDuration {} => to_binary(&get_unbonding_duration(deps)?),
+        QueryMsg::GetMinTokensToEarn {} => to_binary(&get_min_tokens_to_earn(deps)?),
+        QueryMsg::GetStakeForwarders {} => to_binary(&get_stake_forwarders(deps)?),
+        QueryMsg::GetClaimCliffPeriod {} => to_binary(&get_claim_cliff_period(deps)?),
+        QueryMsg::GetAutoDisableThreshold {} => to_binary(&get_auto_disable_threshold(deps)?),
+        QueryMsg::GetMaxRewardPerClaimPerToken {} => to_binary(&get_max_reward_per_claim_per_token(deps)?),
+        QueryMsg::GetEmissionEndPeriod {} => to_binary(&get_emission_end_period(deps)?),
+        QueryMsg::GetEmissionMode {} => to_binary(&get_emission_mode(deps)?),
+        QueryMsg::IsRewardMinter {} => to_binary(&IsRewardMinterResponse { is_reward_minter: IS_REWARD_MINTER.may_load(deps.storage)?.unwrap_or(false) }),
+        QueryMsg::WhitelistedContracts {} => to_binary(&whitelisted_contracts(deps)?),
+        QueryMsg::TokenMultiplier { token_id } => to_binary(&token_multiplier(deps, token_id)?),
+        QueryMsg::GetEarlyStakerBoost {} => to_binary(&EarlyStakerBoostResponse { early_staker_boost: EARLY_STAKER_BOOST.may_load(deps.storage)? }),
+        QueryMsg::GetRewardsTokenCutover {} => to_binary(&RewardsTokenCutoverResponse { rewards_token_cutover: REWARDS_TOKEN_CUTOVER.may_load(deps.storage)? }),
+        QueryMsg::GetMinCycleLength {} => to_binary(&min_cycle_length(deps)?),
+        QueryMsg::GetMinUnstakeCycles {} => to_binary(&get_min_unstake_cycles(deps)?),
         QueryMsg::StartTime {} => to_binary(&start_time(deps, env)?),
         QueryMsg::Disable {} => to_binary(&disable(deps)?),
         QueryMsg::TotalRewardsPool {} => to_binary(&total_rewards_pool(deps)?),
+        QueryMsg::TotalPaidOut {} => to_binary(&total_paid_out(deps)?),
+        QueryMsg::Metrics {} => to_binary(&metrics(deps)?),
         QueryMsg::WithdrawRewardsPoolAmount {} => to_binary(&withdraw_rewards_pool_amount(deps, env)?),
+        QueryMsg::PendingWithdrawal {} => to_binary(&pending_withdrawal(deps)?),
+        QueryMsg::PendingWithdrawals {} => to_binary(&pending_withdrawals(deps)?),
+        QueryMsg::EmissionLedger { start_after, limit } => to_binary(&emission_ledger(deps, start_after, limit)?),
         QueryMsg::StakerHistory { staker, token_id } => to_binary(&staker_history(deps, staker, token_id)?),
+        QueryMsg::StakerHistoryExpanded { staker, token_id } => to_binary(&staker_history_expanded(deps, staker, token_id)?),
         QueryMsg::TokenInfo { token_id } => to_binary(&token_infos(deps, env, token_id)?),
         QueryMsg::EstimateRewards { periods, staker, token_id } => to_binary(&estimate_rewards(deps, env, periods, token_id, staker)?),
+        QueryMsg::EstimateAllRewards { periods, staker, start_after, max_tokens } => to_binary(&estimate_all_rewards(deps, env, periods, staker, start_after, max_tokens)?),
+        QueryMsg::AccruedUpToNow { staker, token_id } => to_binary(&accrued_up_to_now(deps, env, staker, token_id)?),
+        QueryMsg::RewardForPeriod { staker, token_id, period } => to_binary(&reward_for_period(deps, env, staker, token_id, period)?),
+        QueryMsg::ProjectStakeRewards { periods } => to_binary(&project_stake_rewards(deps, periods)?),
+        QueryMsg::EstimatedApr {} => to_binary(&estimated_apr(deps)?),
+        QueryMsg::AccrualDiff { staker, token_id } => to_binary(&accrual_diff(deps, env, staker, token_id)?),
+        QueryMsg::TokenRewardRecipient { staker, token_id } => to_binary(&token_reward_recipient(deps, env, staker, token_id)?),
+        QueryMsg::FinalizableTokens { start_after, limit } => to_binary(&finalizable_tokens(deps, env, start_after, limit)?),
+        QueryMsg::UnstakeAvailableAt { token_id } => to_binary(&unstake_available_at(deps, env, token_id)?),
+        QueryMsg::IsFinalizable { token_id } => to_binary(&is_finalizable(deps, env, token_id)?),
+        QueryMsg::ClaimPlan { staker, token_id } => to_binary(&claim_plan(deps, env, staker, token_id)?),
+        QueryMsg::MaxClaimablePeriods { staker, token_id } => to_binary(&max_claimable_periods(deps, env, staker, token_id)?),
+        QueryMsg::StakerCollectionSummary { staker, nft_contract, start_after, limit } => to_binary(&staker_collection_summary(deps, env, staker, nft_contract, start_after, limit)?),
         QueryMsg::NextClaim { staker, token_id } => to_binary(&next_claims(deps, staker, token_id)?),
+        QueryMsg::NextClaimsBatch { keys } => to_binary(&next_claims_batch(deps, keys)?),
         QueryMsg::NumberOfStakedNfts {} => to_binary(&number_of_staked_nfts(deps)?),
+        QueryMsg::StakedCountByCollection {} => to_binary(&staked_count_by_collection(deps)?),
+        QueryMsg::Boosters { staker } => to_binary(&boosters(deps, staker)?),
+        QueryMsg::StakedCountAtPeriod { period } => to_binary(&staked_count_at(deps, period)?),
         QueryMsg::StakedAllNftInfo { token_id } => to_binary(&staked_all_nft_info(deps, token_id)?),
-        QueryMsg::StakedNftsByOwner { staker } => to_binary(&staked_nfts_by_owner(deps, staker)?),
+        QueryMsg::StakedNftsByOwner { staker, start_after, limit, verify } => to_binary(&staked_nfts_by_owner(deps, env, staker, start_after, limit, verify)?),
+        QueryMsg::StakedTokenIdsByOwner { staker, start_after, limit } => to_binary(&staked_token_ids_by_owner(deps, staker, start_after, limit)?),
+        QueryMsg::IsTokenBurned { token_id } => to_binary(&is_token_burned(deps, token_id)?),
+        QueryMsg::IsTokenBlocked { token_id } => to_binary(&is_token_blocked(deps, token_id)?),
+        QueryMsg::OrphanedClaims { start_after, limit } => to_binary(&orphaned_claims(deps, start_after, limit)?),
+        QueryMsg::ClaimReceipts { staker, start_after, limit } => to_binary(&claim_receipts(deps, staker, start_after, limit)?),
     }
 }
 
@@ -44,9 +107,64 @@ fn get_config(deps: Deps) -> StdResult<ConfigResponse> {
         period_length_in_cycles: config_state.period_length_in_cycles,
         white_listed_nft_contract: config_state.white_listed_nft_contract.to_string(),
         rewards_token_contract: config_state.rewards_token_contract.to_string(),
+        booster_contract: config_state.booster_contract,
+        booster_bps: config_state.booster_bps,
+        config_version: CONFIG_VERSION.may_load(deps.storage)?.unwrap_or(0),
+    })
+}
+
+// query configuration and all runtime settings at once.
+fn full_settings(deps: Deps) -> StdResult<FullSettingsResponse> {
+    let config_state = CONFIG_STATE.load(deps.storage)?;
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+
+    Ok(FullSettingsResponse { 
+        owner: config_state.owner.to_string(), 
+        cycle_length_in_seconds: config_state.cycle_length_in_seconds,
+        period_length_in_cycles: config_state.period_length_in_cycles,
+        white_listed_nft_contract: config_state.white_listed_nft_contract,
+        whitelisted_contracts: whitelisted_contracts(deps)?,
+        rewards_token_contract: config_state.rewards_token_contract,
+        booster_contract: config_state.booster_contract,
+        booster_bps: config_state.booster_bps,
+        min_cycle_length: get_min_cycle_length(deps)?,
+        rewards_per_cycle: REWARDS_SCHEDULE.may_load(deps.storage)?,
+        max_compute_period: MAX_COMPUTE_PERIOD.load(deps.storage)?,
+        unbonding_duration: UNBONDING_DURATION.load(deps.storage)?,
+        min_tokens_to_earn: MIN_TOKENS_TO_EARN.may_load(deps.storage)?.unwrap_or(0),
+        stake_forwarders: STAKE_FORWARDERS.may_load(deps.storage)?,
+        claim_cliff_period: CLAIM_CLIFF_PERIOD.may_load(deps.storage)?.unwrap_or(0),
+        auto_disable_threshold: AUTO_DISABLE_THRESHOLD.may_load(deps.storage)?,
+        require_approved_recipient: REQUIRE_APPROVED_RECIPIENT.may_load(deps.storage)?.unwrap_or(false),
+        allow_prestake: ALLOW_PRESTAKE.may_load(deps.storage)?.unwrap_or(false),
+        allow_public_funding: ALLOW_PUBLIC_FUNDING.may_load(deps.storage)?.unwrap_or(false),
+        require_claim_before_unstake: REQUIRE_CLAIM_BEFORE_UNSTAKE.may_load(deps.storage)?.unwrap_or(false),
+        auto_finalize_on_claim: AUTO_FINALIZE_ON_CLAIM.may_load(deps.storage)?.unwrap_or(false),
+        prorate_final_period: PRORATE_FINAL_PERIOD.may_load(deps.storage)?.unwrap_or(false),
+        rewards_follow_nft: REWARDS_FOLLOW_NFT.may_load(deps.storage)?.unwrap_or(false),
+        fee_on_transfer_token: FEE_ON_TRANSFER_TOKEN.may_load(deps.storage)?.unwrap_or(false),
+        max_reward_per_claim_per_token: MAX_REWARD_PER_CLAIM_PER_TOKEN.may_load(deps.storage)?,
+        min_pool_balance_to_stake: MIN_POOL_BALANCE_TO_STAKE.may_load(deps.storage)?,
+        emission_end_period: EMISSION_END_PERIOD.may_load(deps.storage)?,
+        emission_mode: EMISSION_MODE.may_load(deps.storage)?.unwrap_or(EmissionMode::Flat),
+        reward_per_period: REWARD_PER_PERIOD.may_load(deps.storage)?,
+        min_unstake_cycles: MIN_UNSTAKE_CYCLES.may_load(deps.storage)?.unwrap_or(DEFAULT_MIN_UNSTAKE_CYCLES),
+        withdraw_timelock_seconds: WITHDRAW_TIMELOCK_SECONDS.may_load(deps.storage)?,
+        pool_constrained_emission: POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false),
+        validate_contracts: VALIDATE_CONTRACTS.may_load(deps.storage)?.unwrap_or(false),
+        start: start_timestamp.is_some(),
+        start_time: start_timestamp.unwrap_or(0),
+        disable: DISABLE.load(deps.storage)?,
     })
 }
 
+// query nft contracts of which nfts are able to be staked.
+// only one nft contract is whitelisted for now, so the list has the single whitelisted nft contract.
+fn whitelisted_contracts(deps: Deps) -> StdResult<Vec<String>> {
+    let config = CONFIG_STATE.load(deps.storage)?;
+    Ok(vec![config.white_listed_nft_contract])
+}
+
 // query current cycle and period.
 fn get_current_cycle_and_period(
     deps: Deps,
@@ -112,13 +230,9 @@ fn get_rewards_schedule(
 ) -> StdResult<RewardsScheduleResponse> {
     let rewards_schedule = REWARDS_SCHEDULE.may_load(deps.storage)?;
 
-    if rewards_schedule.is_none() {
-        Ok(RewardsScheduleResponse::none_rewards_schedule())
-        
-    } else {
-        Ok(RewardsScheduleResponse::new(
-            rewards_schedule.unwrap(), 
-        ))
+    match rewards_schedule {
+        Some(t) => Ok(RewardsScheduleResponse::new(t)),
+        None => Ok(RewardsScheduleResponse::none_rewards_schedule()),
     }
 }
 
@@ -126,23 +240,134 @@ fn get_rewards_schedule(
 fn get_max_compute_period(
     deps: Deps,
 ) -> StdResult<MaxComputePeriodResponse> {
-    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
-
-    let res = MaxComputePeriodResponse {
-        max_compute_period,
-    };
+    // the defaults of instantiate are used if the item is not set by a migration.
+    let max_compute_period = MAX_COMPUTE_PERIOD.may_load(deps.storage)?;
+    if max_compute_period.is_none() {
+        return Ok(MaxComputePeriodResponse::defaulted(DEFAULT_MAX_COMPUTE_PERIOD))
+    }
 
-    Ok(res)
+    Ok(MaxComputePeriodResponse::new(max_compute_period.unwrap()))
 }
 
 // query unbonding duration.
 fn get_unbonding_duration(
     deps: Deps,
 ) -> StdResult<UnbondingDurationResponse> {
-    let unbonding_duration = UNBONDING_DURATION.load(deps.storage)?;
+    let unbonding_duration = UNBONDING_DURATION.may_load(deps.storage)?;
+    if unbonding_duration.is_none() {
+        return Ok(UnbondingDurationResponse::defaulted(DEFAULT_UNBONDING_DURATION))
+    }
+
+    Ok(UnbondingDurationResponse::new(unbonding_duration.unwrap()))
+}
+
+// query min tokens to earn rewards.
+fn get_min_tokens_to_earn(
+    deps: Deps,
+) -> StdResult<MinTokensToEarnResponse> {
+    let min_tokens_to_earn = MIN_TOKENS_TO_EARN.may_load(deps.storage)?.unwrap_or(0);
+
+    let res = MinTokensToEarnResponse {
+        min_tokens_to_earn,
+    };
+
+    Ok(res)
+}
+
+// get forwarder addresses which can stake on behalf of other address.
+fn get_stake_forwarders(
+    deps: Deps,
+) -> StdResult<StakeForwardersResponse> {
+    let stake_forwarders = STAKE_FORWARDERS.may_load(deps.storage)?;
 
-    let res = UnbondingDurationResponse {
-        unbonding_duration,
+    let res = StakeForwardersResponse {
+        stake_forwarders,
+    };
+
+    Ok(res)
+}
+
+// get claim cliff period.
+fn get_claim_cliff_period(
+    deps: Deps,
+) -> StdResult<ClaimCliffPeriodResponse> {
+    let claim_cliff_period = CLAIM_CLIFF_PERIOD.may_load(deps.storage)?.unwrap_or(0);
+
+    let res = ClaimCliffPeriodResponse {
+        claim_cliff_period,
+    };
+
+    Ok(res)
+}
+
+// get auto disable threshold of the rewards pool.
+fn get_auto_disable_threshold(
+    deps: Deps,
+) -> StdResult<AutoDisableThresholdResponse> {
+    let auto_disable_threshold = AUTO_DISABLE_THRESHOLD.may_load(deps.storage)?;
+
+    let res = AutoDisableThresholdResponse {
+        auto_disable_threshold,
+    };
+
+    Ok(res)
+}
+
+// get max rewards per claim of a token.
+fn get_max_reward_per_claim_per_token(
+    deps: Deps,
+) -> StdResult<MaxRewardPerClaimPerTokenResponse> {
+    let max_reward_per_claim_per_token = MAX_REWARD_PER_CLAIM_PER_TOKEN.may_load(deps.storage)?;
+
+    let res = MaxRewardPerClaimPerTokenResponse {
+        max_reward_per_claim_per_token,
+    };
+
+    Ok(res)
+}
+
+// get the period which rewards emission is ended.
+fn get_emission_end_period(
+    deps: Deps,
+) -> StdResult<EmissionEndPeriodResponse> {
+    let emission_end_period = EMISSION_END_PERIOD.may_load(deps.storage)?;
+
+    let res = EmissionEndPeriodResponse {
+        emission_end_period,
+    };
+
+    Ok(res)
+}
+
+// get the minimum cycles between the deposit cycle and the cycle of unstake.
+fn get_min_unstake_cycles(
+    deps: Deps,
+) -> StdResult<MinUnstakeCyclesResponse> {
+    let res = MinUnstakeCyclesResponse {
+        min_unstake_cycles: MIN_UNSTAKE_CYCLES.may_load(deps.storage)?.unwrap_or(DEFAULT_MIN_UNSTAKE_CYCLES),
+    };
+
+    Ok(res)
+}
+
+// get the effective minimum cycle length which is validated when the cycle length is set.
+fn min_cycle_length(
+    deps: Deps,
+) -> StdResult<MinCycleLengthResponse> {
+    let res = MinCycleLengthResponse {
+        min_cycle_length: get_min_cycle_length(deps)?,
+    };
+
+    Ok(res)
+}
+
+// get the emission mode and the reward per period of the shared per period mode.
+fn get_emission_mode(
+    deps: Deps,
+) -> StdResult<EmissionModeResponse> {
+    let res = EmissionModeResponse {
+        emission_mode: EMISSION_MODE.may_load(deps.storage)?.unwrap_or(EmissionMode::Flat),
+        reward_per_period: REWARD_PER_PERIOD.may_load(deps.storage)?,
     };
 
     Ok(res)
@@ -156,11 +381,9 @@ fn start_time(
     let now_time = env.block.time.seconds();
 
     let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
-    if start_timestamp.is_none() {
-        Ok(StartTimeResponse::not_started(now_time))
-
-    } else {
-        Ok(StartTimeResponse::new(start_timestamp.unwrap(), now_time))
+    match start_timestamp {
+        Some(t) => Ok(StartTimeResponse::new(t, now_time)),
+        None => Ok(StartTimeResponse::not_started(now_time)),
     }
 }
 
@@ -183,14 +406,43 @@ fn total_rewards_pool (
     deps: Deps,
 ) -> StdResult<TotalRewardsPoolResponse> {
     let total_rewards_pool = TOTAL_REWARDS_POOL.may_load(deps.storage)?;
-    if total_rewards_pool.is_none() {
-        Ok(TotalRewardsPoolResponse::empty_rewards_pool())
-
-    } else {
-        Ok(TotalRewardsPoolResponse::new(total_rewards_pool.unwrap()))
+    match total_rewards_pool {
+        Some(t) => Ok(TotalRewardsPoolResponse::new(t)),
+        None => Ok(TotalRewardsPoolResponse::empty_rewards_pool()),
     }
 }
 
+// get total rewards which have ever been paid out to stakers.
+fn total_paid_out(
+    deps: Deps,
+) -> StdResult<TotalPaidOutResponse> {
+    let total_paid_out = TOTAL_PAID_OUT.may_load(deps.storage)?.unwrap_or(0);
+
+    Ok(TotalPaidOutResponse { total_paid_out })
+}
+
+// get the multiplier bps of rewards of the token, 10000 is returned if it is not set.
+fn token_multiplier(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<TokenMultiplierResponse> {
+    let bps = TOKEN_MULTIPLIERS.may_load(deps.storage, token_id.clone())?.unwrap_or(DEFAULT_TOKEN_MULTIPLIER_BPS);
+
+    Ok(TokenMultiplierResponse { token_id, bps })
+}
+
+// get the operational metrics of processed claims and unstakes.
+fn metrics(
+    deps: Deps,
+) -> StdResult<MetricsResponse> {
+    Ok(MetricsResponse {
+        total_claims: TOTAL_CLAIMS.may_load(deps.storage)?.unwrap_or(0),
+        total_unstakes: TOTAL_UNSTAKES.may_load(deps.storage)?.unwrap_or(0),
+        total_paid_out: TOTAL_PAID_OUT.may_load(deps.storage)?.unwrap_or(0),
+        number_of_staked_nfts: NUMBER_OF_STAKED_NFTS.may_load(deps.storage)?.unwrap_or(0),
+    })
+}
+
 // get current amounts withdrawal rewards pool.
 fn withdraw_rewards_pool_amount (
     deps: Deps,
@@ -211,6 +463,58 @@ fn withdraw_rewards_pool_amount (
     }
 }
 
+// get the withdraw timelock and the withdrawal of rewards pool which waits for the timelock.
+// stakers are able to monitor the pending withdrawal and exit before it is ready.
+fn pending_withdrawal(
+    deps: Deps,
+) -> StdResult<PendingWithdrawalResponse> {
+    Ok(PendingWithdrawalResponse {
+        withdraw_timelock_seconds: WITHDRAW_TIMELOCK_SECONDS.may_load(deps.storage)?,
+        pending_withdrawal: PENDING_WITHDRAWAL.may_load(deps.storage)?,
+    })
+}
+
+// get the queued withdrawals of rewards pool with their amounts and ready timestamps.
+// only one withdrawal is able to be pending, the list is empty when none is pending.
+fn pending_withdrawals(
+    deps: Deps,
+) -> StdResult<PendingWithdrawalsResponse> {
+    let pending_withdrawals = PENDING_WITHDRAWAL.may_load(deps.storage)?.into_iter().collect();
+
+    Ok(PendingWithdrawalsResponse { pending_withdrawals })
+}
+
+// get the ledger of the pool constrained emission and the decided unfunded periods.
+// the completed periods after the processed period are decided by the next claim, unstake, stake, deposit or withdrawal.
+fn emission_ledger(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EmissionLedgerResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let ledger = EMISSION_LEDGER.may_load(deps.storage)?.unwrap_or_default();
+
+    // one more period is loaded to check whether the next page exists.
+    let mut unfunded_periods = UNFUNDED_PERIODS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<u64>>>()?;
+    let mut next_start_after = None;
+    if unfunded_periods.len() > limit {
+        unfunded_periods.truncate(limit);
+        next_start_after = unfunded_periods.last().cloned();
+    }
+
+    Ok(EmissionLedgerResponse {
+        pool_constrained_emission: POOL_CONSTRAINED_EMISSION.may_load(deps.storage)?.unwrap_or(false),
+        processed_period: ledger.processed_period,
+        budget: ledger.budget,
+        unfunded_periods,
+        next_start_after,
+    })
+}
+
 // get next claims state of staker_tokenid_key.
 fn next_claims(
     deps: Deps,
@@ -219,12 +523,26 @@ fn next_claims(
 ) -> StdResult<NextClaimResponse> {
     let staker_tokenid_key = staker_tokenid_key(staker, token_id);
     let next_claims = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key)?;
-    if next_claims.is_none() {
-        Ok(NextClaimResponse::empty_next_claim())
+    match next_claims {
+        Some(t) => Ok(NextClaimResponse::new(t)),
+        None => Ok(NextClaimResponse::empty_next_claim()),
+    }
+}
 
-    } else {
-        Ok(NextClaimResponse::new(next_claims.unwrap()))
+// get next claims of the pairs of staker and token id at once.
+// the responses are in the order of the keys, and the missing next claim is returned as the empty next claim.
+// the number of keys is capped by MAX_LIMIT.
+fn next_claims_batch(
+    deps: Deps,
+    keys: Vec<(String, String)>,
+) -> StdResult<Vec<NextClaimResponse>> {
+    if keys.len() > MAX_LIMIT as usize {
+        return Err(StdError::generic_err(format!("too many keys, max {} keys per batch", MAX_LIMIT)))
     }
+
+    keys.into_iter()
+        .map(|(staker, token_id)| next_claims(deps, staker, token_id))
+        .collect()
 }
 
 // get staker history.
@@ -237,11 +555,25 @@ fn staker_history (
     let staker_tokenid_key = staker_tokenid_key(staker, token_id);
     let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone())?;
 
-    if staker_history.is_none() {
-        Ok(StakerHistoryResponse::have_not_history(staker_tokenid_key))
+    match staker_history {
+        Some(t) => Ok(StakerHistoryResponse::new(staker_tokenid_key, t)),
+        None => Ok(StakerHistoryResponse::have_not_history(staker_tokenid_key)),
+    }
+}
 
-    } else {
-        Ok(StakerHistoryResponse::new(staker_tokenid_key, staker_history.unwrap()))
+// get the snapshot history expanded to the ranges of cycles for charting.
+// the end cycle of each range is the start cycle of the next snapshot, and none for the last snapshot.
+fn staker_history_expanded(
+    deps: Deps,
+    staker: String,
+    token_id: String,
+) -> StdResult<StakerHistoryExpandedResponse> {
+    let staker_tokenid_key = staker_tokenid_key(staker, token_id);
+    let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone())?;
+
+    match staker_history {
+        Some(t) => Ok(StakerHistoryExpandedResponse::new(staker_tokenid_key, snapshot_ranges(&t))),
+        None => Ok(StakerHistoryExpandedResponse::have_not_history(staker_tokenid_key)),
     }
 }
 
@@ -253,20 +585,16 @@ fn token_infos (
 ) -> StdResult<TokenInfosResponse> {
     let token_infos = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
 
-    if token_infos.is_none() {
-        Ok(TokenInfosResponse::invalid_token_id(token_id))
-
-    } else {
-        if token_infos.clone().unwrap().is_staked {
-            Ok(TokenInfosResponse::new(deps, env, token_id, token_infos.unwrap()))
-
-        } else {
-            Ok(TokenInfosResponse::unstaked_token_id(token_id, token_infos.unwrap()))
-        }
+    match token_infos {
+        Some(t) if t.is_staked => Ok(TokenInfosResponse::new(deps, env, token_id, t)),
+        Some(t) => Ok(TokenInfosResponse::unstaked_token_id(token_id, t)),
+        None => Ok(TokenInfosResponse::invalid_token_id(token_id)),
     }
 }
 
 // get calculated current rewards of staker_tokenid_key.
+// the rewards schedule has no history of rates, so the current rewards per cycle is applied to every unclaimed period.
+// a rate change by add_rewards_for_periods is reflected in the estimate immediately, same as claim.
 pub fn estimate_rewards(
     deps: Deps,
     env: Env,
@@ -281,24 +609,39 @@ pub fn estimate_rewards(
         return Ok(EstimateRewardsResponse::not_started(staker_tokenid_key))
     }
 
-    let disable = DISABLE.load(deps.storage)?;
-    if disable == true {
-        return Ok(EstimateRewardsResponse::disabled(staker_tokenid_key))
+    // rewards are zero before the owner sets the rewards schedule, it is not a compute error.
+    if REWARDS_SCHEDULE.may_load(deps.storage)?.is_none() {
+        return Ok(EstimateRewardsResponse::no_rewards_schedule(staker_tokenid_key))
     }
 
+    // the disabled contract does not erase accrued rewards, so the estimate is computed with the disabled annotation.
+    let disable = DISABLE.load(deps.storage)?;
+
+    // the next claim is initialized at the period of staking, so the period zero is never properly staked.
     let next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?;
-    if next_claim.is_none() {
-        return Ok(EstimateRewardsResponse::invalid_claim(staker_tokenid_key))
+    let is_initialized = match next_claim {
+        Some(next_claim) => next_claim.period != 0,
+        None => false,
+    };
+    if !is_initialized {
+        return Ok(EstimateRewardsResponse::claim_not_initialized(staker_tokenid_key))
     }
 
     let config = CONFIG_STATE.load(deps.storage)?;
     let now = env.block.time.seconds();
 
+    // rewards of the unbonding token cannot be claimed, they are paid out when the token is unstaked.
+    let token_info = TOKEN_INFOS.load(deps.storage, token_id.clone())?;
+    let mut claimable_via = CLAIMABLE_VIA_CLAIM.to_string();
+    if token_info.bond_status == UNBONDING || token_info.bond_status == UNBONDED {
+        claimable_via = CLAIMABLE_VIA_UNSTAKE.to_string();
+    }
+
     let compute_rewards = compute_rewards(deps, staker_tokenid_key.clone(), periods, now, start_timestamp.unwrap(), config.clone(), token_id);
     match compute_rewards {
         Ok(t) => {
             let claim = t.0;
-            Ok(EstimateRewardsResponse::new(staker_tokenid_key, claim))
+            Ok(EstimateRewardsResponse::new(staker_tokenid_key, claim, claimable_via, disable))
         },
         Err(e) => {
             Ok(EstimateRewardsResponse::with_err(staker_tokenid_key, e))
@@ -306,6 +649,343 @@ pub fn estimate_rewards(
     }
 }
 
+// estimate rewards of the staker's tokens in a page.
+// the client controls the number of tokens by max_tokens and paginates by start_after.
+pub fn estimate_all_rewards(
+    deps: Deps,
+    env: Env,
+    periods: u64,
+    staker: String,
+    start_after: Option<String>,
+    max_tokens: Option<u32>,
+) -> StdResult<EstimateAllRewardsResponse> {
+    let max_tokens = max_tokens.unwrap_or(DEFAULT_ESTIMATE_ALL_TOKENS).min(MAX_ESTIMATE_ALL_TOKENS) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // one more token id is loaded to check whether the next page exists.
+    let token_ids: StdResult<Vec<_>> = TOKEN_INFOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, token_info)) => token_info.owner == staker,
+            Err(_) => true,
+        })
+        .take(max_tokens + 1)
+        .map(|item| item.map(|(token_id, _)| token_id))
+        .collect();
+
+    let mut token_ids = match token_ids {
+        Ok(t) => t,
+        Err(e) => return Ok(EstimateAllRewardsResponse::with_err(staker, e)),
+    };
+
+    let mut next_start_after = None;
+    if token_ids.len() > max_tokens {
+        token_ids.truncate(max_tokens);
+        next_start_after = token_ids.last().cloned();
+    }
+
+    let mut estimates = vec![];
+    let mut total_amount: u128 = 0;
+    for token_id in token_ids {
+        let estimate = estimate_rewards(deps, env.clone(), periods, token_id, staker.clone())?;
+        if estimate.res_msg == SUCCESS {
+            total_amount += estimate.claim.amount;
+        }
+        estimates.push(estimate);
+    }
+
+    Ok(EstimateAllRewardsResponse::new(staker, estimates, total_amount, next_start_after))
+}
+
+// get rewards accrued up to now for display, including the in-progress period prorated to the second.
+// the completed periods are computed up to the max compute period, and the in-progress period is added only when they are all computed.
+// the in-progress amount is an estimate and is not claimable until the period completes.
+fn accrued_up_to_now(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    token_id: String,
+) -> StdResult<AccruedUpToNowResponse> {
+    let staker_tokenid_key = staker_tokenid_key(staker, token_id.clone());
+
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(AccruedUpToNowResponse::not_started(staker_tokenid_key))
+    }
+    let start_timestamp = start_timestamp.unwrap();
+
+    let next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?;
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if next_claim.is_none() || token_info.is_none() {
+        return Ok(AccruedUpToNowResponse::empty_next_claim(staker_tokenid_key))
+    }
+    let token_info = token_info.unwrap();
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+
+    let compute_rewards = compute_rewards(deps, staker_tokenid_key.clone(), max_compute_period, now, start_timestamp, config.clone(), token_id.clone());
+    let (claim, computed_next_claim) = match compute_rewards {
+        Ok(t) => t,
+        Err(e) => return Ok(AccruedUpToNowResponse::with_err(staker_tokenid_key, e)),
+    };
+
+    let current_period = get_current_period(now, start_timestamp, config.clone());
+    let current_period = match current_period {
+        Ok(t) => t,
+        Err(e) => return Ok(AccruedUpToNowResponse::with_err(staker_tokenid_key, e)),
+    };
+
+    let mut in_progress_amount = 0;
+    if computed_next_claim.period == current_period {
+        let compute_in_progress_rewards = compute_in_progress_rewards(deps, staker_tokenid_key.clone(), token_info, now, start_timestamp, config, token_id);
+        in_progress_amount = match compute_in_progress_rewards {
+            Ok(t) => t,
+            Err(e) => return Ok(AccruedUpToNowResponse::with_err(staker_tokenid_key, e)),
+        };
+    }
+
+    Ok(AccruedUpToNowResponse::new(staker_tokenid_key, claim.amount, in_progress_amount))
+}
+
+// project rewards of a token which would be staked now over the periods.
+// the current rewards per cycle and period length are assumed for every period, and the rewards pool is not checked.
+// the projection is of the whole periods in the flat emission mode, the first period of the real stake is prorated.
+fn project_stake_rewards(
+    deps: Deps,
+    periods: u64,
+) -> StdResult<ProjectStakeRewardsResponse> {
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let rewards_per_cycle = REWARDS_SCHEDULE.may_load(deps.storage)?;
+    if rewards_per_cycle.is_none() {
+        return Ok(ProjectStakeRewardsResponse::invalid_rewards_schedule(periods, config.period_length_in_cycles))
+    }
+
+    Ok(ProjectStakeRewardsResponse::new(periods, rewards_per_cycle.unwrap(), config.period_length_in_cycles))
+}
+
+// get rewards which the token earned in exactly one period for auditing period by period accrual.
+// zero is returned for the period which the token was not staked.
+fn reward_for_period(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    token_id: String,
+    period: u64,
+) -> StdResult<RewardForPeriodResponse> {
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(RewardForPeriodResponse::not_started(staker_tokenid_key, period))
+    }
+
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if token_info.is_none() {
+        return Ok(RewardForPeriodResponse::invalid_token_id(staker_tokenid_key, period))
+    }
+    let token_info = token_info.unwrap();
+    if token_info.owner != staker {
+        return Ok(RewardForPeriodResponse::new(staker_tokenid_key, period, 0))
+    }
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    match compute_reward_for_period(deps, staker_tokenid_key.clone(), token_info, period, now, start_timestamp.unwrap(), config, token_id) {
+        Ok(amount) => Ok(RewardForPeriodResponse::new(staker_tokenid_key, period, amount)),
+        Err(e) => Ok(RewardForPeriodResponse::with_err(staker_tokenid_key, period, e)),
+    }
+}
+
+// estimate rewards of a staked nft per year by the current rate.
+// cycles per year are derived from the cycle length by the year of 365 days.
+// in the flat mode, every staked nft earns the rewards per cycle regardless of the number of staked nfts.
+// in the shared per period mode, the reward per period is split by the number of staked nfts,
+// and a staker who stakes when nothing is staked would earn the whole reward per period.
+// the rewards pool, the emission end period, the booster and the min tokens to earn are not considered.
+fn estimated_apr(
+    deps: Deps,
+) -> StdResult<EstimatedAprResponse> {
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let emission_mode = EMISSION_MODE.may_load(deps.storage)?.unwrap_or(EmissionMode::Flat);
+    let cycles_per_year = SECONDS_PER_YEAR / config.cycle_length_in_seconds;
+    let number_of_staked_nfts = NUMBER_OF_STAKED_NFTS.may_load(deps.storage)?.unwrap_or(0);
+
+    let rewards_per_nft_per_year = match emission_mode {
+        EmissionMode::Flat => {
+            let rewards_per_cycle = REWARDS_SCHEDULE.may_load(deps.storage)?;
+            if rewards_per_cycle.is_none() {
+                return Ok(EstimatedAprResponse::invalid_rewards_schedule(emission_mode, cycles_per_year, number_of_staked_nfts))
+            }
+            rewards_per_cycle.unwrap() * cycles_per_year as u128
+        },
+        EmissionMode::SharedPerPeriod => {
+            let reward_per_period = REWARD_PER_PERIOD.may_load(deps.storage)?.unwrap_or(0);
+            reward_per_period * cycles_per_year as u128 / config.period_length_in_cycles as u128 / number_of_staked_nfts.max(1)
+        },
+    };
+
+    Ok(EstimatedAprResponse::new(emission_mode, cycles_per_year, number_of_staked_nfts, rewards_per_nft_per_year))
+}
+
+// get the largest periods which claim_rewards accepts now.
+// it is the unclaimed periods capped by the max compute period, and zero if nothing is claimable.
+fn max_claimable_periods(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    token_id: String,
+) -> StdResult<MaxClaimablePeriodsResponse> {
+    let staker_tokenid_key = staker_tokenid_key(staker, token_id.clone());
+
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(MaxClaimablePeriodsResponse::not_started(staker_tokenid_key))
+    }
+
+    let next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?;
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id)?;
+    if next_claim.is_none() || token_info.is_none() {
+        return Ok(MaxClaimablePeriodsResponse::new(staker_tokenid_key, 0))
+    }
+    let next_claim = next_claim.unwrap();
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let end_claim_period = get_end_claim_period(deps, token_info.unwrap(), env.block.time.seconds(), start_timestamp.unwrap(), config);
+    match end_claim_period {
+        Ok(t) => {
+            if next_claim.period == 0 || next_claim.period >= t {
+                return Ok(MaxClaimablePeriodsResponse::new(staker_tokenid_key, 0))
+            }
+            let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+            Ok(MaxClaimablePeriodsResponse::new(staker_tokenid_key, (t - next_claim.period).min(max_compute_period)))
+        },
+        Err(e) => Ok(MaxClaimablePeriodsResponse::with_err(staker_tokenid_key, e)),
+    }
+}
+
+// plan the claims of the staked nft to claim all claimable periods.
+// each chunk is a claim of up to max compute period with its estimated rewards, by up to MAX_CLAIM_PLAN_CHUNKS chunks.
+// a chunk is shorter than max compute period when the max reward per claim per token is exceeded.
+fn claim_plan(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    token_id: String,
+) -> StdResult<ClaimPlanResponse> {
+    let staker_tokenid_key = staker_tokenid_key(staker, token_id.clone());
+
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(ClaimPlanResponse::not_started(staker_tokenid_key))
+    }
+    let start_timestamp = start_timestamp.unwrap();
+
+    let next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?;
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if next_claim.is_none() || token_info.is_none() {
+        return Ok(ClaimPlanResponse::new(staker_tokenid_key, 0, vec![], false))
+    }
+    let mut next_claim = next_claim.unwrap();
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let end_claim_period = match get_end_claim_period(deps, token_info.unwrap(), now, start_timestamp, config.clone()) {
+        Ok(t) => t,
+        Err(e) => return Ok(ClaimPlanResponse::with_err(staker_tokenid_key, e)),
+    };
+    if next_claim.period == 0 || next_claim.period >= end_claim_period {
+        return Ok(ClaimPlanResponse::new(staker_tokenid_key, 0, vec![], false))
+    }
+    let total_claimable_periods = end_claim_period - next_claim.period;
+
+    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+    let mut chunks: Vec<ClaimPlanChunk> = vec![];
+    while chunks.len() < MAX_CLAIM_PLAN_CHUNKS && next_claim.period < end_claim_period {
+        let compute_rewards = compute_rewards_from(deps, staker_tokenid_key.clone(), Some(next_claim.clone()), max_compute_period, now, start_timestamp, config.clone(), token_id.clone());
+        match compute_rewards {
+            Ok((claim, new_next_claim)) => {
+                if claim.periods == 0 {
+                    break
+                }
+                chunks.push(ClaimPlanChunk { periods: claim.periods, estimated_amount: claim.amount });
+                next_claim = new_next_claim;
+            },
+            Err(e) => return Ok(ClaimPlanResponse::with_err(staker_tokenid_key, e)),
+        }
+    }
+    let has_more = next_claim.period < end_claim_period;
+
+    Ok(ClaimPlanResponse::new(staker_tokenid_key, total_claimable_periods, chunks, has_more))
+}
+
+// diagnose pending rewards of the staked nft.
+// the last claimed period, the current period and the estimate are bundled into one snapshot.
+fn accrual_diff(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    token_id: String,
+) -> StdResult<AccrualDiffResponse> {
+    let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(AccrualDiffResponse::not_started(staker_tokenid_key))
+    }
+    let start_timestamp = start_timestamp.unwrap();
+
+    let next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?;
+    if next_claim.is_none() {
+        return Ok(AccrualDiffResponse::empty_next_claim(staker_tokenid_key))
+    }
+    let next_claim = next_claim.unwrap();
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let token_info = TOKEN_INFOS.load(deps.storage, token_id.clone())?;
+    let now = env.block.time.seconds();
+
+    let current_period = match get_current_period(now, start_timestamp, config.clone()) {
+        Ok(t) => t,
+        Err(e) => return Ok(AccrualDiffResponse::with_err(staker_tokenid_key, e)),
+    };
+
+    // rewards are not supplied after the staker requests unbond.
+    let capped_by_unbonding = token_info.bond_status == UNBONDING || token_info.bond_status == UNBONDED;
+    let mut end_claim_period = current_period;
+    if capped_by_unbonding {
+        end_claim_period = match get_unbond_end_claim_period(deps, token_info.clone(), start_timestamp, config.clone()) {
+            Ok(t) => t,
+            Err(e) => return Ok(AccrualDiffResponse::with_err(staker_tokenid_key, e)),
+        };
+    }
+
+    let mut unclaimed_periods = 0;
+    if next_claim.period != 0 && end_claim_period > next_claim.period {
+        unclaimed_periods = end_claim_period - next_claim.period;
+    }
+
+    let max_compute_period = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+    let compute_rewards = compute_rewards(deps, staker_tokenid_key.clone(), max_compute_period, now, start_timestamp, config, token_id);
+    match compute_rewards {
+        Ok(t) => {
+            Ok(AccrualDiffResponse::new(
+                staker_tokenid_key,
+                next_claim.period,
+                current_period,
+                unclaimed_periods,
+                t.0,
+                token_info.bond_status,
+                capped_by_unbonding,
+            ))
+        },
+        Err(e) => {
+            Ok(AccrualDiffResponse::with_err(staker_tokenid_key, e))
+        }
+    }
+}
+
 // get the number of staked nfts in the nft staking contract.
 fn number_of_staked_nfts(
     deps: Deps,
@@ -315,19 +995,66 @@ fn number_of_staked_nfts(
         return Ok(NumberOfStakedNftsResponse::not_started())
     }
 
-    let number_of_staked_nfts = NUMBER_OF_STAKED_NFTS.load(deps.storage)?;
-    Ok(NumberOfStakedNftsResponse::new(number_of_staked_nfts))
+    let number_of_staked_nfts = NUMBER_OF_STAKED_NFTS.may_load(deps.storage)?;
+    if number_of_staked_nfts.is_none() {
+        return Ok(NumberOfStakedNftsResponse::defaulted())
+    }
+
+    Ok(NumberOfStakedNftsResponse::new(number_of_staked_nfts.unwrap()))
+}
+
+// get the number of staked nfts by nft contract.
+fn staked_count_by_collection(
+    deps: Deps,
+) -> StdResult<StakedCountByCollectionResponse> {
+    let staked_counts: StdResult<Vec<_>> = STAKED_COUNT_BY_COLLECTION.range(deps.storage, None, None, Order::Ascending).collect();
+    match staked_counts {
+        Ok(t) => Ok(StakedCountByCollectionResponse::new(t)),
+        Err(e) => Ok(StakedCountByCollectionResponse::with_err(e)),
+    }
+}
+
+// get the number of staked booster nfts of the staker.
+fn boosters(
+    deps: Deps,
+    staker: String,
+) -> StdResult<BoostersResponse> {
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let booster_count = BOOSTERS.may_load(deps.storage, staker.clone())?.unwrap_or(0);
+
+    Ok(BoostersResponse {
+        staker,
+        booster_count,
+        booster_bps: config.booster_bps,
+    })
+}
+
+// get the number of staked nfts in effect for the period from the staked count history.
+fn staked_count_at(
+    deps: Deps,
+    period: u64,
+) -> StdResult<StakedCountAtPeriodResponse> {
+    match staked_count_at_period(deps, period) {
+        Ok(t) => Ok(StakedCountAtPeriodResponse::new(period, t)),
+        Err(e) => Ok(StakedCountAtPeriodResponse::with_err(period, e)),
+    }
 }
 
 // get staked nfts info by querying AllNftInfo of whitelisted nft contract.
+// if the nft contract does not implement AllNftInfo, is_error is set and res_msg has the error.
 fn staked_all_nft_info(
     deps: Deps,
     token_id: String,
 ) -> StdResult<StakedAllNftInfoResponse<Extension>> {
-    let config = get_config(deps)?;
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let mut nft_contract = config.clone().white_listed_nft_contract;
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if let Some(token_info) = token_info {
+        nft_contract = token_info.staked_nft_contract(config);
+    }
     
     let all_nft_info: Result<AllNftInfoResponse::<Extension>, StdError>  = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart{
-        contract_addr: config.white_listed_nft_contract,
+        contract_addr: nft_contract,
         msg: to_binary(&Cw721QueryMsg::AllNftInfo { 
             token_id, 
             include_expired: Some(true),
@@ -361,25 +1088,53 @@ fn staked_all_nft_info(
 }
 
 // the number of nfts which are staked by the staker.
+// all staked nfts are returned without the limit and the verification.
+// the verification queries the cw721 owner of every token, so the limit is capped at MAX_VERIFY_LIMIT.
 pub fn staked_nfts_by_owner(
     deps: Deps,
+    env: Env,
     staker: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    verify: Option<bool>,
 ) -> StdResult<StakedNftsByOwnerResponse> {
+    let verify = verify.unwrap_or(false);
+    let limit = match (verify, limit) {
+        (true, t) => t.unwrap_or(MAX_VERIFY_LIMIT).min(MAX_VERIFY_LIMIT) as usize,
+        (false, Some(t)) => t.min(MAX_LIMIT) as usize,
+        (false, None) => usize::MAX,
+    };
+    let start = start_after.map(Bound::exclusive);
 
-    let token_infos: StdResult<Vec<_>> = TOKEN_INFOS.range(deps.storage, None, None, Order::Ascending).collect();
+    // one more token is loaded to check whether the next page exists.
+    let token_infos: StdResult<Vec<_>> = TOKEN_INFOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, token_info)) => token_info.owner == staker,
+            Err(_) => true,
+        })
+        .take(limit.saturating_add(1))
+        .collect();
     match token_infos {
-        Ok(t) => {
+        Ok(mut t) => {
+            let mut next_start_after = None;
+            if t.len() > limit {
+                t.truncate(limit);
+                next_start_after = t.last().map(|(token_id, _)| token_id.clone());
+            }
+
+            let config = CONFIG_STATE.load(deps.storage)?;
             let mut staked_nfts: Vec<TokenInfoMsg> = vec![];
-            for token_info in t {
-                if token_info.1.owner == staker {
-                    let info = TokenInfoMsg {
-                        token_id: token_info.0,
-                        token_info: token_info.1,
-                    };
-                    staked_nfts.append(&mut vec![info])
+            for (token_id, token_info) in t {
+                // the token which the cw721 contract does not return the owner is not verified.
+                let mut verified = None;
+                if verify {
+                    let owner = query_nft_owner(deps, token_info.staked_nft_contract(config.clone()), token_id.clone());
+                    verified = Some(owner.is_ok_and(|t| t.owner == env.contract.address));
                 }
+                staked_nfts.push(TokenInfoMsg { token_id, token_info, verified })
             }
-            Ok(StakedNftsByOwnerResponse::new(staked_nfts))
+            Ok(StakedNftsByOwnerResponse::new(staked_nfts, next_start_after))
         },
         Err(e) => {
             let empty_response = vec![TokenInfoMsg::default()];
@@ -387,4 +1142,323 @@ pub fn staked_nfts_by_owner(
             Ok(StakedNftsByOwnerResponse::with_err(empty_response, e))
         }
     }
+}
+
+// get only token ids of the staker's nfts without token infos.
+fn staked_token_ids_by_owner(
+    deps: Deps,
+    staker: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StakedTokenIdsByOwnerResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // one more token id is loaded to check whether the next page exists.
+    let token_ids: StdResult<Vec<_>> = TOKEN_INFOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, token_info)) => token_info.owner == staker,
+            Err(_) => true,
+        })
+        .take(limit + 1)
+        .map(|item| item.map(|(token_id, _)| token_id))
+        .collect();
+
+    match token_ids {
+        Ok(mut t) => {
+            let mut next_start_after = None;
+            if t.len() > limit {
+                t.truncate(limit);
+                next_start_after = t.last().cloned();
+            }
+            Ok(StakedTokenIdsByOwnerResponse::new(t, next_start_after))
+        },
+        Err(e) => Ok(StakedTokenIdsByOwnerResponse::with_err(e)),
+    }
+}
+
+// summary of the staker's nfts which are staked from the nft contract in a page.
+// the token ids of the staker after start_after are scanned by the per-staker index up to limit,
+// rewards of the nfts in the page are estimated within the max compute period in total,
+// and truncated is set when the max compute period is exhausted.
+fn staker_collection_summary(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    nft_contract: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<StakerCollectionSummaryResponse> {
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(StakerCollectionSummaryResponse::not_started(staker, nft_contract))
+    }
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let mut compute_budget = MAX_COMPUTE_PERIOD.load(deps.storage)?;
+    let mut staked_count: u64 = 0;
+    let mut total_estimated_rewards: u128 = 0;
+    let mut truncated = false;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // the token ids of the staker are ranged by the per-staker index,
+    // one more token id is loaded to check whether the next page exists.
+    let token_ids: StdResult<Vec<String>> = STAKER_TOKENS
+        .prefix(staker.clone())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect();
+    let mut token_ids = match token_ids {
+        Ok(t) => t,
+        Err(e) => return Ok(StakerCollectionSummaryResponse::with_err(staker, nft_contract, e.to_string())),
+    };
+
+    let mut next_start_after = None;
+    if token_ids.len() > limit {
+        token_ids.truncate(limit);
+        next_start_after = token_ids.last().cloned();
+    }
+
+    let mut token_infos = vec![];
+    for token_id in token_ids {
+        if let Some(token_info) = TOKEN_INFOS.may_load(deps.storage, token_id.clone())? {
+            token_infos.push((token_id, token_info));
+        }
+    }
+
+    for (token_id, token_info) in token_infos {
+        if token_info.owner != staker || token_info.staked_nft_contract(config.clone()) != nft_contract {
+            continue
+        }
+        staked_count += 1;
+
+        if compute_budget == 0 {
+            truncated = true;
+            continue
+        }
+
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        if NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone())?.is_none() {
+            continue
+        }
+
+        let compute_rewards = compute_rewards(deps, staker_tokenid_key, compute_budget, now, start_timestamp.unwrap(), config.clone(), token_id);
+        match compute_rewards {
+            Ok(t) => {
+                total_estimated_rewards += t.0.amount;
+                compute_budget -= t.0.periods;
+                if compute_budget == 0 {
+                    truncated = true;
+                }
+            },
+            Err(e) => {
+                return Ok(StakerCollectionSummaryResponse::with_err(staker, nft_contract, e.to_string()))
+            }
+        }
+    }
+
+    Ok(StakerCollectionSummaryResponse::new(staker, nft_contract, staked_count, total_estimated_rewards, truncated, next_start_after))
+}
+
+// check the staked nft is marked as burned.
+fn is_token_burned(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<TokenBurnedResponse> {
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if token_info.is_none() {
+        return Ok(TokenBurnedResponse::invalid_token_id(token_id))
+    }
+
+    Ok(TokenBurnedResponse::new(token_id, token_info.unwrap().burned))
+}
+
+// query whether the token id is blocked from staking.
+fn is_token_blocked(
+    deps: Deps,
+    token_id: String,
+) -> StdResult<TokenBlockedResponse> {
+    let blocked = BLOCKED_TOKENS.may_load(deps.storage, token_id.clone())?.unwrap_or(false);
+
+    Ok(TokenBlockedResponse::new(token_id, blocked))
+}
+
+// get the default recipient of rewards for the staked nft and its expiration.
+// the expired recipient is returned with expired true, the rewards are paid to the staker.
+fn token_reward_recipient(
+    deps: Deps,
+    env: Env,
+    staker: String,
+    token_id: String,
+) -> StdResult<TokenRewardRecipientResponse> {
+    let staker_tokenid_key = staker_tokenid_key(staker, token_id);
+    let token_recipient = TOKEN_RECIPIENTS.may_load(deps.storage, staker_tokenid_key.clone())?;
+    if token_recipient.is_none() {
+        return Ok(TokenRewardRecipientResponse::new(staker_tokenid_key, None, None, false))
+    }
+    let token_recipient = token_recipient.unwrap();
+    let expired = token_recipient.expires.is_expired(&env.block);
+
+    Ok(TokenRewardRecipientResponse::new(staker_tokenid_key, Some(token_recipient.recipient), Some(token_recipient.expires), expired))
+}
+
+// check whether the next unstake of the nft returns the nft to the staker.
+// the unbonding nft is finalizable after the unbonding duration is passed from the requested unbond time.
+fn is_finalizable(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+) -> StdResult<IsFinalizableResponse> {
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if token_info.is_none() {
+        return Ok(IsFinalizableResponse::invalid_token_id(token_id))
+    }
+    let token_info = token_info.unwrap();
+
+    if token_info.bond_status != UNBONDING {
+        return Ok(IsFinalizableResponse::new(token_id, false, None))
+    }
+
+    let unbonding_duration = UNBONDING_DURATION.load(deps.storage)?;
+    let finalize_at = token_info.req_unbond_time + unbonding_duration + 1;
+    let finalizable = env.block.time.seconds() >= finalize_at;
+
+    Ok(IsFinalizableResponse::new(token_id, finalizable, Some(finalize_at)))
+}
+
+// get the block time when the staked nft is able to be unstaked by the frozen cycles.
+// the nft is frozen until the cycle of the deposit cycle + min unstake cycles starts.
+fn unstake_available_at(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+) -> StdResult<UnstakeAvailableAtResponse> {
+    let start_timestamp = START_TIMESTAMP.may_load(deps.storage)?;
+    if start_timestamp.is_none() {
+        return Ok(UnstakeAvailableAtResponse::not_started(token_id))
+    }
+
+    let token_info = TOKEN_INFOS.may_load(deps.storage, token_id.clone())?;
+    if token_info.is_none() {
+        return Ok(UnstakeAvailableAtResponse::invalid_token_id(token_id))
+    }
+    let token_info = token_info.unwrap();
+    if !token_info.is_staked {
+        return Ok(UnstakeAvailableAtResponse::unstaked_token_id(token_id))
+    }
+
+    let config = CONFIG_STATE.load(deps.storage)?;
+    let min_unstake_cycles = MIN_UNSTAKE_CYCLES.may_load(deps.storage)?.unwrap_or(DEFAULT_MIN_UNSTAKE_CYCLES);
+    let available_cycle = token_info.deposit_cycle + min_unstake_cycles;
+    let available_at = start_timestamp.unwrap() + available_cycle.saturating_sub(1) * config.cycle_length_in_seconds;
+    let available_now = env.block.time.seconds() >= available_at;
+
+    Ok(UnstakeAvailableAtResponse::new(token_id, available_at, available_now))
+}
+
+// get staker_tokenid_keys of next claims which have no token info staked by the staker.
+// they are left by state drift, and the contract owner is able to prune them by prune_orphaned_claims.
+fn orphaned_claims(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OrphanedClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // one more key is loaded to check whether the next page exists.
+    let keys: StdResult<Vec<_>> = NEXT_CLAIMS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok(key) => is_orphaned_claim(deps, key).unwrap_or(false),
+            Err(_) => true,
+        })
+        .take(limit + 1)
+        .collect();
+
+    match keys {
+        Ok(mut t) => {
+            let mut next_start_after = None;
+            if t.len() > limit {
+                t.truncate(limit);
+                next_start_after = t.last().cloned();
+            }
+            Ok(OrphanedClaimsResponse::new(t, next_start_after))
+        },
+        Err(e) => Ok(OrphanedClaimsResponse::with_err(e)),
+    }
+}
+
+// get the on-chain receipts of claims in the order of claims, filtered by the staker if it is set.
+fn claim_receipts(
+    deps: Deps,
+    staker: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ClaimReceiptsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // one more receipt is loaded to check whether the next page exists.
+    let receipts: StdResult<Vec<_>> = CLAIM_RECEIPTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, receipt)) => staker.is_none() || staker.as_ref() == Some(&receipt.staker),
+            Err(_) => true,
+        })
+        .take(limit + 1)
+        .map(|item| item.map(|(index, receipt)| ClaimReceiptMsg { index, receipt }))
+        .collect();
+
+    match receipts {
+        Ok(mut t) => {
+            let mut next_start_after = None;
+            if t.len() > limit {
+                t.truncate(limit);
+                next_start_after = t.last().map(|receipt| receipt.index);
+            }
+            Ok(ClaimReceiptsResponse::new(t, next_start_after))
+        },
+        Err(e) => Ok(ClaimReceiptsResponse::with_err(e)),
+    }
+}
+
+// get unbonding nfts which have passed the unbonding duration and can be unstaked now.
+fn finalizable_tokens(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FinalizableTokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let now = env.block.time.seconds();
+    let unbonding_duration = UNBONDING_DURATION.load(deps.storage)?;
+
+    // one more token is loaded to check whether the next page exists.
+    let tokens: StdResult<Vec<_>> = TOKEN_INFOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, token_info)) => token_info.bond_status == UNBONDING && now > token_info.req_unbond_time + unbonding_duration,
+            Err(_) => true,
+        })
+        .take(limit + 1)
+        .map(|item| item.map(|(token_id, token_info)| TokenInfoMsg { token_id, token_info, verified: None }))
+        .collect();
+
+    match tokens {
+        Ok(mut t) => {
+            let mut next_start_after = None;
+            if t.len() > limit {
+                t.truncate(limit);
+                next_start_after = t.last().map(|token| token.token_id.clone());
+            }
+            Ok(FinalizableTokensResponse::new(t, next_start_after))
+        },
+        Err(e) => Ok(FinalizableTokensResponse::with_err(e)),
+    }
 }
\ No newline at end of file
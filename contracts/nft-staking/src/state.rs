@@ -3,8 +3,8 @@ use std::str::FromStr;
 use cw20::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::{Addr, DepsMut, MessageInfo};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, DepsMut, MessageInfo, Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
 
 use crate::ContractError;
 
@@ -13,6 +13,21 @@ pub const UNBONDED: &str = "BOND_STATUS_UNBONDED";
 pub const UNBONDING: &str = "BOND_STATUS_UNBONDING";
 pub const BONDED: &str = "BOND_STATUS_BONDED";
 
+// default max compute period = 2500.
+// default unbonding duration = 1814400 (= 3 weeks).
+// default min unstake cycles = 2.
+pub const DEFAULT_MAX_COMPUTE_PERIOD: u64 = 2_500;
+pub const DEFAULT_UNBONDING_DURATION: u64 = 1_814_400;
+pub const DEFAULT_MIN_UNSTAKE_CYCLES: u64 = 2;
+
+// max compute period is rejected over the hard cap.
+// a snapshot is able to start in every cycle, so computed cycles over the recommended value are warned.
+pub const MAX_ALLOWED_COMPUTE_PERIOD: u64 = 10_000;
+pub const RECOMMENDED_MAX_COMPUTE_CYCLES: u64 = 7_500;
+
+// the emission ledger decides at most the number of completed periods per call, and the undecided periods are simulated up to the same number.
+pub const MAX_EMISSION_LEDGER_PERIODS: u64 = 100;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: Addr,
@@ -20,19 +35,29 @@ pub struct Config {
     pub period_length_in_cycles: u64,
     pub white_listed_nft_contract: String,
     pub rewards_token_contract: String,
+    // the staker who stakes an nft of the booster contract gets rewards increased by booster_bps.
+    #[serde(default)]
+    pub booster_contract: Option<String>,
+    #[serde(default)]
+    pub booster_bps: u16,
 }
 
+// flat mode pays the rewards per cycle to every staked nft.
+// shared per period mode splits the reward per period among the staked nfts of the period.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum EmissionMode {
+    Flat,
+    SharedPerPeriod,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Copy, Default)]
 pub struct Snapshot {
     pub is_staked: bool,
     pub start_cycle: u64,
 }
 
 impl Snapshot {
-    pub fn default() -> Self {
-        Snapshot { is_staked: false, start_cycle: 0 }
-    }
-
     pub fn new(
         is_staked: bool,
         start_cycle: u64,
@@ -41,6 +66,163 @@ impl Snapshot {
     }
 }
 
+// the range of cycles which a snapshot lasts, the end cycle is exclusive and none for the last snapshot.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SnapshotRange {
+    pub start_cycle: u64,
+    pub end_cycle: Option<u64>,
+    pub is_staked: bool,
+}
+
+// staker histories are saved compactly, a snapshot is encoded to a number as start_cycle << 1 | is_staked.
+// they are expanded to snapshots on read, so compute_rewards and queries use the vector of snapshots as before.
+// the history saved as the vector of snapshots by the previous version is read as it is until it is saved again.
+pub struct StakerHistories<'a> {
+    compact: Map<'a, String, Vec<u64>>,
+    legacy: Map<'a, String, Vec<Snapshot>>,
+}
+
+impl<'a> StakerHistories<'a> {
+    pub const fn new(
+        compact_namespace: &'a str,
+        legacy_namespace: &'a str,
+    ) -> Self {
+        StakerHistories { 
+            compact: Map::new(compact_namespace), 
+            legacy: Map::new(legacy_namespace),
+        }
+    }
+
+    pub fn encode(staker_history: &[Snapshot]) -> Vec<u64> {
+        staker_history
+            .iter()
+            .map(|snapshot| snapshot.start_cycle << 1 | snapshot.is_staked as u64)
+            .collect()
+    }
+
+    pub fn decode(compact_history: &[u64]) -> Vec<Snapshot> {
+        compact_history
+            .iter()
+            .map(|encoded| Snapshot::new(encoded & 1 == 1, encoded >> 1))
+            .collect()
+    }
+
+    pub fn may_load(
+        &self,
+        store: &dyn Storage,
+        staker_tokenid_key: String,
+    ) -> StdResult<Option<Vec<Snapshot>>> {
+        let compact_history = self.compact.may_load(store, staker_tokenid_key.clone())?;
+        if let Some(compact_history) = compact_history {
+            return Ok(Some(Self::decode(&compact_history)))
+        }
+
+        self.legacy.may_load(store, staker_tokenid_key)
+    }
+
+    pub fn load(
+        &self,
+        store: &dyn Storage,
+        staker_tokenid_key: String,
+    ) -> StdResult<Vec<Snapshot>> {
+        let compact_history = self.compact.may_load(store, staker_tokenid_key.clone())?;
+        if let Some(compact_history) = compact_history {
+            return Ok(Self::decode(&compact_history))
+        }
+
+        self.legacy.load(store, staker_tokenid_key)
+    }
+
+    pub fn save(
+        &self,
+        store: &mut dyn Storage,
+        staker_tokenid_key: String,
+        staker_history: &[Snapshot],
+    ) -> StdResult<()> {
+        self.legacy.remove(store, staker_tokenid_key.clone());
+        self.compact.save(store, staker_tokenid_key, &Self::encode(staker_history))
+    }
+
+    pub fn remove(
+        &self,
+        store: &mut dyn Storage,
+        staker_tokenid_key: String,
+    ) {
+        self.legacy.remove(store, staker_tokenid_key.clone());
+        self.compact.remove(store, staker_tokenid_key);
+    }
+
+    #[cfg(test)]
+    pub fn save_legacy(
+        &self,
+        store: &mut dyn Storage,
+        staker_tokenid_key: String,
+        staker_history: &[Snapshot],
+    ) -> StdResult<()> {
+        self.legacy.save(store, staker_tokenid_key, &staker_history.to_vec())
+    }
+}
+
+// the number of staked nfts of the staker by period.
+// the last number and the min number in the period are recorded when the number is changed,
+// the min number is the number of the period because the number must be held for the full period.
+// the number before the first record is recorded at period 0, and the staker who has no record uses the current number.
+pub struct StakerCountHistory<'a> {
+    last: Map<'a, (String, u64), u64>,
+    min: Map<'a, (String, u64), u64>,
+}
+
+impl<'a> StakerCountHistory<'a> {
+    pub const fn new(
+        last_namespace: &'a str,
+        min_namespace: &'a str,
+    ) -> Self {
+        StakerCountHistory {
+            last: Map::new(last_namespace),
+            min: Map::new(min_namespace),
+        }
+    }
+
+    pub fn record(
+        &self,
+        store: &mut dyn Storage,
+        staker: String,
+        period: u64,
+        prev_count: u64,
+        count: u64,
+    ) -> StdResult<()> {
+        let first_record = self.last.prefix(staker.clone()).keys(store, None, None, Order::Ascending).next().is_none();
+        if first_record {
+            self.last.save(store, (staker.clone(), 0), &prev_count)?;
+        }
+
+        // the number before the first change in the period is the number at the start of the period.
+        let prev_min = self.min.may_load(store, (staker.clone(), period))?.unwrap_or(prev_count);
+        self.min.save(store, (staker.clone(), period), &prev_min.min(count))?;
+        self.last.save(store, (staker, period), &count)
+    }
+
+    pub fn at_period(
+        &self,
+        store: &dyn Storage,
+        staker: String,
+        period: u64,
+        current_count: u64,
+    ) -> StdResult<u64> {
+        if let Some(min_count) = self.min.may_load(store, (staker.clone(), period))? {
+            return Ok(min_count)
+        }
+
+        let last_count = self.last
+            .prefix(staker)
+            .range(store, None, Some(Bound::inclusive(period)), Order::Descending)
+            .next()
+            .transpose()?;
+
+        Ok(last_count.map(|(_, count)| count).unwrap_or(current_count))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TokenInfo {
     pub owner: String,
@@ -49,10 +231,14 @@ pub struct TokenInfo {
     pub withdraw_cycle: u64,
     pub bond_status: String,
     pub req_unbond_time: u64,
+    #[serde(default)]
+    pub burned: bool,
+    #[serde(default)]
+    pub nft_contract: String,
 }
 
-impl TokenInfo {
-    pub fn default() -> Self {
+impl Default for TokenInfo {
+    fn default() -> Self {
         TokenInfo { 
             owner: String::from_str("").unwrap(), 
             is_staked: false, 
@@ -60,13 +246,18 @@ impl TokenInfo {
             withdraw_cycle: 0,
             bond_status: UNSPECIFIED.to_string(),
             req_unbond_time: 0,
+            burned: false,
+            nft_contract: String::from_str("").unwrap(),
         }
     }
+}
 
+impl TokenInfo {
     pub fn stake(
         owner: String,
         is_staked: bool,
         deposit_cycle: u64,
+        nft_contract: String,
     ) -> Self {
         TokenInfo { 
             owner, 
@@ -75,6 +266,8 @@ impl TokenInfo {
             withdraw_cycle: 0,
             bond_status: BONDED.to_string(),
             req_unbond_time: 0,
+            burned: false,
+            nft_contract,
         }
     }
 
@@ -84,6 +277,7 @@ impl TokenInfo {
         deposit_cycle: u64,
         withdraw_cycle: u64,
         req_unbond_time: u64,
+        nft_contract: String,
     ) -> Self {
         TokenInfo { 
             owner, 
@@ -92,6 +286,8 @@ impl TokenInfo {
             withdraw_cycle,
             bond_status: UNBONDING.to_string(),
             req_unbond_time,
+            burned: false,
+            nft_contract,
         }
     }
 
@@ -101,6 +297,7 @@ impl TokenInfo {
         deposit_cycle: u64,
         withdraw_cycle: u64,
         req_unbond_time: u64,
+        nft_contract: String,
     ) -> Self {
         TokenInfo { 
             owner, 
@@ -109,12 +306,15 @@ impl TokenInfo {
             withdraw_cycle,
             bond_status: UNBONDED.to_string(),
             req_unbond_time,
+            burned: false,
+            nft_contract,
         }
     }
     pub fn unstake(
         is_staked: bool,
         deposit_cycle: u64,
         withdraw_cycle: u64,
+        nft_contract: String,
     ) -> Self {
         TokenInfo { 
             owner: String::from_str("").unwrap(), 
@@ -123,9 +323,45 @@ impl TokenInfo {
             withdraw_cycle,
             bond_status: UNSPECIFIED.to_string(),
             req_unbond_time: 0,
+            burned: false,
+            nft_contract,
         }
     }
 
+    // the token is burned on the cw721 side while staked, accrual is stopped.
+    pub fn burned(
+        owner: String,
+        deposit_cycle: u64,
+        withdraw_cycle: u64,
+        bond_status: String,
+        req_unbond_time: u64,
+        nft_contract: String,
+    ) -> Self {
+        TokenInfo { 
+            owner, 
+            is_staked: false, 
+            deposit_cycle, 
+            withdraw_cycle,
+            bond_status,
+            req_unbond_time,
+            burned: true,
+            nft_contract,
+        }
+    }
+
+    // the nft contract of the staked nft.
+    // the whitelisted nft contract is used for the token info recorded before the nft contract is stored.
+    pub fn staked_nft_contract(
+        &self,
+        config: Config,
+    ) -> String {
+        if self.nft_contract.is_empty() {
+            return config.white_listed_nft_contract
+        }
+
+        self.nft_contract.clone()
+    }
+
     // check message sender is nft owner which records in the TOKEN_INFOs state.
     pub fn check_staker(
         deps: DepsMut,
@@ -148,39 +384,31 @@ impl TokenInfo {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
 pub struct NextClaim {
     pub period: u64,
     pub staker_snapshot_index: u64,
+    // the excess of a period which exceeded the max reward per claim per token, paid first by the next claim.
+    #[serde(default)]
+    pub remainder: u128,
 }
 
 impl NextClaim {
-    pub fn default() -> Self {
-        NextClaim { 
-            period: 0,
-            staker_snapshot_index: 0,
-        }
-    }
-
     pub fn new(
         period: u64,
         staker_snapshot_index: u64,
     ) -> Self {
-        NextClaim { period, staker_snapshot_index }
+        NextClaim { period, staker_snapshot_index, remainder: 0 }
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
 pub struct Claim {
     pub start_period: u64,
     pub periods: u64,
     pub amount: u128,
-}
-
-impl Claim {
-    pub fn default() -> Self {
-        Claim { start_period: 0, periods: 0, amount: 0 }
-    }
+    // the part of the amount which is paid in the secondary rewards token from the cutover period.
+    pub secondary_amount: u128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -194,25 +422,155 @@ impl Grant {
         address: String,
         expires: Option<Expiration>,
     ) -> Self {
-        let expires_data: Expiration;
-        if expires.is_none() {
-            expires_data = Expiration::default()
-        } else {
-            expires_data = expires.unwrap()
-        }
-        Grant { address, expires: expires_data }
+        Grant { address, expires: expires.unwrap_or_default() }
+    }
+}
+
+// the default recipient of rewards for the staked nft.
+// the recipient reverts to the staker after it expires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenRecipient {
+    pub recipient: String,
+    pub expires: Expiration,
+}
+
+impl TokenRecipient {
+    pub fn new(
+        recipient: String,
+        expires: Option<Expiration>,
+    ) -> Self {
+        TokenRecipient { recipient, expires: expires.unwrap_or_default() }
     }
 }
 
+// rewards pool deposit of fee on transfer token which waits for the reply to check the actual received amount.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRewardsDeposit {
+    pub requested_amount: u128,
+    pub balance_before: u128,
+}
+
+// on-chain receipt of the claim, a durable audit log of claims beyond the transient event.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimReceipt {
+    pub staker: String,
+    pub token_id: String,
+    pub amount: u128,
+    pub recipient: String,
+    pub start_period: u64,
+    pub periods: u64,
+    pub timestamp: u64,
+}
+
+// withdrawal of rewards pool requested by the owner which waits for the withdraw timelock.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingWithdrawal {
+    pub amount: u128,
+    pub recipient: String,
+    pub requested_at: u64,
+    pub ready_at: u64,
+}
+
+// ledger of the pool constrained emission.
+// the periods until the processed period are decided as funded or unfunded, the budget is the funded amount not emitted yet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct EmissionLedger {
+    pub processed_period: u64,
+    pub budget: u128,
+}
+
+// rotation of the rewards token, the periods from the cutover period are paid in the secondary rewards token.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardsTokenCutover {
+    pub secondary_rewards_token: String,
+    pub cutover_period: u64,
+}
+
+// the boost for the early stakers who staked before the boost deadline period.
+// the boost bps is applied until the deadline period and linearly decays to zero over the boost decay periods.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EarlyStakerBoost {
+    pub boost_bps: u16,
+    pub boost_deadline_period: u64,
+    pub boost_decay_periods: u64,
+}
+
 pub const CONFIG_STATE: Item<Config> = Item::new("config");
 pub const START_TIMESTAMP: Item<u64> = Item::new("start_timestamp");
 pub const REWARDS_SCHEDULE: Item<u128> = Item::new("rewards_schedule");
 pub const TOTAL_REWARDS_POOL: Item<u128> = Item::new("total_rewards_pool");
 pub const DISABLE: Item<bool> = Item::new("disable");
-pub const STAKER_HISTORIES: Map<String, Vec<Snapshot>> = Map::new("staker_histories");
+pub const STAKER_HISTORIES: StakerHistories = StakerHistories::new("compact_staker_histories", "staker_histories");
 pub const NEXT_CLAIMS: Map<String, NextClaim> = Map::new("next_claims");
 pub const TOKEN_INFOS: Map<String, TokenInfo> = Map::new("token_infos");
 pub const NUMBER_OF_STAKED_NFTS: Item<u128> = Item::new("number_of_staked_nfts");
 pub const MAX_COMPUTE_PERIOD: Item<u64> = Item::new("max_compute_period");
 pub const GRANTS: Map<String, Grant> = Map::new("grant");
-pub const UNBONDING_DURATION: Item<u64> = Item::new("unbonding_duration");
\ No newline at end of file
+pub const UNBONDING_DURATION: Item<u64> = Item::new("unbonding_duration");
+pub const STAKER_NFT_COUNTS: Map<String, u64> = Map::new("staker_nft_counts");
+pub const MIN_TOKENS_TO_EARN: Item<u64> = Item::new("min_tokens_to_earn");
+// the number of staked nfts of the staker by period to check min_tokens_to_earn of each period.
+pub const STAKER_NFT_COUNT_HISTORY: StakerCountHistory = StakerCountHistory::new("staker_nft_count_history", "staker_min_nft_count_history");
+pub const STAKE_FORWARDERS: Item<Vec<String>> = Item::new("stake_forwarders");
+pub const CLAIM_CLIFF_PERIOD: Item<u64> = Item::new("claim_cliff_period");
+pub const AUTO_DISABLE_THRESHOLD: Item<u128> = Item::new("auto_disable_threshold");
+pub const TOKEN_RECIPIENTS: Map<String, TokenRecipient> = Map::new("token_recipients");
+pub const REQUIRE_APPROVED_RECIPIENT: Item<bool> = Item::new("require_approved_recipient");
+pub const APPROVED_RECIPIENTS: Map<String, Vec<String>> = Map::new("approved_recipients");
+pub const STAKED_COUNT_BY_COLLECTION: Map<String, u64> = Map::new("staked_count_by_collection");
+pub const ALLOW_PRESTAKE: Item<bool> = Item::new("allow_prestake");
+pub const FEE_ON_TRANSFER_TOKEN: Item<bool> = Item::new("fee_on_transfer_token");
+pub const PENDING_REWARDS_DEPOSIT: Item<PendingRewardsDeposit> = Item::new("pending_rewards_deposit");
+pub const MAX_REWARD_PER_CLAIM_PER_TOKEN: Item<u128> = Item::new("max_reward_per_claim_per_token");
+pub const MIN_POOL_BALANCE_TO_STAKE: Item<u128> = Item::new("min_pool_balance_to_stake");
+pub const EMISSION_END_PERIOD: Item<u64> = Item::new("emission_end_period");
+pub const TOTAL_PAID_OUT: Item<u128> = Item::new("total_paid_out");
+pub const VALIDATE_CONTRACTS: Item<bool> = Item::new("validate_contracts");
+pub const EMISSION_MODE: Item<EmissionMode> = Item::new("emission_mode");
+pub const REWARD_PER_PERIOD: Item<u128> = Item::new("reward_per_period");
+pub const STAKED_COUNT_HISTORY: Map<u64, u128> = Map::new("staked_count_history");
+pub const MIN_CYCLE_LENGTH_IN_SECONDS: Item<u64> = Item::new("min_cycle_length_in_seconds");
+pub const MIN_UNSTAKE_CYCLES: Item<u64> = Item::new("min_unstake_cycles");
+pub const ALLOW_PUBLIC_FUNDING: Item<bool> = Item::new("allow_public_funding");
+// the number of staked booster nfts by staker.
+pub const BOOSTERS: Map<String, u64> = Map::new("boosters");
+// the staker of the staked booster nft by token id.
+pub const BOOSTER_TOKENS: Map<String, String> = Map::new("booster_tokens");
+// the number of staked booster nfts by staker and period, the booster must be staked for the full period to boost it.
+pub const BOOSTER_COUNT_HISTORY: StakerCountHistory = StakerCountHistory::new("booster_count_history", "booster_min_count_history");
+pub const REQUIRE_CLAIM_BEFORE_UNSTAKE: Item<bool> = Item::new("require_claim_before_unstake");
+// if it is set, claim_rewards on the token whose unbonding duration has elapsed finalizes the unstake instead of erroring.
+pub const AUTO_FINALIZE_ON_CLAIM: Item<bool> = Item::new("auto_finalize_on_claim");
+// if it is set, the completed cycles of the period of the requested unbond time are paid instead of dropping the partial period.
+pub const PRORATE_FINAL_PERIOD: Item<bool> = Item::new("prorate_final_period");
+// increased whenever the config is changed, claims with the expected config version are rejected if it differs.
+pub const CONFIG_VERSION: Item<u64> = Item::new("config_version");
+// if it is set, the rewards pool is withdrawn by request_withdraw and execute_withdraw after the timelock.
+pub const WITHDRAW_TIMELOCK_SECONDS: Item<u64> = Item::new("withdraw_timelock_seconds");
+pub const PENDING_WITHDRAWAL: Item<PendingWithdrawal> = Item::new("pending_withdrawal");
+// the token ids which are not able to be staked, e.g. stolen or flagged nfts.
+pub const BLOCKED_TOKENS: Map<String, bool> = Map::new("blocked_tokens");
+// claim receipts by the monotonic index which starts from 1.
+pub const CLAIM_RECEIPTS: Map<u64, ClaimReceipt> = Map::new("claim_receipts");
+pub const CLAIM_RECEIPT_COUNT: Item<u64> = Item::new("claim_receipt_count");
+// if it is set, the period which is not funded by the rewards pool does not accrue rewards until the pool is refilled.
+pub const POOL_CONSTRAINED_EMISSION: Item<bool> = Item::new("pool_constrained_emission");
+pub const EMISSION_LEDGER: Item<EmissionLedger> = Item::new("emission_ledger");
+pub const UNFUNDED_PERIODS: Map<u64, bool> = Map::new("unfunded_periods");
+// the max number of staked nfts in the period, it is recorded only in the pool constrained emission.
+pub const PEAK_STAKED_COUNT_HISTORY: Map<u64, u128> = Map::new("peak_staked_count_history");
+// if it is set, the accrued rewards of the token are paid to the current staker of the token instead of the staker who accrued them.
+pub const REWARDS_FOLLOW_NFT: Item<bool> = Item::new("rewards_follow_nft");
+pub const REWARDS_TOKEN_CUTOVER: Item<RewardsTokenCutover> = Item::new("rewards_token_cutover");
+// the number of processed claims and finalized unstakes for the operational metrics.
+pub const TOTAL_CLAIMS: Item<u64> = Item::new("total_claims");
+pub const TOTAL_UNSTAKES: Item<u64> = Item::new("total_unstakes");
+pub const EARLY_STAKER_BOOST: Item<EarlyStakerBoost> = Item::new("early_staker_boost");
+// the multiplier bps of rewards which is set by the contract owner per token id, 10000 is 1x if it is not set.
+pub const TOKEN_MULTIPLIERS: Map<String, u16> = Map::new("token_multipliers");
+// the largest multiplier bps which has ever been set, it is not decreased when the multiplier is lowered or removed.
+pub const MAX_TOKEN_MULTIPLIER_BPS: Item<u16> = Item::new("max_token_multiplier_bps");
+// whether the nft staking contract holds the minter role of the rewards token, it is verified by the cw20 minter query.
+pub const IS_REWARD_MINTER: Item<bool> = Item::new("is_reward_minter");
+// the per-staker index of the token ids whose token info records the staker as the owner.
+pub const STAKER_TOKENS: Map<(String, String), bool> = Map::new("staker_tokens");
\ No newline at end of file
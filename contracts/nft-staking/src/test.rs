@@ -1,17 +1,20 @@
 #[cfg(test)]
 mod tests{
+    use std::cell::RefCell;
     use std::ops::Add;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std:: {MessageInfo, DepsMut, Env, Empty, MemoryStorage, OwnedDeps, Addr, Uint128, BlockInfo, Timestamp, TransactionInfo, ContractInfo, to_binary, Response, Binary, CosmosMsg, WasmMsg};
-    use cw20::{Cw20Coin, MinterResponse, Cw20ReceiveMsg, Cw20ExecuteMsg, BalanceResponse, Expiration};
+    use cosmwasm_std:: {MessageInfo, DepsMut, Env, Empty, MemoryStorage, OwnedDeps, Addr, Uint128, BlockInfo, Timestamp, TransactionInfo, ContractInfo, to_binary, Response, Binary, CosmosMsg, WasmMsg, WasmQuery, SystemResult, ContractResult, SystemError, from_binary, Deps, QuerierWrapper, Storage, Order, Record, Reply, SubMsgResult, SubMsgResponse, to_vec};
+    use cw20::{Cw20Coin, MinterResponse, Cw20ReceiveMsg, Cw20ExecuteMsg, BalanceResponse, Expiration, Cw20QueryMsg, TokenInfoResponse};
     use cw20_base::contract::{instantiate, execute, query_balance};
-    use cw721::{Cw721ReceiveMsg, Cw721Execute};
+    use cw721::{Cw721ReceiveMsg, Cw721Execute, Cw721QueryMsg, OwnerOfResponse, AllNftInfoResponse, NftInfoResponse, ContractInfoResponse as Cw721ContractInfoResponse};
     use cw721_base::{Cw721Contract, Extension, InstantiateMsg as Cw721BaseInstantiateMsg, ExecuteMsg as Cw721BaseExecuteMsg, MintMsg};
     use cw20_base::{msg::InstantiateMsg as Cw20InstantiateMsg};
-    use crate::execute::{instantiate as nft_staking_instantiate, add_rewards_pool, add_rewards_for_periods, start, grant, set_config, revoke, disable, claim_rewards, unstake_nft, withdraw_all_rewards_pool};
-    use crate::handler::{get_cycle, update_histories, IS_STAKED, get_period, check_start_timestamp, check_disable, staker_tokenid_key, get_current_period, manage_number_nfts, check_unbonding_end, compute_rewards};
-    use crate::msg::{InstantiateMsg, SetConfigMsg};
-    use crate::state::{Config, CONFIG_STATE, TOTAL_REWARDS_POOL, REWARDS_SCHEDULE, NEXT_CLAIMS, NextClaim, TOKEN_INFOS, TokenInfo, STAKER_HISTORIES, MAX_COMPUTE_PERIOD, UNBONDING_DURATION, BONDED, UNBONDING, START_TIMESTAMP, Claim};
+    use cw2::{ContractVersion, set_contract_version};
+    use crate::execute::{CONTRACT_NAME as NFT_STAKING_CONTRACT_NAME, migrate, CONTRACT_VERSION, instantiate as nft_staking_instantiate, set_rewards_token_cutover, add_rewards_pool, add_rewards_for_periods, set_rewards_per_period, start, grant, set_config, replace_whitelisted_contract, revoke, extend_grant, disable, enable, set_disabled, claim_rewards, claim_all_rewards, unstake_nft, withdraw_all_rewards_pool, withdraw_rewards_pool, sync_rewards_pool, mark_token_burned, set_min_tokens_to_earn, stake_nft, set_stake_forwarders, set_claim_cliff_period, set_unbonding_duration, set_auto_disable_threshold, set_token_reward_recipient, set_require_approved_recipient, approve_reward_recipient, skip_to_current_period, set_allow_prestake, set_allow_public_funding, set_require_claim_before_unstake, set_auto_finalize_on_claim, set_prorate_final_period, set_early_staker_boost, set_token_multiplier, refresh_reward_minter, set_booster, unstake_booster, set_fee_on_transfer_token, add_rewards_pool_from, reply, ADD_REWARDS_POOL_REPLY_ID, set_max_reward_per_claim_per_token, set_min_pool_balance_to_stake, set_emission_end_period, set_emission_mode, set_min_unstake_cycles, set_max_compute_period, set_withdraw_timelock_seconds, request_withdraw, execute_withdraw, cancel_withdraw, block_token, unblock_token, prune_orphaned_claims, sweep_abandoned_rewards, index_staker_tokens, process_emission_ledger, set_pool_constrained_emission, stake_many, set_rewards_follow_nft};
+    use crate::handler::{get_cycle, update_histories, IS_STAKED, get_period, check_start_timestamp, check_disable, staker_tokenid_key, get_current_period, manage_number_nfts, check_unbonding_end, compute_rewards, MAX_SNAPSHOTS_PER_TOKEN, save_token_info};
+    use crate::msg::{InstantiateMsg, MigrateMsg, IsRewardMinterResponse, TokenMultiplierResponse, EarlyStakerBoostResponse, MetricsResponse, RewardsTokenCutoverResponse, StakerHistoryResponse, SetConfigMsg, QueryMsg, ConfigResponse, SUCCESS, GetGrantsResponse, TokenBurnedResponse, MinTokensToEarnResponse, FullSettingsResponse, AccrualDiffResponse, StakeNftMsg, StakeForwardersResponse, ClaimCliffPeriodResponse, EstimateRewardsResponse, EstimateAllRewardsResponse, AccruedUpToNowResponse, RewardForPeriodResponse, ProjectStakeRewardsResponse, EstimatedAprResponse, BoostersResponse, NextClaimResponse, MaxComputePeriodResponse, UnbondingDurationResponse, NumberOfStakedNftsResponse, MaxClaimablePeriodsResponse, AutoDisableThresholdResponse, MaxRewardPerClaimPerTokenResponse, EmissionEndPeriodResponse, EmissionModeResponse, MinCycleLengthResponse, MinUnstakeCyclesResponse, TotalPaidOutResponse, TokenRewardRecipientResponse, StakedCountByCollectionResponse, StakedCountAtPeriodResponse, FinalizableTokensResponse, IsFinalizableResponse, UnstakeAvailableAtResponse, StakedNftsByOwnerResponse, StakedTokenIdsByOwnerResponse, StakedAllNftInfoResponse, StakerCollectionSummaryResponse, CLAIMABLE_VIA_CLAIM, CLAIMABLE_VIA_UNSTAKE, PendingWithdrawalResponse, PendingWithdrawalsResponse, TokenBlockedResponse, ClaimReceiptsResponse, StakerHistoryExpandedResponse, ClaimPlanResponse, ClaimPlanChunk, OrphanedClaimsResponse, EmissionLedgerResponse};
+    use crate::query::query;
+    use crate::state::{Config, MAX_TOKEN_MULTIPLIER_BPS, MAX_EMISSION_LEDGER_PERIODS, CONFIG_STATE, MIN_TOKENS_TO_EARN, CLAIM_CLIFF_PERIOD, EMISSION_MODE, MIN_UNSTAKE_CYCLES, FEE_ON_TRANSFER_TOKEN, REQUIRE_APPROVED_RECIPIENT, ALLOW_PRESTAKE, TOKEN_MULTIPLIERS, EarlyStakerBoost, RewardsTokenCutover, TOTAL_REWARDS_POOL, REWARDS_SCHEDULE, NEXT_CLAIMS, NextClaim, TOKEN_INFOS, TokenInfo, STAKER_HISTORIES, MAX_COMPUTE_PERIOD, UNBONDING_DURATION, BONDED, UNBONDING, UNBONDED, UNSPECIFIED, START_TIMESTAMP, Claim, NUMBER_OF_STAKED_NFTS, STAKER_NFT_COUNTS, DISABLE, Snapshot, StakerHistories, VALIDATE_CONTRACTS, EmissionMode, GRANTS, PendingWithdrawal, ClaimReceipt, SnapshotRange, MAX_ALLOWED_COMPUTE_PERIOD, STAKER_TOKENS};
     use crate::error::ContractError;
 
     const CONTRACT_NAME: &str = "CW721CTRT";
@@ -25,6 +28,8 @@ mod tests{
     const PERIOD_LENGTH_IN_CYCLES: u64 = 3;
     const REWARDS_PER_CYCLE: u128 = 17;
     const DEFAULT_MAX_COMPUTE_PERIOD: u64 = 2500;
+    // the error of the cw721-base contract for the token id which does not exist.
+    const CW721_TOKEN_NOT_FOUND: &str = "cw721_base::state::TokenInfo<core::option::Option<cosmwasm_std::results::empty::Empty>> not found";
 
     #[test]
     fn test_set_config() {
@@ -48,6 +53,397 @@ mod tests{
         assert_eq!(config.rewards_token_contract, mock_env_cw20().contract.address);
     }
 
+    #[test]
+    fn test_max_period_length() {
+        // instantiate with the period length over the cap
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            cycle_length_in_seconds: CYCLE_LENGTH_IN_SECONDS,
+            period_length_in_cycles: 1_001,
+            white_listed_nft_contract: mock_env_cw721().contract.address.to_string(),
+            rewards_token_contract: mock_env_cw20().contract.address.to_string(),
+            validate_contracts: None,
+            min_cycle_length_override: None,
+            owner: None,
+        };
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg);
+        assert_eq!(ContractError::PeriodLengthTooLong { max_period_length: 1_000, period_length_in_cycles: 1_001 }.to_string(), res.err().unwrap().to_string());
+
+        // set config with the period length over the cap
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: None,
+            period_length_in_cycles: Some(1_001),
+            white_listed_nft_contract: None,
+            rewards_token_contract: None,
+        };
+        let res = set_config(deps.as_mut(), info.clone(), env.clone(), config.clone(), set_config_msg);
+        assert_eq!(ContractError::PeriodLengthTooLong { max_period_length: 1_000, period_length_in_cycles: 1_001 }.to_string(), res.err().unwrap().to_string());
+
+        // the cap itself is allowed
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: None,
+            period_length_in_cycles: Some(1_000),
+            white_listed_nft_contract: None,
+            rewards_token_contract: None,
+        };
+        set_config(deps.as_mut(), info, env, config, set_config_msg).unwrap();
+        assert_eq!(CONFIG_STATE.load(deps.as_mut().storage).unwrap().period_length_in_cycles, 1_000);
+    }
+
+    #[test]
+    fn test_single_cycle_period() {
+        // the period length of zero is rejected and one is allowed
+        let instantiate_msg = |period_length_in_cycles: u64| InstantiateMsg {
+            cycle_length_in_seconds: CYCLE_LENGTH_IN_SECONDS,
+            period_length_in_cycles,
+            white_listed_nft_contract: mock_env_cw721().contract.address.to_string(),
+            rewards_token_contract: mock_env_cw20().contract.address.to_string(),
+            validate_contracts: None,
+            min_cycle_length_override: None,
+            owner: None,
+        };
+        let mut deps = mock_dependencies();
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(0));
+        assert_eq!(ContractError::PeriodLengthInvalid { min_period: 1, period_length_in_cycles: 0 }.to_string(), res.err().unwrap().to_string());
+        nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(1)).unwrap();
+
+        // do stake, the period is same as the cycle
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let config = Config { period_length_in_cycles: 1, ..config };
+        CONFIG_STATE.save(deps.as_mut().storage, &config).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // the current period is not claimable
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert!(res.is_err());
+
+        // period 1 is claimable in cycle 2
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), REWARDS_PER_CYCLE));
+
+        // periods 2 to 10 are claimable in cycle 11
+        env.block.time = env.block.time.plus_seconds(540);
+        assert_eq!(get_current_period(env.block.time.seconds(), START_TIMESTAMP.load(deps.as_ref().storage).unwrap(), config.clone()).unwrap(), 11);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), REWARDS_PER_CYCLE * 9));
+
+        // the nft staked in cycle 11 earns from period 11
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: "token_id_other_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+        env.block.time = env.block.time.plus_seconds(120);
+        let res = claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, "token_id_other_0".to_string(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), REWARDS_PER_CYCLE * 2));
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), REWARDS_PER_CYCLE * 2));
+    }
+
+    #[test]
+    fn test_min_cycle_length_override() {
+        let instantiate_msg = |cycle_length_in_seconds: u64, min_cycle_length_override: Option<u64>| InstantiateMsg {
+            cycle_length_in_seconds,
+            period_length_in_cycles: PERIOD_LENGTH_IN_CYCLES,
+            white_listed_nft_contract: mock_env_cw721().contract.address.to_string(),
+            rewards_token_contract: mock_env_cw20().contract.address.to_string(),
+            validate_contracts: None,
+            min_cycle_length_override,
+            owner: None,
+        };
+
+        // the cycle is shorter than the raised minimum
+        let mut deps = mock_dependencies();
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(60, Some(120)));
+        assert_eq!(ContractError::CycleLengthInvalid { min_cycle_length: 120, cycle_length_in_seconds: 60 }.to_string(), res.err().unwrap().to_string());
+
+        // the raised minimum is also applied to set_config
+        nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(120, Some(120))).unwrap();
+        let res: MinCycleLengthResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetMinCycleLength {}).unwrap()).unwrap();
+        assert_eq!(res.min_cycle_length, 120);
+
+        let config = CONFIG_STATE.load(deps.as_mut().storage).unwrap();
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: Some(100),
+            period_length_in_cycles: None,
+            white_listed_nft_contract: None,
+            rewards_token_contract: None,
+        };
+        let res = set_config(deps.as_mut(), mock_info(MINTER, &[]), mock_env(), config, set_config_msg);
+        assert_eq!(ContractError::CycleLengthInvalid { min_cycle_length: 120, cycle_length_in_seconds: 100 }.to_string(), res.err().unwrap().to_string());
+
+        // the override is floored at the absolute minimum
+        let mut deps = mock_dependencies();
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(5, Some(1)));
+        assert_eq!(ContractError::CycleLengthInvalid { min_cycle_length: 10, cycle_length_in_seconds: 5 }.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_instantiate_with_owner() {
+        let instantiate_msg = |owner: Option<String>| InstantiateMsg {
+            cycle_length_in_seconds: CYCLE_LENGTH_IN_SECONDS,
+            period_length_in_cycles: PERIOD_LENGTH_IN_CYCLES,
+            white_listed_nft_contract: mock_env_cw721().contract.address.to_string(),
+            rewards_token_contract: mock_env_cw20().contract.address.to_string(),
+            validate_contracts: None,
+            min_cycle_length_override: None,
+            owner,
+        };
+        let multisig = "multisig".to_string();
+
+        // invalid owner
+        let mut deps = mock_dependencies();
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(Some("Invalid".to_string())));
+        assert!(res.is_err());
+
+        // the deployer instantiates with the multisig as the owner
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(Some(multisig.clone()))).unwrap();
+        assert_eq!(res.attributes[1].value, multisig);
+        let config = CONFIG_STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.owner, multisig);
+
+        // admin actions require the multisig, the deployer is not the owner
+        let res = add_rewards_for_periods(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), REWARDS_PER_CYCLE, config.clone());
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+        add_rewards_for_periods(deps.as_mut(), mock_env(), mock_info(multisig.as_str(), &[]), REWARDS_PER_CYCLE, config.clone()).unwrap();
+
+        // the owner is the instantiater by default
+        let mut deps = mock_dependencies();
+        nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(None)).unwrap();
+        assert_eq!(CONFIG_STATE.load(deps.as_ref().storage).unwrap().owner, MINTER);
+    }
+
+    #[test]
+    fn test_instantiate_invalid_contracts() {
+        let instantiate_msg = |white_listed_nft_contract: &str, rewards_token_contract: &str| InstantiateMsg {
+            cycle_length_in_seconds: CYCLE_LENGTH_IN_SECONDS,
+            period_length_in_cycles: PERIOD_LENGTH_IN_CYCLES,
+            white_listed_nft_contract: white_listed_nft_contract.to_string(),
+            rewards_token_contract: rewards_token_contract.to_string(),
+            validate_contracts: None,
+            min_cycle_length_override: None,
+            owner: None,
+        };
+        let cw721_contract = mock_env_cw721().contract.address.to_string();
+        let cw20_contract = mock_env_cw20().contract.address.to_string();
+        let mut deps = mock_dependencies();
+
+        // empty addresses
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg("", &cw20_contract));
+        assert!(matches!(res.err().unwrap(), ContractError::Std(_)));
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(&cw721_contract, ""));
+        assert!(matches!(res.err().unwrap(), ContractError::Std(_)));
+
+        // malformed addresses
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg("Invalid", &cw20_contract));
+        assert!(matches!(res.err().unwrap(), ContractError::Std(_)));
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(&cw721_contract, "Invalid"));
+        assert!(matches!(res.err().unwrap(), ContractError::Std(_)));
+
+        // the rewards token contract is the nft staking contract itself
+        let res = nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(&cw721_contract, MOCK_CONTRACT_ADDR));
+        assert_eq!(res.err().unwrap().to_string(), ContractError::RewardsTokenIsStakingContract {}.to_string());
+
+        nft_staking_instantiate(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), instantiate_msg(&cw721_contract, &cw20_contract)).unwrap();
+    }
+
+    #[test]
+    fn test_accrual_survives_whitelist_change() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let new_contract = "other_cw721_contract".to_string();
+
+        // the token of the collection a is staked, then the whitelist is changed to the collection b
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(600);
+        replace_whitelisted_contract(deps.as_mut(), info.clone(), env.clone(), config.clone(), cw721_contract_address.to_string(), new_contract.clone()).unwrap();
+        let config = CONFIG_STATE.load(deps.as_ref().storage).unwrap();
+
+        let other_token_id = "token_id_other_0".to_string();
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: other_token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(new_contract.as_str(), &[]), config.clone(), msg).unwrap();
+
+        // the accrual of the collection a token does not depend on the whitelist
+        env.block.time = env.block.time.plus_seconds(1400);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 561);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        assert_eq!(TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap().staked_nft_contract(config.clone()), cw721_contract_address.to_string());
+
+        // the collection b token accrues from the cycle it is staked
+        let res = claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, other_token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), 51 * 7 + 17 * 2));
+    }
+
+    #[test]
+    fn test_replace_whitelisted_contract() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let new_contract = "other_cw721_contract".to_string();
+
+        // the old contract should be the current whitelisted contract
+        let res = replace_whitelisted_contract(deps.as_mut(), info.clone(), env.clone(), config.clone(), new_contract.clone(), new_contract.clone());
+        assert_eq!(ContractError::InvalidWhitelistedContract { white_listed_contract: cw721_contract_address.to_string(), requester: new_contract.clone() }.to_string(), res.err().unwrap().to_string());
+
+        replace_whitelisted_contract(deps.as_mut(), info.clone(), env.clone(), config.clone(), cw721_contract_address.to_string(), new_contract.clone()).unwrap();
+        let config = CONFIG_STATE.load(deps.as_mut().storage).unwrap();
+        assert_eq!(config.white_listed_nft_contract, new_contract);
+
+        // the old contract cannot stake anymore
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: "token_id_other_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        let res = stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg);
+        assert_eq!(ContractError::InvalidWhitelistedContract { white_listed_contract: new_contract.clone(), requester: cw721_contract_address.to_string() }.to_string(), res.err().unwrap().to_string());
+
+        // the staked nft is returned via the original contract
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert!(res.messages.iter().any(|m| m.msg == CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: cw721_contract_address.to_string(), 
+            msg: to_binary(&Cw721BaseExecuteMsg::<Extension, Empty>::TransferNft { recipient: staker.clone(), token_id: token_id.clone() }).unwrap(), 
+            funds: vec![],
+        })));
+
+        // the staked token without the recorded nft contract blocks replacing
+        let legacy_token_info = TokenInfo::stake(GRANTER.to_string(), true, 1, "".to_string());
+        TOKEN_INFOS.save(deps.as_mut().storage, "token_id_legacy".to_string(), &legacy_token_info).unwrap();
+        let res = replace_whitelisted_contract(deps.as_mut(), info.clone(), env.clone(), config.clone(), new_contract.clone(), cw721_contract_address.to_string());
+        assert_eq!(ContractError::WhitelistReplaceBlocked { token_id: "token_id_legacy".to_string() }.to_string(), res.err().unwrap().to_string());
+
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: None,
+            period_length_in_cycles: None,
+            white_listed_nft_contract: Some(cw721_contract_address.to_string()),
+            rewards_token_contract: None,
+        };
+        let res = set_config(deps.as_mut(), info.clone(), env.clone(), config.clone(), set_config_msg);
+        assert_eq!(ContractError::WhitelistReplaceBlocked { token_id: "token_id_legacy".to_string() }.to_string(), res.err().unwrap().to_string());
+
+        // the same whitelisted contract is allowed in set_config
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: None,
+            period_length_in_cycles: None,
+            white_listed_nft_contract: Some(new_contract.clone()),
+            rewards_token_contract: None,
+        };
+        set_config(deps.as_mut(), info.clone(), env.clone(), config.clone(), set_config_msg).unwrap();
+    }
+
+    #[test]
+    fn test_whitelisted_contracts() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, _staker, _token_id) = test_environment();
+
+        let res: Vec<String> = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::WhitelistedContracts {}).unwrap()).unwrap();
+        assert_eq!(res, vec![cw721_contract_address.to_string()]);
+
+        // only one collection is whitelisted, so replacing the contract replaces the list
+        let new_contract = "other_cw721_contract".to_string();
+        replace_whitelisted_contract(deps.as_mut(), info.clone(), env.clone(), config.clone(), cw721_contract_address.to_string(), new_contract.clone()).unwrap();
+        let res: Vec<String> = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::WhitelistedContracts {}).unwrap()).unwrap();
+        assert_eq!(res, vec![new_contract]);
+    }
+
+    #[test]
+    fn test_full_settings() {
+        // test environment
+        let (deps, _info, env, _cw721_contract, cw721_contract_address, _config, _staker, _token_id) = test_environment();
+
+        let res: FullSettingsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FullSettings {}).unwrap()).unwrap();
+        assert_eq!(res.owner, MINTER);
+        assert_eq!(res.cycle_length_in_seconds, CYCLE_LENGTH_IN_SECONDS);
+        assert_eq!(res.period_length_in_cycles, PERIOD_LENGTH_IN_CYCLES);
+        assert_eq!(res.white_listed_nft_contract, cw721_contract_address);
+        assert_eq!(res.whitelisted_contracts, vec![cw721_contract_address.to_string()]);
+        assert_eq!(res.rewards_token_contract, mock_env_cw20().contract.address);
+        assert_eq!(res.min_cycle_length, 10);
+        assert_eq!(res.rewards_per_cycle, Some(REWARDS_PER_CYCLE));
+        assert_eq!(res.max_compute_period, DEFAULT_MAX_COMPUTE_PERIOD);
+        assert_eq!(res.unbonding_duration, 1814400);
+        assert_eq!(res.min_tokens_to_earn, 0);
+        assert_eq!(res.emission_mode, EmissionMode::Flat);
+        assert_eq!(res.min_unstake_cycles, 2);
+        assert!(!res.fee_on_transfer_token);
+        assert!(!res.require_approved_recipient);
+        assert!(!res.allow_prestake);
+        assert_eq!(res.claim_cliff_period, 0);
+        assert_eq!(res.stake_forwarders, None);
+        assert_eq!(res.auto_disable_threshold, None);
+        assert!(!res.require_approved_recipient);
+        assert!(!res.allow_prestake);
+        assert!(!res.allow_public_funding);
+        assert!(!res.require_claim_before_unstake);
+        assert!(!res.auto_finalize_on_claim);
+        assert!(!res.prorate_final_period);
+        assert!(!res.fee_on_transfer_token);
+        assert_eq!(res.max_reward_per_claim_per_token, None);
+        assert_eq!(res.min_pool_balance_to_stake, None);
+        assert_eq!(res.emission_end_period, None);
+        assert_eq!(res.emission_mode, EmissionMode::Flat);
+        assert_eq!(res.reward_per_period, None);
+        assert_eq!(res.min_unstake_cycles, 2);
+        assert_eq!(res.withdraw_timelock_seconds, None);
+        assert!(!res.pool_constrained_emission);
+        assert!(!res.rewards_follow_nft);
+        assert!(!res.validate_contracts);
+        assert!(res.start);
+        assert_eq!(res.start_time, env.block.time.seconds());
+        assert!(!res.disable);
+    }
+
+    #[test]
+    fn test_version() {
+        // test environment
+        let (deps, _info, env, _cw721_contract, _cw721_contract_address, _config, _staker, _token_id) = test_environment();
+
+        let res: ContractVersion = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Version {}).unwrap()).unwrap();
+        assert_eq!(res.contract, NFT_STAKING_CONTRACT_NAME);
+        assert_eq!(res.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn test_extend_grant() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+
+        let expires = Expiration::AtHeight(env.block.height + 100);
+        grant(deps.as_mut(), info.clone(), env.clone(), config.clone(), GRANTER.to_string(), Some(expires)).unwrap();
+
+        // extend the expiration in place
+        let new_expires = Expiration::AtHeight(env.block.height + 200);
+        extend_grant(deps.as_mut(), info.clone(), env.clone(), config.clone(), GRANTER.to_string(), Some(new_expires)).unwrap();
+
+        let res: GetGrantsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetAllGrants {}).unwrap()).unwrap();
+        assert_eq!(res.grants.len(), 1);
+        assert_eq!(res.grants[0].address, GRANTER.to_string());
+        assert_eq!(res.grants[0].expires, new_expires);
+
+        // the grantee cannot extend, and not granted address cannot be extended
+        let res = extend_grant(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), config.clone(), GRANTER.to_string(), None);
+        assert_eq!(ContractError::OwnerOnly {}.to_string(), res.err().unwrap().to_string());
+        let res = extend_grant(deps.as_mut(), info.clone(), env.clone(), config.clone(), STAKER.to_string(), None);
+        assert_eq!(ContractError::InvalidGrantedAddress { address: STAKER.to_string() }.to_string(), res.err().unwrap().to_string());
+    }
+
     #[test]
     fn test_grant_and_revoke() {
         // test environment
@@ -57,7 +453,7 @@ mod tests{
         let expiration = Expiration::default();
 
         // grant
-        grant(deps.as_mut(), info.clone(), config.clone(), address.clone(), Some(expiration)).unwrap();
+        grant(deps.as_mut(), info.clone(), env.clone(), config.clone(), address.clone(), Some(expiration)).unwrap();
 
         let granter_info = mock_info(address.as_str(), &[]);
         let set_config_msg = SetConfigMsg {
@@ -70,6 +466,23 @@ mod tests{
         // check that granter can execute set_config
         set_config(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), set_config_msg.clone()).unwrap();
 
+        // granter cannot grant and revoke, which are only for contract owner
+        let result = grant(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), STAKER.to_string(), None);
+        assert_eq!(ContractError::OwnerOnly {}.to_string(), result.err().unwrap().to_string());
+        let result = revoke(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), address.clone());
+        assert_eq!(ContractError::OwnerOnly {}.to_string(), result.err().unwrap().to_string());
+
+        // not granted address is unauthorized
+        let result = grant(deps.as_mut(), mock_info(STAKER, &[]), env.clone(), config.clone(), STAKER.to_string(), None);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), result.err().unwrap().to_string());
+
+        // the contract owner cannot grant or revoke itself
+        let result = grant(deps.as_mut(), info.clone(), env.clone(), config.clone(), MINTER.to_string(), None);
+        assert_eq!(ContractError::CannotGrantSelf {}.to_string(), result.err().unwrap().to_string());
+        let result = revoke(deps.as_mut(), info.clone(), env.clone(), config.clone(), MINTER.to_string());
+        assert_eq!(ContractError::CannotGrantSelf {}.to_string(), result.err().unwrap().to_string());
+        assert!(GRANTS.may_load(deps.as_mut().storage, MINTER.to_string()).unwrap().is_none());
+
         let config = CONFIG_STATE.load(deps.as_mut().storage).unwrap();
         assert_eq!(config.cycle_length_in_seconds, 100);
         assert_eq!(config.period_length_in_cycles, PERIOD_LENGTH_IN_CYCLES);
@@ -77,7 +490,7 @@ mod tests{
         assert_eq!(config.rewards_token_contract, mock_env_cw20().contract.address);
 
         // revoke
-        revoke(deps.as_mut(), info, config.clone(), address).unwrap();
+        revoke(deps.as_mut(), info, env.clone(), config.clone(), address).unwrap();
 
         // revoked granter cannot execute set_config
         let result = set_config(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), set_config_msg.clone());        
@@ -90,7 +503,7 @@ mod tests{
         let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
 
         let rewards_per_cycle = REWARDS_PER_CYCLE;
-        add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), rewards_per_cycle.clone(), config.clone()).unwrap();
+        add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), rewards_per_cycle, config.clone()).unwrap();
 
         // normal case
         let rewards_schedule = REWARDS_SCHEDULE.load(deps.as_mut().storage).unwrap();
@@ -98,10 +511,98 @@ mod tests{
 
         // error case that rewards per cycle is zero
         let rewards_per_cycle: u128 = 0;
-        let result = add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), rewards_per_cycle.clone(), config.clone());
+        let result = add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), rewards_per_cycle, config.clone());
         assert_eq!(ContractError::InvalidRewardsSchedule {}.to_string(), result.err().unwrap().to_string())
     }
 
+    #[test]
+    fn test_raw_rewards_schedule() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+
+        // unset rewards schedule is returned as null
+        REWARDS_SCHEDULE.remove(deps.as_mut().storage);
+        let res: Option<u128> = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::RawRewardsSchedule {}).unwrap()).unwrap();
+        assert_eq!(res, None);
+
+        add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), REWARDS_PER_CYCLE, config.clone()).unwrap();
+        let res: Option<u128> = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::RawRewardsSchedule {}).unwrap()).unwrap();
+        assert_eq!(res, Some(REWARDS_PER_CYCLE));
+    }
+
+    #[test]
+    fn test_set_rewards_per_period() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+
+        // the period length is 3 cycles, rewards per period 51 is saved as rewards per cycle 17
+        let res = set_rewards_per_period(deps.as_mut(), env.clone(), info.clone(), 51, config.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, "51");
+        assert_eq!(res.attributes[2].value, REWARDS_PER_CYCLE.to_string());
+        assert_eq!(REWARDS_SCHEDULE.load(deps.as_ref().storage).unwrap(), REWARDS_PER_CYCLE);
+
+        // not divisible by the period length
+        let res = set_rewards_per_period(deps.as_mut(), env.clone(), info.clone(), 50, config.clone());
+        assert_eq!(ContractError::RewardsPerPeriodNotDivisible { rewards_per_period: 50, period_length_in_cycles: PERIOD_LENGTH_IN_CYCLES }.to_string(), res.err().unwrap().to_string());
+
+        // zero and unauthorized are rejected as same as add_rewards_for_periods
+        let res = set_rewards_per_period(deps.as_mut(), env.clone(), info.clone(), 0, config.clone());
+        assert_eq!(ContractError::InvalidRewardsSchedule {}.to_string(), res.err().unwrap().to_string());
+        let res = set_rewards_per_period(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 51, config.clone());
+        assert!(res.is_err());
+
+        // the authorization is checked before the divisibility
+        let res = set_rewards_per_period(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 50, config.clone());
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_claim_rewards_expected_config_version() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        // the rewards schedule is set before staking
+        let res: ConfigResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(res.config_version, 1);
+        let expected_config_version = res.config_version;
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 561);
+
+        // the config is changed between the estimate and the claim
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: None,
+            period_length_in_cycles: Some(4),
+            white_listed_nft_contract: None,
+            rewards_token_contract: None,
+        };
+        set_config(deps.as_mut(), info.clone(), env.clone(), config.clone(), set_config_msg).unwrap();
+        let config = CONFIG_STATE.load(deps.as_ref().storage).unwrap();
+        let res: ConfigResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(res.config_version, 2);
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, Some(expected_config_version));
+        assert_eq!(ContractError::ConfigChanged { expected: 1, current: 2 }.to_string(), res.err().unwrap().to_string());
+
+        // the rewards rate is changed between the estimate and the claim
+        set_rewards_per_period(deps.as_mut(), env.clone(), info.clone(), 40, config.clone()).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, Some(2));
+        assert_eq!(ContractError::ConfigChanged { expected: 2, current: 3 }.to_string(), res.err().unwrap().to_string());
+
+        // the current version is accepted
+        claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, Some(3)).unwrap();
+
+        // the settings which change the claimed rewards increase the config version
+        set_unbonding_duration(deps.as_mut(), info.clone(), env.clone(), config.clone(), 100).unwrap();
+        set_claim_cliff_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1).unwrap();
+        set_rewards_follow_nft(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        set_prorate_final_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        set_require_claim_before_unstake(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        let res: ConfigResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(res.config_version, 8);
+    }
+
     #[test]
     fn test_disable() {
         // set environment and do stake
@@ -114,279 +615,3790 @@ mod tests{
         let claim_recipient_address = None;
 
         // cannot run functions
-        let res = claim_rewards(deps.as_mut(), info.clone(), env.clone(), periods, token_id.clone(), config.clone(), claim_recipient_address.clone());
+        let res = claim_rewards(deps.as_mut(), info.clone(), env.clone(), periods, token_id.clone(), config.clone(), claim_recipient_address.clone(), None);
         assert_eq!(ContractError::Disabled {}.to_string(), res.err().unwrap().to_string());
 
         let staker_info = mock_info(staker.as_str(), &[]);
-        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone());
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), None);
         assert_eq!(ContractError::Disabled {}.to_string(), res.err().unwrap().to_string());
 
-        let res = withdraw_all_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone());
+        let res = withdraw_all_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone(), None);
         assert_eq!(ContractError::Disabled {}.to_string(), res.err().unwrap().to_string());
     }
 
     #[test]
-    fn test_stake() {
-        do_stake();
+    fn test_set_disabled() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+
+        // set to the same state
+        let res = set_disabled(deps.as_mut(), info.clone(), env.clone(), config.clone(), false).unwrap();
+        assert_eq!(res.attributes.get(2).unwrap().value, "false");
+        assert_eq!(res.attributes.get(3).unwrap().value, "false");
+        assert!(!DISABLE.load(deps.as_mut().storage).unwrap());
+
+        // set to the opposite state, and again
+        for _ in 0..2 {
+            set_disabled(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+            assert!(DISABLE.load(deps.as_mut().storage).unwrap());
+        }
+        let res = set_disabled(deps.as_mut(), info.clone(), env.clone(), config.clone(), false).unwrap();
+        assert_eq!(res.attributes.get(2).unwrap().value, "true");
+        assert_eq!(res.attributes.get(3).unwrap().value, "false");
+        assert!(!DISABLE.load(deps.as_mut().storage).unwrap());
+
+        let res = set_disabled(deps.as_mut(), mock_info(STAKER, &[]), env.clone(), config.clone(), true);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
     }
 
     #[test]
-    fn test_claim() {
-        // do stake
-        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+    fn test_grant_suspended_while_disabled() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        grant(deps.as_mut(), info.clone(), env.clone(), config.clone(), GRANTER.to_string(), None).unwrap();
+        let granter_info = mock_info(GRANTER, &[]);
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: Some(100),
+            period_length_in_cycles: None,
+            white_listed_nft_contract: None,
+            rewards_token_contract: None,
+        };
 
-        // time passed by 5000 seconds
-        let timestamp = env.block.time.seconds() + 5000;
-        let staker_info = mock_info(staker.as_str(), &[]);
-        let request_claim_period = 5;
-        let claim_recipient_address = None;
-        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        // the granted address is able to disable the contract
+        disable(deps.as_mut(), granter_info.clone(), env.clone(), config.clone()).unwrap();
+
+        // but not able to change the config, enable the contract or any other admin function while disabled
+        let res = set_config(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), set_config_msg.clone());
+        assert_eq!(ContractError::GrantSuspended {}.to_string(), res.err().unwrap().to_string());
+        let res = set_min_tokens_to_earn(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), 2);
+        assert_eq!(ContractError::GrantSuspended {}.to_string(), res.err().unwrap().to_string());
+        let res = enable(deps.as_mut(), granter_info.clone(), env.clone(), config.clone());
+        assert_eq!(ContractError::GrantSuspended {}.to_string(), res.err().unwrap().to_string());
+        let res = set_disabled(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), false);
+        assert_eq!(ContractError::GrantSuspended {}.to_string(), res.err().unwrap().to_string());
+
+        // the contract owner recovers the config and enables the contract
+        set_config(deps.as_mut(), info.clone(), env.clone(), config.clone(), set_config_msg.clone()).unwrap();
+        enable(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+
+        // the grant is available again after enabled
+        set_min_tokens_to_earn(deps.as_mut(), granter_info.clone(), env.clone(), config.clone(), 2).unwrap();
+    }
 
-        // claim
-        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp.clone());
+    #[test]
+    fn test_enable_validate_contracts() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        disable(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+        VALIDATE_CONTRACTS.save(deps.as_mut().storage, &true).unwrap();
+
+        // the rewards token contract does not respond to the token info query.
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let res = enable(deps.as_mut(), info.clone(), env.clone(), config.clone());
+        match res.err().unwrap() {
+            ContractError::RewardsTokenContractUnavailable { rewards_token_contract, reason: _ } => {
+                assert_eq!(rewards_token_contract, config.rewards_token_contract)
+            },
+            e => panic!("unexpected error {}", e),
+        }
+        assert!(DISABLE.load(deps.as_mut().storage).unwrap());
+
+        // both contracts respond.
+        let cw20_contract_address = config.rewards_token_contract.clone();
+        let cw721_contract_address = config.white_listed_nft_contract.clone();
+        deps.querier.update_wasm(move |query| {
+            match query {
+                WasmQuery::Smart { contract_addr, msg: _ } if *contract_addr == cw20_contract_address => {
+                    let res = TokenInfoResponse { name: "rewards".to_string(), symbol: "RWD".to_string(), decimals: 6, total_supply: Uint128::zero() };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                },
+                WasmQuery::Smart { contract_addr, msg: _ } if *contract_addr == cw721_contract_address => {
+                    let res = Cw721ContractInfoResponse { name: CONTRACT_NAME.to_string(), symbol: SYMBOL.to_string() };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                },
+                _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+            }
+        });
+        enable(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+        assert!(!DISABLE.load(deps.as_mut().storage).unwrap());
+    }
 
-        // --------------------------------
-        // check after run claim function
-        let staker_rewards = query_balance(deps.as_ref(), staker.clone()).unwrap();
-        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
-        let next_claim = NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
-        
-        // deposit cycle = 1.
-        // cycle length in seconds is 60 and period length in cycles is 3 for test.
-        // rewards per cycle is 17.
-        // rewards are sufficient because of a lot of time passed after staked.
-        // request claim period is 5.
+    #[test]
+    fn test_withdraw_rewards_pool_to_recipient() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
 
-        // the equation of claimable rewards value = 5 * 3 * 17 = 255
-        // and next claim is 6 because rewards are claimed until period 5.
-        assert_eq!(255, staker_rewards.balance.u128());
-        assert_eq!(1999999745, contract_balance.balance.u128());
-        assert_eq!(6, next_claim.period);
-        assert_eq!(res.as_ref().unwrap().attributes.get(2).unwrap().value, staker);
-        assert_eq!(res.as_ref().unwrap().attributes.get(3).unwrap().value, 255.to_string());
+        let treasury = "treasury".to_string();
+
+        // withdraw to the treasury instead of the owner
+        let res = withdraw_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, Some(treasury.clone())).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(treasury.clone(), 1000));
+
+        let res = withdraw_all_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(treasury.clone())).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(treasury.clone(), ADD_REWARDS_POOL));
+
+        // the owner is the recipient when it is not set
+        let res = withdraw_all_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone(), None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(MINTER.to_string(), ADD_REWARDS_POOL));
+
+        // invalid recipient
+        let res = withdraw_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, Some("Invalid".to_string()));
+        assert!(res.is_err());
+
+        // only the owner can withdraw
+        let res = withdraw_all_rewards_pool(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone(), Some(treasury.clone()));
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_withdraw_timelock() {
+        // test environment
+        let (mut deps, info, mut env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        let treasury = "treasury".to_string();
+
+        // the withdrawal is not able to be requested without the timelock
+        let res = request_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, None);
+        assert_eq!(ContractError::WithdrawTimelockNotSet {}.to_string(), res.err().unwrap().to_string());
+
+        set_withdraw_timelock_seconds(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(86400)).unwrap();
+
+        // direct withdrawals are rejected
+        let res = withdraw_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, None);
+        assert_eq!(ContractError::WithdrawTimelockEnabled {}.to_string(), res.err().unwrap().to_string());
+        let res = withdraw_all_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone(), None);
+        assert_eq!(ContractError::WithdrawTimelockEnabled {}.to_string(), res.err().unwrap().to_string());
+
+        // the timelock cannot be decreased or removed
+        let res = set_withdraw_timelock_seconds(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(60));
+        assert_eq!(ContractError::WithdrawTimelockDecreased { current: 86400 }.to_string(), res.err().unwrap().to_string());
+        let res = set_withdraw_timelock_seconds(deps.as_mut(), info.clone(), env.clone(), config.clone(), None);
+        assert_eq!(ContractError::WithdrawTimelockDecreased { current: 86400 }.to_string(), res.err().unwrap().to_string());
+
+        // only the owner can request
+        let res = request_withdraw(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone(), 1000, None);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+
+        // the amount is more than the rewards pool
+        let res = request_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone(), ADD_REWARDS_POOL + 1, None);
+        assert!(res.is_err());
+
+        let requested_at = env.block.time.seconds();
+        request_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, Some(treasury.clone())).unwrap();
+
+        let res: PendingWithdrawalResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingWithdrawal {}).unwrap()).unwrap();
+        assert_eq!(res.withdraw_timelock_seconds, Some(86400));
+        assert_eq!(res.pending_withdrawal, Some(PendingWithdrawal {
+            amount: 1000,
+            recipient: treasury.clone(),
+            requested_at,
+            ready_at: requested_at + 86400,
+        }));
+
+        // only one withdrawal is pending
+        let res = request_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, None);
+        assert_eq!(ContractError::WithdrawalAlreadyPending { ready_at: requested_at + 86400 }.to_string(), res.err().unwrap().to_string());
+
+        // early execute is rejected
+        env.block.time = env.block.time.plus_seconds(86399);
+        let res = execute_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone());
+        assert_eq!(ContractError::WithdrawTimelockNotEnded { ready_at: requested_at + 86400 }.to_string(), res.err().unwrap().to_string());
+
+        env.block.time = env.block.time.plus_seconds(1);
+        let res = execute_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(treasury.clone(), 1000));
+
+        // the executed withdrawal is removed
+        let res = execute_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone());
+        assert_eq!(ContractError::NoPendingWithdrawal {}.to_string(), res.err().unwrap().to_string());
+
+        // the owner cancels the pending withdrawal
+        request_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, None).unwrap();
+        let res = cancel_withdraw(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone());
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+        cancel_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+
+        let res: PendingWithdrawalResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingWithdrawal {}).unwrap()).unwrap();
+        assert_eq!(res.pending_withdrawal, None);
+
+        env.block.time = env.block.time.plus_seconds(86400);
+        let res = execute_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone());
+        assert_eq!(ContractError::NoPendingWithdrawal {}.to_string(), res.err().unwrap().to_string());
+
+        // the timelock is able to be increased
+        set_withdraw_timelock_seconds(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(172800)).unwrap();
+    }
+
+    #[test]
+    fn test_pending_withdrawals() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // empty when none is pending
+        let res: PendingWithdrawalsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingWithdrawals {}).unwrap()).unwrap();
+        assert!(res.pending_withdrawals.is_empty());
+
+        set_withdraw_timelock_seconds(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(3600)).unwrap();
+        request_withdraw(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000, None).unwrap();
+
+        let res: PendingWithdrawalsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingWithdrawals {}).unwrap()).unwrap();
+        assert_eq!(res.pending_withdrawals.len(), 1);
+        assert_eq!(res.pending_withdrawals[0].amount, 1000);
+        assert_eq!(res.pending_withdrawals[0].recipient, MINTER.to_string());
+        assert_eq!(res.pending_withdrawals[0].ready_at, env.block.time.seconds() + 3600);
+    }
+
+    #[test]
+    fn test_sync_rewards_pool() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // desync the total rewards pool from the balance
+        TOTAL_REWARDS_POOL.save(deps.as_mut().storage, &100).unwrap();
+
+        // the contract must be disabled
+        let res = sync_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone());
+        assert_eq!(ContractError::NotDisabled {}.to_string(), res.err().unwrap().to_string());
+        disable(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+
+        // only the owner can sync
+        let res = sync_rewards_pool(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone());
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+
+        let res = sync_rewards_pool(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, "100");
+        assert_eq!(res.attributes[2].value, ADD_REWARDS_POOL.to_string());
+        assert_eq!(TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap(), ADD_REWARDS_POOL);
+    }
+
+    #[test]
+    fn test_allow_public_funding() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        let cw20_info = mock_info(mock_env_cw20().contract.address.as_str(), &[]);
+        let msg = Cw20ReceiveMsg {
+            sender: "anyone".to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary("add rewards pool").unwrap(),
+        };
+
+        // the non owner cannot fund by default
+        let res = add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), msg.clone());
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+
+        // only the owner can allow the public funding
+        let res = set_allow_public_funding(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone(), true);
+        assert!(res.is_err());
+        set_allow_public_funding(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL + 1000, vec![]);
+        let res = add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), msg.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, "1000");
+        assert_eq!(res.attributes[4].value, "anyone");
+        assert_eq!(TOTAL_REWARDS_POOL.load(deps.as_mut().storage).unwrap(), ADD_REWARDS_POOL + 1000);
+
+        // the rewards token contract is still required
+        let res = add_rewards_pool(deps.as_mut(), mock_info("other_cw20", &[]), env.clone(), config.clone(), msg.clone());
+        assert!(res.is_err());
+
+        let res: FullSettingsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FullSettings {}).unwrap()).unwrap();
+        assert!(res.allow_public_funding);
+
+        // the owner check is restored
+        set_allow_public_funding(deps.as_mut(), info.clone(), env.clone(), config.clone(), false).unwrap();
+        let res = add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), msg);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_add_rewards_pool_not_received() {
+        // test environment
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        let cw20_info = mock_info(mock_env_cw20().contract.address.as_str(), &[]);
+        let msg = Cw20ReceiveMsg {
+            sender: MINTER.to_string(),
+            amount: Uint128::from(ADD_REWARDS_POOL + 1000),
+            msg: to_binary("add rewards pool").unwrap(),
+        };
+
+        // the rewards token reports more than it transfers
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let res = add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), msg.clone());
+        assert_eq!(ContractError::RewardsDepositNotReceived { 
+            reported_amount: ADD_REWARDS_POOL + 1000, 
+            total_rewards_pool: ADD_REWARDS_POOL,
+            balance: ADD_REWARDS_POOL,
+        }.to_string(), res.err().unwrap().to_string());
+        assert_eq!(TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap(), ADD_REWARDS_POOL);
+
+        // the pool is already funded and the fake deposit is smaller than the pool
+        let fake_msg = Cw20ReceiveMsg {
+            sender: MINTER.to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary("add rewards pool").unwrap(),
+        };
+        let res = add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), fake_msg);
+        assert_eq!(ContractError::RewardsDepositNotReceived { 
+            reported_amount: 1000, 
+            total_rewards_pool: ADD_REWARDS_POOL,
+            balance: ADD_REWARDS_POOL,
+        }.to_string(), res.err().unwrap().to_string());
+        assert_eq!(TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap(), ADD_REWARDS_POOL);
+
+        // the balance includes the reported amount
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL * 2 + 1000, vec![]);
+        add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), msg).unwrap();
+        assert_eq!(TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap(), ADD_REWARDS_POOL * 2 + 1000);
+    }
+
+    #[test]
+    fn test_fee_on_transfer_token() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        set_fee_on_transfer_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+
+        // receive hook is rejected because received amount cannot be verified
+        let cw20_info = mock_info(mock_env_cw20().contract.address.as_str(), &[]);
+        let msg = Cw20ReceiveMsg {
+            sender: MINTER.to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary("add rewards pool").unwrap(),
+        };
+        let res = add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), msg);
+        assert_eq!(ContractError::FeeOnTransferDepositRequired {}.to_string(), res.err().unwrap().to_string());
+
+        // pull rewards from the owner
+        let res = add_rewards_pool_from(deps.as_mut(), info.clone(), env.clone(), config.clone(), 1000).unwrap();
+        assert_eq!(res.messages[0].id, ADD_REWARDS_POOL_REPLY_ID);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: mock_env_cw20().contract.address.to_string(), 
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom { owner: MINTER.to_string(), recipient: env.contract.address.to_string(), amount: Uint128::from(1000u128) }).unwrap(), 
+            funds: vec![],
+        }));
+
+        // the rewards token takes 3% transfer fee, only the received amount is added
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL + 970, vec![]);
+        let reply_msg = Reply { id: ADD_REWARDS_POOL_REPLY_ID, result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }) };
+        let res = reply(deps.as_mut(), env.clone(), reply_msg.clone()).unwrap();
+        assert_eq!(res.attributes[2].value, "970");
+        assert_eq!(res.attributes[3].value, "30");
+        assert_eq!(TOTAL_REWARDS_POOL.load(deps.as_mut().storage).unwrap(), ADD_REWARDS_POOL + 970);
+
+        // the pending deposit is consumed
+        let res = reply(deps.as_mut(), env.clone(), reply_msg);
+        assert_eq!(ContractError::NoPendingRewardsDeposit {}.to_string(), res.err().unwrap().to_string());
+
+        let res: FullSettingsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FullSettings {}).unwrap()).unwrap();
+        assert!(res.fee_on_transfer_token);
+    }
+
+    #[test]
+    fn test_stake() {
+        do_stake();
+    }
+
+    #[test]
+    fn test_stake_for() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+
+        // direct stake, the sender is the owner
+        let msg = Cw721ReceiveMsg {
+            sender: staker.clone(),
+            token_id: token_id.clone(),
+            msg: to_binary(&StakeNftMsg { stake_for: None }).unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap().owner, staker);
+
+        // stake on behalf of the user, the user is the owner
+        let vault = "vault".to_string();
+        let user = "user".to_string();
+        let msg = Cw721ReceiveMsg {
+            sender: vault.clone(),
+            token_id: "token_id_test_1".to_string(),
+            msg: to_binary(&StakeNftMsg { stake_for: Some(user.clone()) }).unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, "token_id_test_1".to_string()).unwrap().owner, user);
+        assert!(NEXT_CLAIMS.has(deps.as_mut().storage, staker_tokenid_key(user.clone(), "token_id_test_1".to_string())));
+        assert!(!NEXT_CLAIMS.has(deps.as_mut().storage, staker_tokenid_key(vault.clone(), "token_id_test_1".to_string())));
+        assert_eq!(STAKER_NFT_COUNTS.load(deps.as_mut().storage, user.clone()).unwrap(), 1);
+        assert!(STAKER_NFT_COUNTS.may_load(deps.as_mut().storage, vault.clone()).unwrap().is_none());
+
+        // only forwarders can stake on behalf of other address
+        let market = "market".to_string();
+        set_stake_forwarders(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(vec![market.clone()])).unwrap();
+
+        let msg = Cw721ReceiveMsg {
+            sender: vault.clone(),
+            token_id: "token_id_test_2".to_string(),
+            msg: to_binary(&StakeNftMsg { stake_for: Some(user.clone()) }).unwrap(),
+        };
+        let res = stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg);
+        assert_eq!(ContractError::UnauthorizedForwarder { forwarder: vault.clone() }.to_string(), res.err().unwrap().to_string());
+
+        let msg = Cw721ReceiveMsg {
+            sender: market.clone(),
+            token_id: "token_id_test_2".to_string(),
+            msg: to_binary(&StakeNftMsg { stake_for: Some(user.clone()) }).unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, "token_id_test_2".to_string()).unwrap().owner, user);
+        assert_eq!(STAKER_NFT_COUNTS.load(deps.as_mut().storage, user.clone()).unwrap(), 2);
+
+        // not forwarder can still stake for itself
+        let msg = Cw721ReceiveMsg {
+            sender: vault.clone(),
+            token_id: "token_id_test_3".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, "token_id_test_3".to_string()).unwrap().owner, vault);
+
+        let res: StakeForwardersResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetStakeForwarders {}).unwrap()).unwrap();
+        assert_eq!(res.stake_forwarders, Some(vec![market]));
+    }
+
+    #[test]
+    fn test_staked_count_by_collection() {
+        // do stake, the token is staked from the whitelisted nft contract
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, _staker, _token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // whitelisted nft contract is changed to other collection
+        let other_collection = "cosmos2contract_other_cw721".to_string();
+        let set_config_msg = SetConfigMsg {
+            cycle_length_in_seconds: None,
+            period_length_in_cycles: None,
+            white_listed_nft_contract: Some(other_collection.clone()),
+            rewards_token_contract: None,
+        };
+        set_config(deps.as_mut(), info.clone(), env.clone(), config.clone(), set_config_msg).unwrap();
+        let config = CONFIG_STATE.load(deps.as_mut().storage).unwrap();
+
+        let other_token_id = "token_id_other_0".to_string();
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: other_token_id.clone(),
+            msg: to_binary(&StakeNftMsg { stake_for: None }).unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(other_collection.as_str(), &[]), config.clone(), msg).unwrap();
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, other_token_id.clone()).unwrap().nft_contract, other_collection);
+
+        let res: StakedCountByCollectionResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedCountByCollection {}).unwrap()).unwrap();
+        assert_eq!(res.staked_counts, vec![(cw721_contract_address.to_string(), 1), (other_collection.clone(), 1)]);
+
+        // unstake the token, the nft is returned by its own collection
+        let granter_info = mock_info(GRANTER, &[]);
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), granter_info.clone(), config.clone(), other_token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), granter_info.clone(), config.clone(), other_token_id.clone(), None, None).unwrap();
+        assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: other_collection.clone(), 
+            msg: to_binary(&Cw721BaseExecuteMsg::<Extension, Empty>::TransferNft { recipient: GRANTER.to_string(), token_id: other_token_id.clone() }).unwrap(), 
+            funds: vec![],
+        }));
+
+        let res: StakedCountByCollectionResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedCountByCollection {}).unwrap()).unwrap();
+        assert_eq!(res.staked_counts, vec![(cw721_contract_address.to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_staker_collection_summary() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, _token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // stake more nfts of the staker and the granter
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        for (sender, token_id) in [(staker.clone(), "token_id_other_0"), (GRANTER.to_string(), "token_id_other_1")] {
+            let msg = Cw721ReceiveMsg {
+                sender,
+                token_id: token_id.to_string(),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        let query_msg = QueryMsg::StakerCollectionSummary { staker: staker.clone(), nft_contract: cw721_contract_address.to_string(), start_after: None, limit: None };
+        let res: StakerCollectionSummaryResponse = from_binary(&query(deps.as_ref(), env.clone(), query_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.staked_count, 2);
+        assert_eq!(res.total_estimated_rewards, 561 * 2);
+        assert!(!res.truncated);
+        assert_eq!(res.res_msg, SUCCESS);
+
+        // the max compute period is exhausted in the second nft
+        MAX_COMPUTE_PERIOD.save(deps.as_mut().storage, &15).unwrap();
+        let res: StakerCollectionSummaryResponse = from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.staked_count, 2);
+        assert_eq!(res.total_estimated_rewards, 561 + 51 * 4);
+        assert!(res.truncated);
+        MAX_COMPUTE_PERIOD.save(deps.as_mut().storage, &DEFAULT_MAX_COMPUTE_PERIOD).unwrap();
+
+        // the token ids of the staker are summarized by pages
+        let query_msg = QueryMsg::StakerCollectionSummary { staker: staker.clone(), nft_contract: cw721_contract_address.to_string(), start_after: None, limit: Some(1) };
+        let res: StakerCollectionSummaryResponse = from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.staked_count, 1);
+        assert_eq!(res.total_estimated_rewards, 561);
+        assert_eq!(res.next_start_after, Some("token_id_other_0".to_string()));
+
+        let query_msg = QueryMsg::StakerCollectionSummary { staker: staker.clone(), nft_contract: cw721_contract_address.to_string(), start_after: res.next_start_after, limit: Some(1) };
+        let res: StakerCollectionSummaryResponse = from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.staked_count, 1);
+        assert_eq!(res.total_estimated_rewards, 561);
+        assert_eq!(res.next_start_after, None);
+
+        // the staker has no nft of other collection
+        let query_msg = QueryMsg::StakerCollectionSummary { staker: staker.clone(), nft_contract: "cosmos2contract_other_cw721".to_string(), start_after: None, limit: None };
+        let res: StakerCollectionSummaryResponse = from_binary(&query(deps.as_ref(), env.clone(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.staked_count, 0);
+        assert_eq!(res.total_estimated_rewards, 0);
+    }
+
+    #[test]
+    fn test_prestake() {
+        // test environment, the nft staking contract is not started yet
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        START_TIMESTAMP.remove(deps.as_mut().storage);
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+
+        let msg = Cw721ReceiveMsg {
+            sender: staker.clone(),
+            token_id: token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+
+        // prestake is not allowed
+        let res = stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg.clone());
+        assert_eq!(ContractError::NotStarted {}.to_string(), res.err().unwrap().to_string());
+
+        // prestake, the deposit is deferred to the first cycle
+        set_allow_prestake(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg.clone()).unwrap();
+
+        let token_info = TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap();
+        assert_eq!(token_info.deposit_cycle, 1);
+        assert_eq!(token_info.bond_status, BONDED);
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        assert_eq!(NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap(), NextClaim::new(1, 0));
+
+        // start after 5000 seconds, accrual begins at start
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(5000);
+        start(deps.as_mut(), info.clone(), env.clone(), config.clone(), None).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(2000);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+    }
+
+    #[test]
+    fn test_claim_nothing_claimable_yet() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // right after staking and in the frozen unstake window, no period is completed
+        let mut env = env.clone();
+        for _ in 0..2 {
+            let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 10, token_id.clone(), config.clone(), None, None);
+            assert_eq!(ContractError::NothingClaimableYet {}.to_string(), res.err().unwrap().to_string());
+            env.block.time = env.block.time.plus_seconds(CYCLE_LENGTH_IN_SECONDS);
+        }
+
+        // the first period is completed
+        env.block.time = env.block.time.plus_seconds(CYCLE_LENGTH_IN_SECONDS);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 10, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51));
+    }
+
+    #[test]
+    fn test_claim() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+
+        // time passed by 5000 seconds
+        let timestamp = env.block.time.seconds() + 5000;
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let request_claim_period = 5;
+        let claim_recipient_address = None;
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+        // claim
+        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp);
+
+        // --------------------------------
+        // check after run claim function
+        let staker_rewards = query_balance(deps.as_ref(), staker.clone()).unwrap();
+        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
+        let next_claim = NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
+        
+        // deposit cycle = 1.
+        // cycle length in seconds is 60 and period length in cycles is 3 for test.
+        // rewards per cycle is 17.
+        // rewards are sufficient because of a lot of time passed after staked.
+        // request claim period is 5.
+
+        // the equation of claimable rewards value = 5 * 3 * 17 = 255
+        // and next claim is 6 because rewards are claimed until period 5.
+        assert_eq!(255, staker_rewards.balance.u128());
+        assert_eq!(1999999745, contract_balance.balance.u128());
+        assert_eq!(6, next_claim.period);
+        assert_eq!(res.as_ref().unwrap().attributes.get(2).unwrap().value, staker);
+        assert_eq!(res.as_ref().unwrap().attributes.get(3).unwrap().value, 255.to_string());
+    }
+
+    #[test]
+    fn test_claim_exceeding_max_compute_period() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+
+        let timestamp = env.block.time.seconds() + 5000;
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // exceed max compute period that default value is 2500
+        let request_claim_period = 3000;
+        let claim_recipient_address = None;
+
+        // claim error
+        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp);
+        assert_eq!(ContractError::InvalidMaxPeriod {
+            periods: request_claim_period,
+            max_compute_period: DEFAULT_MAX_COMPUTE_PERIOD,
+        }.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_max_claimable_periods() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let max_claimable_periods = |deps: Deps, env: Env, token_id: String| -> u64 {
+            let res: MaxClaimablePeriodsResponse = from_binary(&query(deps, env, QueryMsg::MaxClaimablePeriods { staker: STAKER.to_string(), token_id }).unwrap()).unwrap();
+            res.max_claimable_periods
+        };
+
+        // nothing is claimable in the first period
+        assert_eq!(max_claimable_periods(deps.as_ref(), env.clone(), token_id.clone()), 0);
+        assert_eq!(max_claimable_periods(deps.as_ref(), env.clone(), "unknown".to_string()), 0);
+
+        // 11 periods are claimable
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        assert_eq!(max_claimable_periods(deps.as_ref(), env.clone(), token_id.clone()), 11);
+
+        // the gap exceeds the max compute period
+        MAX_COMPUTE_PERIOD.save(deps.as_mut().storage, &5).unwrap();
+        let periods = max_claimable_periods(deps.as_ref(), env.clone(), token_id.clone());
+        assert_eq!(periods, 5);
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), periods, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+        assert_eq!(max_claimable_periods(deps.as_ref(), env.clone(), token_id.clone()), 5);
+    }
+
+    #[test]
+    fn test_claim_other_recipient_address() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+
+        let timestamp = env.block.time.seconds() + 5000;
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let request_claim_period = 5;
+
+        // set the recipient address is granter address
+        let claim_recipient_address = Some(GRANTER.to_string());
+
+        // claim
+        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp);
+
+        // --------------------------------
+        // check after run claim function
+        let staker_rewards = query_balance(deps.as_ref(), staker.clone()).unwrap();
+        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
+        let granter_rewards = query_balance(deps.as_ref(), GRANTER.to_string()).unwrap();
+
+        // the granter receives claim rewards
+        assert_eq!(255, granter_rewards.balance.u128());
+        assert_eq!(1999999745, contract_balance.balance.u128());
+        assert_eq!(0, staker_rewards.balance.u128());
+        assert_eq!(res.as_ref().unwrap().attributes.get(2).unwrap().value, GRANTER.to_string());
+        assert_eq!(res.as_ref().unwrap().attributes.get(3).unwrap().value, 255.to_string());
+    }
+
+
+    #[test]
+    fn test_claim_while_unbonding_duration() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
+
+        let staker_info = mock_info(STAKER, &[]);
+        let timestamp = env.block.time.seconds() + 2000;
+        let claim_recipient_address = None;
+        let request_claim_period = 5;
+
+        // request unbond nft. the nft is unbonding
+        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp).unwrap();
+        let token_info = TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone());
+        assert_eq!(token_info.unwrap().bond_status, UNBONDING);
+
+        // claim error
+        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp);
+        assert_eq!(ContractError::TokenIdIsUnbonding {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_claim_empty_rewards_pool() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
+
+        let staker_info = mock_info(STAKER, &[]);
+        let timestamp = env.block.time.seconds() + 2000;
+        let claim_recipient_address = None;
+        let request_claim_period = 5;
+
+        // withdraw all rewards pool
+        test_execute_token_contract_transfer(deps.as_mut(), env.clone(), info, MINTER.to_string(), ADD_REWARDS_POOL);
+
+        // claim error
+        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp);
+        assert_eq!(ContractError::InsufficientRewardsPool {
+            rewards_pool_balance: test_query_rewards_token_balance(deps.as_mut(), env.clone().contract.address.to_string()).balance.u128(),
+            claim_amount: 255, 
+        }.to_string(), res.err().unwrap().to_string());
+
+    }
+
+    #[test]
+    fn test_unstake() {
+        // do stake
+        let (mut deps, _info, env, cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        
+        let staker_info = mock_info(STAKER, &[]);
+        let timestamp = env.block.time.seconds() + 2000;
+
+        // not claim to other address
+        let claim_recipient_address = None;
+
+        // request unbond nft
+        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp).unwrap();
+
+        // requested unbonding period value
+        let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
+        let requested_unbonding_period = get_current_period(timestamp, start_timestamp, config.clone()).unwrap();
+
+        let unbonding_duration = UNBONDING_DURATION.load(deps.as_mut().storage).unwrap();
+        assert_eq!(unbonding_duration, 1814400);
+
+        // current time is after sum of timestamp and unbonding duration + 1 
+        let timestamp = timestamp + unbonding_duration + 1;
+
+        // re-request unstake the nft has "UNBONDED" as bond_status
+        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp).unwrap();
+
+        // for test, seperate executing transfer nft and unstake function.
+        test_execute_transfer_nft_unstake(deps.as_mut(), env.clone(), staker.clone(), token_id, cw721_contract);
+
+        // --------------------------------
+        // check after run unstake function
+        let staker_rewards = query_balance(deps.as_ref(), staker).unwrap();
+        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
+
+        // deposit cycle = 1.
+        // cycle length in seconds is 60 and period length in cycles is 3 for test.
+        // requested unbonding time is now + 2000 seconds.
+        // rewards per cycle is 17.
+
+        // requested unbonding period that requested unbonding time is included is 12 (last cycle second is 2,160) and previous period that is claimable period is 11(last cycle second is 1,980).
+        // claimable rewards is limited at requested unbonding time, although current time is passed by requested unbonding time + unbonding duration(1814400) + 1.
+        // so, the equation of claimable rewards value = 1980 / 60 * 17 = 561
+        // nft staking contract's rewards pool (i.e. cw20 token balance of contract) is 2000000000 - 561 = 1999999439
+        
+        assert_eq!(12, requested_unbonding_period);
+        assert_eq!(561, staker_rewards.balance.u128());
+        assert_eq!(1999999439, contract_balance.balance.u128());
+    }
+
+    #[test]
+    fn test_unstake_rewards_capped_at_req_unbond_time() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let key = staker_tokenid_key(staker.clone(), token_id.clone());
+        let next_claim = NEXT_CLAIMS.load(deps.as_ref().storage, key.clone()).unwrap();
+
+        // request unbonding at +2000, the next claim is not touched
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(NEXT_CLAIMS.load(deps.as_ref().storage, key.clone()).unwrap(), next_claim);
+        let req_unbond_time = TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap().req_unbond_time;
+        assert_eq!(req_unbond_time, env.block.time.seconds());
+
+        // finalize long after the unbonding duration, the rewards are capped at the requested unbond time (11 periods)
+        env.block.time = env.block.time.plus_seconds(3 * 1814400);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.periods, 11);
+        assert_eq!(res.claim.amount, 561);
+
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        let claim_remain_rewards = res.attributes.iter().find(|attr| attr.key == "claim_remain_rewards").unwrap().value.clone();
+        assert_eq!(claim_remain_rewards, "561");
+        assert!(NEXT_CLAIMS.may_load(deps.as_ref().storage, key).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_require_claim_before_unstake() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let res = set_require_claim_before_unstake(deps.as_mut(), staker_info.clone(), env.clone(), config.clone(), true);
+        assert!(res.is_err());
+        set_require_claim_before_unstake(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+
+        let start_time = env.block.time;
+        let mut env = env.clone();
+        env.block.time = start_time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+
+        // unclaimed rewards remain, unstake refuses to pay them
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None);
+        assert_eq!(res.err().unwrap().to_string(), ContractError::ClaimBeforeUnstake {}.to_string());
+
+        // unstake with deferred claim, then claim the rewards
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+
+        // claim to zero before requesting unbonding, then unstake does not pay rewards
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        set_require_claim_before_unstake(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        let mut env = env.clone();
+        env.block.time = start_time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let claim_remain_rewards = res.attributes.iter().find(|attr| attr.key == "claim_remain_rewards").unwrap().value.clone();
+        assert_eq!(claim_remain_rewards, "0");
+    }
+
+    #[test]
+    fn test_prorate_final_period() {
+        // the unbond is requested at cycle 36, the completed cycles 34 and 35 of period 12 are prorated
+        let unstake_mid_period = |prorate_final_period: bool| -> u128 {
+            let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+            mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+            UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+            let staker_info = mock_info(staker.as_str(), &[]);
+            set_prorate_final_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), prorate_final_period).unwrap();
+
+            let mut env = env.clone();
+            env.block.time = env.block.time.plus_seconds(2100);
+            unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+            env.block.time = env.block.time.plus_seconds(100);
+            let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+            res.attributes.iter().find(|attr| attr.key == "claim_remain_rewards").unwrap().value.parse().unwrap()
+        };
+
+        assert_eq!(unstake_mid_period(false), 561);
+        assert_eq!(unstake_mid_period(true), 561 + REWARDS_PER_CYCLE * 2);
+    }
+
+    #[test]
+    fn test_prorate_final_period_deferred_claim() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        set_prorate_final_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+
+        // the deferred claim includes the prorated final period and clears the next claim
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res: RewardForPeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::RewardForPeriod { staker: staker.clone(), token_id: token_id.clone(), period: 12 }).unwrap()).unwrap();
+        assert_eq!(res.amount, REWARDS_PER_CYCLE * 2);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561 + REWARDS_PER_CYCLE * 2));
+        assert!(NEXT_CLAIMS.may_load(deps.as_ref().storage, staker_tokenid_key(staker.clone(), token_id.clone())).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_auto_finalize_on_claim() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let res = set_auto_finalize_on_claim(deps.as_mut(), staker_info.clone(), env.clone(), config.clone(), true);
+        assert!(res.is_err());
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+
+        // the unbonding has elapsed, but the claim is rejected without the flag
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::TokenIdIsUnbonding {}.to_string(), res.err().unwrap().to_string());
+
+        // the claim finalizes the unstake, the rewards are paid and the nft is returned
+        set_auto_finalize_on_claim(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.attributes[0].value, "unstake_nft");
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        let phase = res.attributes.iter().find(|attr| attr.key == "phase").unwrap().value.clone();
+        assert_eq!(phase, "finalized");
+        assert!(NEXT_CLAIMS.may_load(deps.as_mut().storage, staker_tokenid_key(staker.clone(), token_id.clone())).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_auto_finalize_on_claim_before_unbonding_end() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        set_auto_finalize_on_claim(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+
+        // the unbonding duration has not elapsed yet
+        env.block.time = env.block.time.plus_seconds(30);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::TokenIdIsUnbonding {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_unstake_pays_rewards_without_claim_requirement() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+    }
+
+    #[test]
+    fn test_unstake_frozen_cycle_boundary() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &0).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let start_time = env.block.time;
+
+        // staked at cycle 1, the difference of exactly 1 is frozen
+        let mut env = env.clone();
+        env.block.time = start_time.plus_seconds(10);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = start_time.plus_seconds(CYCLE_LENGTH_IN_SECONDS);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None);
+        assert_eq!(ContractError::TokenSteelFrozen {}.to_string(), res.err().unwrap().to_string());
+
+        // the difference of exactly 2
+        env.block.time = start_time.plus_seconds(2 * CYCLE_LENGTH_IN_SECONDS);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+
+        // staked at the last second of cycle 1, a full cycle length later is still cycle 2 and frozen
+        let other_token_id = "token_id_other_0".to_string();
+        env.block.time = start_time.plus_seconds(CYCLE_LENGTH_IN_SECONDS - 1);
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: other_token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id.clone(), None, None).unwrap();
+        env.block.time = start_time.plus_seconds(2 * CYCLE_LENGTH_IN_SECONDS - 1);
+        let res = unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id.clone(), None, None);
+        assert_eq!(ContractError::TokenSteelFrozen {}.to_string(), res.err().unwrap().to_string());
+
+        // the min unstake cycles is raised to 3
+        set_min_unstake_cycles(deps.as_mut(), info.clone(), env.clone(), config.clone(), 3).unwrap();
+        let res: MinUnstakeCyclesResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetMinUnstakeCycles {}).unwrap()).unwrap();
+        assert_eq!(res.min_unstake_cycles, 3);
+        env.block.time = start_time.plus_seconds(2 * CYCLE_LENGTH_IN_SECONDS);
+        let res = unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id.clone(), None, None);
+        assert_eq!(ContractError::TokenSteelFrozen {}.to_string(), res.err().unwrap().to_string());
+        env.block.time = start_time.plus_seconds(3 * CYCLE_LENGTH_IN_SECONDS);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id.clone(), None, None).unwrap();
+
+        let res = set_min_unstake_cycles(deps.as_mut(), info.clone(), env.clone(), config.clone(), 0);
+        assert_eq!(ContractError::InvalidMinUnstakeCycles {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_unstake_not_reach_unbonding_time() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
+                
+        let staker_info = mock_info(STAKER, &[]);
+        let timestamp = env.block.time.seconds();
+        let claim_recipient_address = None;
+
+        // request unbond nft
+        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp).unwrap();
+
+        // re-request unstake before end of unbonding duration
+        let unbonding_duration = UNBONDING_DURATION.load(deps.as_mut().storage).unwrap();
+        let before_unbonding_duration = unbonding_duration - 10;
+        assert_eq!(1814390, before_unbonding_duration);
+
+        // unbonding duration error
+        let res = test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), before_unbonding_duration);
+        assert_eq!(ContractError::NotReachUnbondingTime {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_unstake_staker_has_alotof_rewards() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        
+        let staker_info = mock_info(STAKER, &[]);
+
+        // pass many time enough to exceed max compute period
+        let timestamp = env.block.time.seconds() + 10000000;
+        let claim_recipient_address = None;
+
+        // current period is much bigger than default max compute period
+        let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
+        let current_period = get_current_period(timestamp, start_timestamp, config.clone()).unwrap();
+        assert_eq!(55556, current_period);
+        assert!(current_period > DEFAULT_MAX_COMPUTE_PERIOD);
+
+        // request unbond nft
+        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp).unwrap();
+
+        let unbonding_duration = UNBONDING_DURATION.load(deps.as_mut().storage).unwrap();
+
+        // current time is after sum of timestamp and unbonding duration + 1 
+        let timestamp = timestamp + unbonding_duration + 1;
+
+        // re-request unstake the nft has "UNBONDED" as bond_status
+        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp).unwrap();
+
+        // --------------------------------
+        // check after run unstake function
+        let staker_rewards = query_balance(deps.as_ref(), staker).unwrap();
+        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
+
+        // deposit cycle = 1.
+        // cycle length in seconds is 60 and period length in cycles is 3 for test.
+        // requested unbonding period is 55556.
+        // rewards per cycle is 17.
+
+        // claimable period is 55555 which is previous period of the requested unbonding period
+        // so, the equation of claimable rewards value = 55555 * 3 * 17 = 2,833,305
+        // nft staking contract's rewards pool (i.e. cw20 token balance of contract) is 2000000000 - 2833305 = 1997166695
+        assert_eq!(2833305, staker_rewards.balance.u128());
+        assert_eq!(1997166695, contract_balance.balance.u128());
+    }
+
+    #[test]
+    fn test_set_max_compute_period() {
+        // test environment
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+
+        let res = set_max_compute_period(deps.as_mut(), info.clone(), env.clone(), 0, config.clone());
+        assert_eq!(ContractError::InvalidSetMaxPeriod {}.to_string(), res.err().unwrap().to_string());
+
+        // lowering is allowed without the warning
+        let res = set_max_compute_period(deps.as_mut(), info.clone(), env.clone(), 1, config.clone()).unwrap();
+        assert_eq!(res.attributes.len(), 3);
+        assert_eq!(MAX_COMPUTE_PERIOD.load(deps.as_ref().storage).unwrap(), 1);
+
+        // the recommended max compute period is 7500 cycles / 3 cycles per period
+        let res = set_max_compute_period(deps.as_mut(), info.clone(), env.clone(), 2500, config.clone()).unwrap();
+        assert_eq!(res.attributes.len(), 3);
+
+        let res = set_max_compute_period(deps.as_mut(), info.clone(), env.clone(), 2501, config.clone()).unwrap();
+        assert_eq!(res.attributes[3].key, "warning");
+        assert_eq!(res.attributes[4].value, "2500");
+
+        // the hard cap
+        let res = set_max_compute_period(deps.as_mut(), info.clone(), env.clone(), MAX_ALLOWED_COMPUTE_PERIOD, config.clone()).unwrap();
+        assert_eq!(res.attributes[3].key, "warning");
+        assert_eq!(MAX_COMPUTE_PERIOD.load(deps.as_ref().storage).unwrap(), MAX_ALLOWED_COMPUTE_PERIOD);
+
+        let res = set_max_compute_period(deps.as_mut(), info.clone(), env.clone(), MAX_ALLOWED_COMPUTE_PERIOD + 1, config.clone());
+        assert_eq!(ContractError::MaxComputePeriodTooLarge { 
+            max_compute_period: MAX_ALLOWED_COMPUTE_PERIOD + 1, 
+            max_allowed_compute_period: MAX_ALLOWED_COMPUTE_PERIOD,
+        }.to_string(), res.err().unwrap().to_string());
+        assert_eq!(MAX_COMPUTE_PERIOD.load(deps.as_ref().storage).unwrap(), MAX_ALLOWED_COMPUTE_PERIOD);
+    }
+
+    #[test]
+    fn test_claim_plan() {
+        // do stake
+        let (mut deps, _info, mut env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        MAX_COMPUTE_PERIOD.save(deps.as_mut().storage, &4).unwrap();
+        let plan_msg = QueryMsg::ClaimPlan { staker: staker.clone(), token_id: token_id.clone() };
+
+        // nothing is claimable in the first period
+        let res: ClaimPlanResponse = from_binary(&query(deps.as_ref(), env.clone(), plan_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.total_claimable_periods, 0);
+        assert!(res.chunks.is_empty());
+
+        // the gap of 11 periods spans 3 chunks
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: ClaimPlanResponse = from_binary(&query(deps.as_ref(), env.clone(), plan_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+        assert_eq!(res.total_claimable_periods, 11);
+        assert_eq!(res.chunks, vec![
+            ClaimPlanChunk { periods: 4, estimated_amount: 204 },
+            ClaimPlanChunk { periods: 4, estimated_amount: 204 },
+            ClaimPlanChunk { periods: 3, estimated_amount: 153 },
+        ]);
+        assert!(!res.has_more);
+
+        // the first chunk is claimed as planned
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 4, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 204));
+        let res: ClaimPlanResponse = from_binary(&query(deps.as_ref(), env.clone(), plan_msg.clone()).unwrap()).unwrap();
+        assert_eq!(res.total_claimable_periods, 7);
+        assert_eq!(res.chunks.len(), 2);
+
+        // the number of chunks is capped
+        MAX_COMPUTE_PERIOD.save(deps.as_mut().storage, &1).unwrap();
+        let res: ClaimPlanResponse = from_binary(&query(deps.as_ref(), env.clone(), plan_msg).unwrap()).unwrap();
+        assert_eq!(res.total_claimable_periods, 7);
+        assert_eq!(res.chunks.len(), 5);
+        assert_eq!(res.chunks[0], ClaimPlanChunk { periods: 1, estimated_amount: 51 });
+        assert!(res.has_more);
+    }
+
+    #[test]
+    fn test_staker_history_expanded() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, _config, staker, token_id) = do_stake();
+        let key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+        // staked in cycle 1, unstaked in cycle 5 and staked again in cycle 8
+        STAKER_HISTORIES.save(deps.as_mut().storage, key.clone(), &[
+            Snapshot::new(true, 1),
+            Snapshot::new(false, 5),
+            Snapshot::new(true, 8),
+        ]).unwrap();
+
+        let res: StakerHistoryExpandedResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakerHistoryExpanded { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.staker_tokenid_key, key);
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+        assert_eq!(res.staker_history_ranges, vec![
+            SnapshotRange { start_cycle: 1, end_cycle: Some(5), is_staked: true },
+            SnapshotRange { start_cycle: 5, end_cycle: Some(8), is_staked: false },
+            SnapshotRange { start_cycle: 8, end_cycle: None, is_staked: true },
+        ]);
+
+        // no history
+        let res: StakerHistoryExpandedResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakerHistoryExpanded { staker: "anyone".to_string(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(res.staker_history_ranges.is_empty());
+        assert_eq!(res.res_msg, ContractError::HaveNotHistory {}.to_string());
+    }
+
+    #[test]
+    fn test_staker_history_bounded_by_churn() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+        // stake and unstake repeatedly in different cycles
+        let mut env = env.clone();
+        for _ in 0..(MAX_SNAPSHOTS_PER_TOKEN + 10) {
+            env.block.time = env.block.time.plus_seconds(200);
+            unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+            env.block.time = env.block.time.plus_seconds(100);
+            unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+            assert!(STAKER_HISTORIES.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().len() <= 2);
+
+            env.block.time = env.block.time.plus_seconds(60);
+            let msg = Cw721ReceiveMsg {
+                sender: staker.clone(),
+                token_id: token_id.clone(),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+            assert_eq!(STAKER_HISTORIES.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().len(), 1);
+        }
+
+        // rewards are still computed from the latest stake
+        let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
+        let next_claim = NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
+        assert_eq!(next_claim.staker_snapshot_index, 0);
+        env.block.time = env.block.time.plus_seconds(2000);
+        let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), 100, env.block.time.seconds(), start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(claim.start_period, next_claim.period);
+        assert!(claim.amount > 0);
+
+        // adjacent snapshots of same stake are coalesced when the history is full
+        let mut staker_history = vec![Snapshot::new(true, 1)];
+        for i in 1..MAX_SNAPSHOTS_PER_TOKEN {
+            staker_history.push(Snapshot::new(i % 10 != 0, i as u64 + 1));
+        }
+        STAKER_HISTORIES.save(deps.as_mut().storage, staker_tokenid_key.clone(), &staker_history).unwrap();
+        NEXT_CLAIMS.save(deps.as_mut().storage, staker_tokenid_key.clone(), &NextClaim::new(1, 15)).unwrap();
+        let current_cycle = MAX_SNAPSHOTS_PER_TOKEN as u64 + 10;
+        update_histories(deps.as_mut(), staker_tokenid_key.clone(), !IS_STAKED, current_cycle).unwrap();
+
+        let staker_history = STAKER_HISTORIES.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
+        assert_eq!(staker_history.len(), 20);
+        assert_eq!(staker_history[0], Snapshot::new(true, 1));
+        assert_eq!(staker_history[1], Snapshot::new(false, 11));
+        assert_eq!(staker_history[2], Snapshot::new(true, 12));
+        assert_eq!(staker_history[19], Snapshot::new(false, current_cycle));
+        // the 16th snapshot is merged to the third snapshot starting at cycle 12
+        assert_eq!(NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().staker_snapshot_index, 2);
+
+        // coalescing cannot help if every snapshot alternates
+        let staker_history: Vec<Snapshot> = (0..MAX_SNAPSHOTS_PER_TOKEN).map(|i| Snapshot::new(i % 2 == 0, i as u64 + 1)).collect();
+        STAKER_HISTORIES.save(deps.as_mut().storage, staker_tokenid_key.clone(), &staker_history).unwrap();
+        let res = update_histories(deps.as_mut(), staker_tokenid_key.clone(), IS_STAKED, current_cycle);
+        assert_eq!(ContractError::TooManySnapshots {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_compact_staker_history() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // a history with many transitions, every 2 cycles
+        let staker_history: Vec<Snapshot> = (0..MAX_SNAPSHOTS_PER_TOKEN - 1).map(|i| Snapshot::new(i % 2 == 0, i as u64 * 2 + 1)).collect();
+        assert_eq!(StakerHistories::decode(&StakerHistories::encode(&staker_history)), staker_history);
+
+        // the history of the previous version is read as it is and gives the same claim
+        STAKER_HISTORIES.remove(deps.as_mut().storage, staker_tokenid_key.clone());
+        STAKER_HISTORIES.save_legacy(deps.as_mut().storage, staker_tokenid_key.clone(), &staker_history).unwrap();
+        assert_eq!(STAKER_HISTORIES.load(deps.as_ref().storage, staker_tokenid_key.clone()).unwrap(), staker_history);
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let start_timestamp = START_TIMESTAMP.load(deps.as_ref().storage).unwrap();
+        let (legacy_claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), 100, env.block.time.seconds(), start_timestamp, config.clone(), token_id.clone()).unwrap();
+
+        // the migration does not convert histories, they are converted when they are saved again
+        set_contract_version(deps.as_mut().storage, NFT_STAKING_CONTRACT_NAME, "0.0.1").unwrap();
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(STAKER_HISTORIES.load(deps.as_ref().storage, staker_tokenid_key.clone()).unwrap(), staker_history);
+        let res: StakerHistoryResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakerHistory { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.staker_history, staker_history);
+        let (compact_claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), 100, env.block.time.seconds(), start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(compact_claim, legacy_claim);
+        claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+
+        // benchmark of the storage gas of a claim which reads and writes the history.
+        // the wasm execution gas is not measured here, the kv store gas of wasmd is estimated by
+        // read 1000 + 3 per byte and write 2000 + 30 per byte.
+        let history_gas = |bytes: usize| 1000 + 3 * bytes + 2000 + 30 * bytes;
+        let legacy_bytes = to_vec(&staker_history).unwrap().len();
+        let compact_bytes = to_vec(&StakerHistories::encode(&staker_history)).unwrap().len();
+        assert_eq!((legacy_bytes, compact_bytes), (3658, 370));
+        assert_eq!((history_gas(legacy_bytes), history_gas(compact_bytes)), (123714, 15210));
+    }
+
+    #[test]
+    fn test_first_period_proration() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, _staker, _token_id) = test_environment();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+
+        // stake at the first, the middle and the last cycle of period 2
+        let stake_cycles: [u64; 3] = [4, 5, 6];
+        for stake_cycle in stake_cycles {
+            let mut stake_env = env.clone();
+            stake_env.block.time = stake_env.block.time.plus_seconds((stake_cycle - 1) * CYCLE_LENGTH_IN_SECONDS);
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: format!("token_id_other_{}", stake_cycle),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), stake_env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+            assert_eq!(get_current_period(stake_env.block.time.seconds(), env.block.time.seconds(), config.clone()).unwrap(), 2);
+        }
+
+        // claim period 2 at the first cycle of period 3, the reward is the remaining cycles of period 2
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2 * PERIOD_LENGTH_IN_CYCLES * CYCLE_LENGTH_IN_SECONDS);
+        for stake_cycle in stake_cycles {
+            let token_id = format!("token_id_other_{}", stake_cycle);
+            let res = claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 1, token_id, config.clone(), None, None).unwrap();
+            let remaining_cycles = (2 * PERIOD_LENGTH_IN_CYCLES + 1 - stake_cycle) as u128;
+            assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), remaining_cycles * REWARDS_PER_CYCLE));
+        }
+    }
+
+    #[test]
+    fn test_emission_mode() {
+        // flat pays the rewards per cycle to every nft, shared splits 60 per period among the staked nfts.
+        let modes = [
+            (EmissionMode::Flat, None, [561, 561, 561]),
+            (EmissionMode::SharedPerPeriod, Some(60), [660, 330, 330]),
+        ];
+        for (emission_mode, reward_per_period, expected) in modes {
+            let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+            mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+            let staker_info = mock_info(staker.as_str(), &[]);
+            set_emission_mode(deps.as_mut(), info.clone(), env.clone(), config.clone(), emission_mode, reward_per_period).unwrap();
+            let res: EmissionModeResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetEmissionMode {}).unwrap()).unwrap();
+            assert_eq!(res.emission_mode, emission_mode);
+            assert_eq!(res.reward_per_period, reward_per_period);
+
+            // only the staker's nft is staked, period 12
+            let mut env = env.clone();
+            env.block.time = env.block.time.plus_seconds(2000);
+            let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+            assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), expected[0]));
+
+            // granter stakes the other nft at the first cycle of period 12
+            let other_token_id = "token_id_other_0".to_string();
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: other_token_id.clone(),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+
+            // two nfts are staked from period 12 to 22
+            env.block.time = env.block.time.plus_seconds(1980);
+            let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+            assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), expected[1]));
+            let res = claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, other_token_id.clone(), config.clone(), None, None).unwrap();
+            assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), expected[2]));
+        }
+    }
+
+    #[test]
+    fn test_corrupted_next_claim() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // the snapshot index is beyond the staker history
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        let mut next_claim = NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
+        next_claim.staker_snapshot_index = STAKER_HISTORIES.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().len() as u64;
+        NEXT_CLAIMS.save(deps.as_mut().storage, staker_tokenid_key.clone(), &next_claim).unwrap();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::CorruptedNextClaim {}.to_string(), res.err().unwrap().to_string());
+
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.res_msg, ContractError::CorruptedNextClaim {}.to_string());
+    }
+
+    #[test]
+    fn test_staked_count_at_period() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+
+        // period 12, granter stakes two nfts
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        for other_token_id in ["token_id_other_0", "token_id_other_1"] {
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: other_token_id.to_string(),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        // period 14, the staker's nft is unstaked after unbonding
+        env.block.time = env.block.time.plus_seconds(300);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(staker.as_str(), &[]), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(staker.as_str(), &[]), config.clone(), token_id.clone(), None, None).unwrap();
+
+        let expected: [(u64, u128); 7] = [(0, 0), (1, 1), (11, 1), (12, 3), (13, 3), (14, 2), (100, 2)];
+        for (period, staked_count) in expected {
+            let res: StakedCountAtPeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedCountAtPeriod { period }).unwrap()).unwrap();
+            assert_eq!(res.period, period);
+            assert_eq!(res.staked_count, staked_count);
+            assert_eq!(res.res_msg, SUCCESS);
+        }
+    }
+
+    #[test]
+    fn test_set_emission_mode_without_reward_per_period() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        let res = set_emission_mode(deps.as_mut(), info.clone(), env.clone(), config.clone(), EmissionMode::SharedPerPeriod, None);
+        assert_eq!(ContractError::EmptyRewardPerPeriod {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_total_paid_out() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let res: TotalPaidOutResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TotalPaidOut {}).unwrap()).unwrap();
+        assert_eq!(res.total_paid_out, 0);
+
+        // claim rewards to the other recipient
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), Some(GRANTER.to_string()), None).unwrap();
+        let res: TotalPaidOutResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TotalPaidOut {}).unwrap()).unwrap();
+        assert_eq!(res.total_paid_out, 255);
+
+        // unstake pays out the remained rewards
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        let res: TotalPaidOutResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TotalPaidOut {}).unwrap()).unwrap();
+        assert_eq!(res.total_paid_out, 561);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let res: MetricsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Metrics {}).unwrap()).unwrap();
+        assert_eq!(res, MetricsResponse { total_claims: 0, total_unstakes: 0, total_paid_out: 0, number_of_staked_nfts: 1 });
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), None, None).unwrap();
+        let res: MetricsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Metrics {}).unwrap()).unwrap();
+        assert_eq!(res, MetricsResponse { total_claims: 1, total_unstakes: 0, total_paid_out: 255, number_of_staked_nfts: 1 });
+
+        // the unstake is counted when it is finalized
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        let res: MetricsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Metrics {}).unwrap()).unwrap();
+        assert_eq!(res.total_unstakes, 0);
+
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        let res: MetricsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Metrics {}).unwrap()).unwrap();
+        assert_eq!(res, MetricsResponse { total_claims: 1, total_unstakes: 1, total_paid_out: 561, number_of_staked_nfts: 0 });
+    }
+
+    #[test]
+    fn test_metrics_deferred_unstake() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+
+        // the unstake which defers rewards is counted as well
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        let res: MetricsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Metrics {}).unwrap()).unwrap();
+        assert_eq!(res, MetricsResponse { total_claims: 0, total_unstakes: 1, total_paid_out: 0, number_of_staked_nfts: 0 });
+    }
+
+    #[test]
+    fn test_unstake_claim_on_unstake() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // rewards are paid out when the nft is unstaked
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(true)).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(true)).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        assert!(NEXT_CLAIMS.may_load(deps.as_mut().storage, staker_tokenid_key(staker.clone(), token_id.clone())).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unstake_deferred_claim() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+        // the nft is returned without paying rewards
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: cw721_contract_address.to_string(), 
+            msg: to_binary(&Cw721BaseExecuteMsg::<Extension, Empty>::TransferNft { recipient: staker.clone(), token_id: token_id.clone() }).unwrap(), 
+            funds: vec![],
+        }));
+
+        let token_info = TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap();
+        assert_eq!(token_info.bond_status, UNBONDED);
+        assert_eq!(token_info.owner, staker);
+        assert!(!token_info.is_staked);
+        assert!(NEXT_CLAIMS.may_load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().is_some());
+        assert_eq!(NUMBER_OF_STAKED_NFTS.load(deps.as_mut().storage).unwrap(), 0);
+
+        // the deferred rewards are claimed until the requested unbond time
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        assert!(NEXT_CLAIMS.may_load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().is_none());
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap().owner, "");
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::EmptyNextClaim {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_unstake_phase() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let attr = |res: &Response, key: &str| res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(attr(&res, "phase"), "start_unbonding");
+        assert_eq!(attr(&res, "token_id"), token_id);
+        assert_eq!(attr(&res, "owner"), staker);
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "unstake_nft");
+        assert_eq!(res.events[0].attributes[0].value, "start_unbonding");
+
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(attr(&res, "phase"), "finalized");
+        assert_eq!(attr(&res, "token_id"), token_id);
+        assert_eq!(attr(&res, "owner"), staker);
+        assert_eq!(res.events[0].ty, "unstake_nft");
+        assert_eq!(res.events[0].attributes[0].value, "finalized");
+        assert_eq!(res.events[0].attributes[1].value, token_id);
+        assert_eq!(res.events[0].attributes[2].value, staker);
+    }
+
+    #[test]
+    fn test_claim_rewards_unbonded_token() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+
+        // only the staker of the deferred rewards is able to claim.
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::EmptyNextClaim {}.to_string(), res.err().unwrap().to_string());
+
+        // claim the part of the deferred rewards, the token is still unbonded.
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+        let token_info = TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap();
+        assert_eq!(token_info.bond_status, UNBONDED);
+        assert_eq!(token_info.owner, staker);
+
+        // the rest is capped at the requested unbond time although more time is passed.
+        env.block.time = env.block.time.plus_seconds(5000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 306));
+        assert!(NEXT_CLAIMS.may_load(deps.as_mut().storage, staker_tokenid_key(staker.clone(), token_id.clone())).unwrap().is_none());
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap().bond_status, UNSPECIFIED);
+    }
+
+    #[test]
+    fn test_stake_settles_deferred_rewards() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+
+        // the nft is transferred and staked by the granter, deferred rewards are paid to the previous staker
+        env.block.time = env.block.time.plus_seconds(1000);
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        let res = stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        assert!(NEXT_CLAIMS.may_load(deps.as_mut().storage, staker_tokenid_key(staker.clone(), token_id.clone())).unwrap().is_none());
+        assert_eq!(TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap().owner, GRANTER);
+    }
+
+    #[test]
+    fn test_rewards_follow_nft() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let granter_info = mock_info(GRANTER, &[]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        // the other address has no next claim of the token
+        let res = claim_rewards(deps.as_mut(), granter_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::EmptyNextClaim {}.to_string(), res.err().unwrap().to_string());
+
+        // only the current staker of the token is able to claim
+        set_rewards_follow_nft(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        let res = claim_rewards(deps.as_mut(), granter_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::InvalidNftOwner { requester: GRANTER.to_string(), nft_owner: staker.clone() }.to_string(), res.err().unwrap().to_string());
+
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+
+        // the nft is transferred and staked by the granter, deferred rewards follow the nft to the granter
+        env.block.time = env.block.time.plus_seconds(1000);
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        let res = stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), 561));
+        assert!(NEXT_CLAIMS.may_load(deps.as_mut().storage, staker_tokenid_key(staker.clone(), token_id.clone())).unwrap().is_none());
+
+        // the previous staker is not able to claim anymore
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::InvalidNftOwner { requester: staker.clone(), nft_owner: GRANTER.to_string() }.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_rewards_token_cutover() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // both of the rewards token and the secondary rewards token hold the rewards pool
+        let secondary_rewards_token = "xpla1secondaryrewardstoken".to_string();
+        let cw20_contract_address = mock_env_cw20().contract.address.to_string();
+        let secondary_contract_address = secondary_rewards_token.clone();
+        deps.querier.update_wasm(move |query| {
+            match query {
+                WasmQuery::Smart { contract_addr, msg } if *contract_addr == cw20_contract_address || *contract_addr == secondary_contract_address => {
+                    let res = BalanceResponse { balance: Uint128::from(ADD_REWARDS_POOL) };
+                    match from_binary(msg).unwrap() {
+                        Cw20QueryMsg::Balance { address: _ } => SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap())),
+                        _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw20".to_string() }),
+                    }
+                },
+                _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+            }
+        });
+
+        let res = set_rewards_token_cutover(deps.as_mut(), info.clone(), env.clone(), config.clone(), config.rewards_token_contract.clone(), 6);
+        assert_eq!(ContractError::InvalidSecondaryRewardsToken {}.to_string(), res.err().unwrap().to_string());
+
+        let res = set_rewards_token_cutover(deps.as_mut(), info.clone(), env.clone(), config.clone(), secondary_rewards_token.clone(), 0);
+        assert_eq!(ContractError::InvalidCutoverPeriod { cutover_period: 0, current_period: 1 }.to_string(), res.err().unwrap().to_string());
+
+        set_rewards_token_cutover(deps.as_mut(), info.clone(), env.clone(), config.clone(), secondary_rewards_token.clone(), 6).unwrap();
+        let res: RewardsTokenCutoverResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetRewardsTokenCutover {}).unwrap()).unwrap();
+        assert_eq!(res.rewards_token_cutover, Some(RewardsTokenCutover { secondary_rewards_token: secondary_rewards_token.clone(), cutover_period: 6 }));
+
+        // the periods from 1 to 5 are paid in the rewards token, the periods from 6 to 11 are paid in the secondary rewards token
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.attributes[3].value, "561");
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+        assert_eq!(res.messages[1].msg, CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: secondary_rewards_token.clone(), 
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: staker.clone(), amount: Uint128::from(306u128) }).unwrap(), 
+            funds: vec![],
+        }));
+
+        // the next claim is paid only in the secondary rewards token
+        env.block.time = env.block.time.plus_seconds(180);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: secondary_rewards_token.clone(), 
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: staker.clone(), amount: Uint128::from(51u128) }).unwrap(), 
+            funds: vec![],
+        }));
+
+        // the cutover is not able to be changed after it is reached
+        let res = set_rewards_token_cutover(deps.as_mut(), info.clone(), env.clone(), config.clone(), secondary_rewards_token.clone(), 20);
+        assert_eq!(ContractError::RewardsTokenCutoverReached { cutover_period: 6 }.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_finalizable_tokens() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // stake other nfts
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        let other_token_ids = vec!["token_id_other_0".to_string(), "token_id_other_1".to_string()];
+        for other_token_id in other_token_ids.clone() {
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: other_token_id,
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        // request unstake of staker's nft and one of granter's nfts
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(staker.as_str(), &[]), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(30);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_ids[0].clone(), None, None).unwrap();
+
+        // the unbonding duration is not passed yet
+        let res: FinalizableTokensResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FinalizableTokens { start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.tokens, vec![]);
+
+        // only staker's nft passed the unbonding duration
+        env.block.time = env.block.time.plus_seconds(40);
+        let res: FinalizableTokensResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FinalizableTokens { start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.tokens.len(), 1);
+        assert_eq!(res.tokens[0].token_id, token_id);
+        assert_eq!(res.tokens[0].token_info.owner, staker);
+        assert_eq!(res.next_start_after, None);
+
+        // both nfts passed the unbonding duration, the bonded nft is skipped
+        env.block.time = env.block.time.plus_seconds(40);
+        let res: FinalizableTokensResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FinalizableTokens { start_after: None, limit: Some(1) }).unwrap()).unwrap();
+        assert_eq!(res.tokens.len(), 1);
+        assert_eq!(res.tokens[0].token_id, other_token_ids[0]);
+        assert_eq!(res.tokens[0].token_info.owner, GRANTER.to_string());
+        assert_eq!(res.next_start_after, Some(other_token_ids[0].clone()));
+
+        let res: FinalizableTokensResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FinalizableTokens { start_after: res.next_start_after, limit: Some(1) }).unwrap()).unwrap();
+        assert_eq!(res.tokens.len(), 1);
+        assert_eq!(res.tokens[0].token_id, token_id);
+        assert_eq!(res.next_start_after, None);
+    }
+
+    #[test]
+    fn test_unstake_available_at() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, _staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &0).unwrap();
+        let start_time = env.block.time;
+
+        // staked at cycle 1, it is able to be unstaked from the start of cycle 3
+        let mut env = env.clone();
+        env.block.time = start_time.plus_seconds(10);
+        let res: UnstakeAvailableAtResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::UnstakeAvailableAt { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.available_at, start_time.seconds() + 2 * CYCLE_LENGTH_IN_SECONDS);
+        assert!(!res.available_now);
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+
+        // the just staked token at cycle 34 versus the old one
+        env.block.time = start_time.plus_seconds(2000);
+        let other_token_id = "token_id_other_0".to_string();
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: other_token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+        let res: UnstakeAvailableAtResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::UnstakeAvailableAt { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(res.available_now);
+        let res: UnstakeAvailableAtResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::UnstakeAvailableAt { token_id: other_token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.available_at, start_time.seconds() + 35 * CYCLE_LENGTH_IN_SECONDS);
+        assert!(!res.available_now);
+
+        // the frozen rule of unstake matches the available time
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id.clone(), None, None).unwrap();
+        env.block.time = Timestamp::from_seconds(res.available_at - 1);
+        let unstake = unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id.clone(), None, None);
+        assert_eq!(ContractError::TokenSteelFrozen {}.to_string(), unstake.err().unwrap().to_string());
+        env.block.time = Timestamp::from_seconds(res.available_at);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id.clone(), None, None).unwrap();
+
+        // the unstaked token
+        let res: UnstakeAvailableAtResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::UnstakeAvailableAt { token_id: other_token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.res_msg, ContractError::UnstakedTokenId {}.to_string());
+    }
+
+    #[test]
+    fn test_is_finalizable() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+
+        // bonded token
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: IsFinalizableResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsFinalizable { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(!res.finalizable);
+        assert_eq!(res.finalize_at, None);
+
+        // unbonding token which does not pass the unbonding duration
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(staker.as_str(), &[]), config.clone(), token_id.clone(), None, None).unwrap();
+        let finalize_at = env.block.time.seconds() + 61;
+        env.block.time = env.block.time.plus_seconds(60);
+        let res: IsFinalizableResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsFinalizable { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(!res.finalizable);
+        assert_eq!(res.finalize_at, Some(finalize_at));
+
+        // unbonding token which is ready, the next unstake returns the nft
+        env.block.time = env.block.time.plus_seconds(1);
+        let res: IsFinalizableResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsFinalizable { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(res.finalizable);
+        assert_eq!(res.finalize_at, Some(finalize_at));
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(staker.as_str(), &[]), config.clone(), token_id.clone(), None, None).unwrap();
+
+        let res: IsFinalizableResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsFinalizable { token_id: "unknown".to_string() }).unwrap()).unwrap();
+        assert_eq!(res.res_msg, ContractError::InvalidTokenId {}.to_string());
+    }
+
+    #[test]
+    fn test_staked_token_ids_by_owner() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, _token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        for i in 0..4 {
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: format!("token_id_other_{}", i),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        let res: StakedNftsByOwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedNftsByOwner { staker: GRANTER.to_string(), start_after: None, limit: None, verify: None }).unwrap()).unwrap();
+        let expected: Vec<String> = res.staked_nfts.iter().map(|nft| nft.token_id.clone()).collect();
+        assert_eq!(expected.len(), 4);
+
+        // same token ids as the full query, by pages
+        let res: StakedTokenIdsByOwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedTokenIdsByOwner { staker: GRANTER.to_string(), start_after: None, limit: Some(3) }).unwrap()).unwrap();
+        assert_eq!(res.token_ids, expected[..3].to_vec());
+        assert_eq!(res.next_start_after, Some(expected[2].clone()));
+
+        let res: StakedTokenIdsByOwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedTokenIdsByOwner { staker: GRANTER.to_string(), start_after: res.next_start_after, limit: Some(3) }).unwrap()).unwrap();
+        assert_eq!(res.token_ids, expected[3..].to_vec());
+        assert_eq!(res.next_start_after, None);
+
+        let res: StakedTokenIdsByOwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedTokenIdsByOwner { staker: staker.clone(), start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.token_ids, vec![TOKEN_ID.to_string()]);
+    }
+
+    #[test]
+    fn test_stake_many() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = test_environment();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // the staker owns two nfts and approved the nft staking contract as the operator, the granter owns the other
+        let cw20_contract_address = mock_env_cw20().contract.address.to_string();
+        let cw721_contract_address = mock_env_cw721().contract.address.to_string();
+        let staker_token_ids = vec![token_id.clone(), "token_id_test_1".to_string()];
+        let owned_token_ids = staker_token_ids.clone();
+        deps.querier.update_wasm(move |query| {
+            match query {
+                WasmQuery::Smart { contract_addr, msg } if *contract_addr == cw20_contract_address => {
+                    let res = BalanceResponse { balance: Uint128::from(ADD_REWARDS_POOL) };
+                    match from_binary(msg).unwrap() {
+                        Cw20QueryMsg::Balance { address: _ } => SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap())),
+                        _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw20".to_string() }),
+                    }
+                },
+                WasmQuery::Smart { contract_addr, msg } if *contract_addr == cw721_contract_address => {
+                    match from_binary(msg).unwrap() {
+                        Cw721QueryMsg::OwnerOf { token_id, include_expired: _ } => {
+                            let owner = if owned_token_ids.contains(&token_id) { STAKER } else { GRANTER };
+                            let res = OwnerOfResponse { owner: owner.to_string(), approvals: vec![] };
+                            SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                        },
+                        _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw721".to_string() }),
+                    }
+                },
+                _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+            }
+        });
+
+        let res = stake_many(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), vec![]);
+        assert_eq!(ContractError::EmptyTokenIds {}.to_string(), res.err().unwrap().to_string());
+
+        // the nft of the granter is not pulled although the nft staking contract is approved by the granter
+        let res = stake_many(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), vec![token_id.clone(), "token_id_other_0".to_string()]);
+        assert_eq!(ContractError::InvalidNftOwner { requester: staker.clone(), nft_owner: GRANTER.to_string() }.to_string(), res.err().unwrap().to_string());
+
+        let res = stake_many(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), staker_token_ids.clone()).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        for (i, staked_token_id) in staker_token_ids.iter().enumerate() {
+            let expected = CosmosMsg::Wasm(WasmMsg::Execute { 
+                contract_addr: mock_env_cw721().contract.address.to_string(), 
+                msg: to_binary(&Cw721BaseExecuteMsg::<Extension, Empty>::TransferNft { 
+                    recipient: env.contract.address.to_string(), 
+                    token_id: staked_token_id.clone(), 
+                }).unwrap(), 
+                funds: vec![] 
+            });
+            assert_eq!(res.messages[i].msg, expected);
+
+            let token_info = TOKEN_INFOS.load(deps.as_ref().storage, staked_token_id.clone()).unwrap();
+            assert_eq!(token_info.owner, staker);
+            assert!(token_info.is_staked);
+        }
+        assert_eq!(NUMBER_OF_STAKED_NFTS.load(deps.as_ref().storage).unwrap(), 2);
+        assert_eq!(STAKER_NFT_COUNTS.load(deps.as_ref().storage, staker.clone()).unwrap(), 2);
+
+        let res = stake_many(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), vec![token_id.clone()]);
+        assert_eq!(ContractError::AlreadyStaked {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_staked_nfts_by_owner_verify() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, _staker, _token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        for i in 0..12 {
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: format!("token_id_other_{}", i),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        // token_id_other_1 is drifted, the nft staking contract is not the cw721 owner anymore
+        let held_token_ids = (0..12).filter(|i| *i != 1).map(|i| format!("token_id_other_{}", i)).collect();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, held_token_ids);
+
+        // not verified without verify
+        let res: StakedNftsByOwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedNftsByOwner { staker: GRANTER.to_string(), start_after: None, limit: None, verify: None }).unwrap()).unwrap();
+        assert_eq!(res.staked_nfts.len(), 12);
+        assert!(res.staked_nfts.iter().all(|nft| nft.verified.is_none()));
+        assert_eq!(res.next_start_after, None);
+
+        // the limit is capped at 10 with verify
+        let res: StakedNftsByOwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedNftsByOwner { staker: GRANTER.to_string(), start_after: None, limit: Some(30), verify: Some(true) }).unwrap()).unwrap();
+        assert_eq!(res.staked_nfts.len(), 10);
+        assert_eq!(res.next_start_after, Some("token_id_other_7".to_string()));
+        for nft in res.staked_nfts {
+            assert_eq!(nft.verified, Some(nft.token_id != "token_id_other_1"));
+        }
+
+        let res: StakedNftsByOwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedNftsByOwner { staker: GRANTER.to_string(), start_after: res.next_start_after, limit: None, verify: Some(true) }).unwrap()).unwrap();
+        let token_ids: Vec<String> = res.staked_nfts.iter().map(|nft| nft.token_id.clone()).collect();
+        assert_eq!(token_ids, vec!["token_id_other_8".to_string(), "token_id_other_9".to_string()]);
+        assert!(res.staked_nfts.iter().all(|nft| nft.verified == Some(true)));
+        assert_eq!(res.next_start_after, None);
+    }
+
+    #[test]
+    fn test_claim_all_rewards() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, _staker, _token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // stake four nfts, the last one is unbonding when claiming all
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        for i in 0..4 {
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: format!("token_id_other_{}", i),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), "token_id_other_3".to_string(), None, None).unwrap();
+
+        let res = claim_all_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, config.clone(), None, None).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        for message in res.messages.iter() {
+            assert_eq!(message.msg, cw20_transfer_msg(GRANTER.to_string(), 561));
+        }
+        let claimed: Vec<String> = res.attributes.iter().filter(|attr| attr.key == "claimed_token_id").map(|attr| attr.value.clone()).collect();
+        assert_eq!(claimed, vec!["token_id_other_0", "token_id_other_1", "token_id_other_2"]);
+        assert_eq!(res.attributes.iter().find(|attr| attr.key == "total_claim_amount").unwrap().value, (561 * 3).to_string());
+        // the skipped unbonding token is the last processed token
+        assert_eq!(res.attributes.iter().find(|attr| attr.key == "last_processed_token_id").unwrap().value, "token_id_other_3");
+        assert_eq!(res.attributes.iter().find(|attr| attr.key == "next_start_after").unwrap().value, "none");
+
+        // the next claim moves to the current period
+        let next_claim = NEXT_CLAIMS.load(deps.as_ref().storage, staker_tokenid_key(GRANTER.to_string(), "token_id_other_0".to_string())).unwrap();
+        assert_eq!(next_claim.period, 12);
+
+        // nothing is claimable in the same period
+        let res = claim_all_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, config.clone(), None, None);
+        assert_eq!(res.unwrap_err().to_string(), ContractError::NoAmountClaim {}.to_string());
+    }
+
+    #[test]
+    fn test_index_staker_tokens() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        for i in 0..2 {
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: format!("token_id_other_{}", i),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        // the token ids are indexed by the staker on stake
+        let indexed: Vec<String> = STAKER_TOKENS.prefix(GRANTER.to_string()).keys(deps.as_ref().storage, None, None, Order::Ascending).map(|t| t.unwrap()).collect();
+        assert_eq!(indexed, vec!["token_id_other_0", "token_id_other_1"]);
+        assert!(STAKER_TOKENS.has(deps.as_ref().storage, (staker.clone(), token_id.clone())));
+
+        // the token info saved before the index exists is not claimed by claim all
+        STAKER_TOKENS.remove(deps.as_mut().storage, (GRANTER.to_string(), "token_id_other_1".to_string()));
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_all_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, config.clone(), None, None).unwrap();
+        let claimed: Vec<String> = res.attributes.iter().filter(|attr| attr.key == "claimed_token_id").map(|attr| attr.value.clone()).collect();
+        assert_eq!(claimed, vec!["token_id_other_0"]);
+
+        // only the contract owner indexes the token infos, by pages
+        let res = index_staker_tokens(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+        let res = index_staker_tokens(deps.as_mut(), info.clone(), env.clone(), config.clone(), None, Some(2)).unwrap();
+        assert_eq!(res.attributes.iter().find(|attr| attr.key == "next_start_after").unwrap().value, "token_id_other_1");
+        let res = index_staker_tokens(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some("token_id_other_1".to_string()), Some(2)).unwrap();
+        assert_eq!(res.attributes.iter().find(|attr| attr.key == "indexed_tokens").unwrap().value, "1");
+        assert_eq!(res.attributes.iter().find(|attr| attr.key == "next_start_after").unwrap().value, "none");
+
+        let res = claim_all_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, config.clone(), None, None).unwrap();
+        let claimed: Vec<String> = res.attributes.iter().filter(|attr| attr.key == "claimed_token_id").map(|attr| attr.value.clone()).collect();
+        assert_eq!(claimed, vec!["token_id_other_1"]);
+
+        // the token id is removed from the index when the owner is cleared
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), "token_id_other_0".to_string(), None, None).unwrap();
+        assert!(STAKER_TOKENS.has(deps.as_ref().storage, (GRANTER.to_string(), "token_id_other_0".to_string())));
+        env.block.time = env.block.time.plus_seconds(UNBONDING_DURATION.load(deps.as_ref().storage).unwrap() + 1);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), "token_id_other_0".to_string(), None, None).unwrap();
+        assert!(!STAKER_TOKENS.has(deps.as_ref().storage, (GRANTER.to_string(), "token_id_other_0".to_string())));
+    }
+
+    #[test]
+    fn test_estimate_all_rewards_paging() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, _staker, _token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // stake seven nfts, more than the default cap of five
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        for i in 0..7 {
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: format!("token_id_other_{}", i),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+        }
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: EstimateAllRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateAllRewards { periods: 100, staker: GRANTER.to_string(), start_after: None, max_tokens: None }).unwrap()).unwrap();
+        assert_eq!(res.estimates.len(), 5);
+        assert_eq!(res.total_amount, 561 * 5);
+        assert!(res.has_more);
+        assert_eq!(res.next_start_after, Some("token_id_other_4".to_string()));
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+
+        let res: EstimateAllRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateAllRewards { periods: 100, staker: GRANTER.to_string(), start_after: res.next_start_after, max_tokens: None }).unwrap()).unwrap();
+        assert_eq!(res.estimates.len(), 2);
+        assert_eq!(res.estimates[1].req_staker_tokenid_key, staker_tokenid_key(GRANTER.to_string(), "token_id_other_6".to_string()));
+        assert_eq!(res.total_amount, 561 * 2);
+        assert!(!res.has_more);
+        assert_eq!(res.next_start_after, None);
+
+        // the client controls the batch size
+        let res: EstimateAllRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateAllRewards { periods: 100, staker: GRANTER.to_string(), start_after: None, max_tokens: Some(7) }).unwrap()).unwrap();
+        assert_eq!(res.estimates.len(), 7);
+        assert!(!res.has_more);
+    }
+
+    #[test]
+    fn test_finalizable_tokens_paging() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, _staker, _token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+
+        // stake and request unstake of 7 nfts
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        let mut other_token_ids = vec![];
+        for i in 0..7 {
+            let other_token_id = format!("token_id_other_{}", i);
+            let msg = Cw721ReceiveMsg {
+                sender: GRANTER.to_string(),
+                token_id: other_token_id.clone(),
+                msg: to_binary("send nft to stake").unwrap(),
+            };
+            stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg).unwrap();
+            other_token_ids.push(other_token_id);
+        }
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        for other_token_id in other_token_ids.clone() {
+            unstake_nft(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), other_token_id, None, None).unwrap();
+        }
+        env.block.time = env.block.time.plus_seconds(100);
+
+        // page to completion, the last page is partial
+        let mut start_after = None;
+        let mut pages = vec![];
+        loop {
+            let res: FinalizableTokensResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FinalizableTokens { start_after, limit: Some(3) }).unwrap()).unwrap();
+            pages.push(res.tokens.iter().map(|token| token.token_id.clone()).collect::<Vec<String>>());
+            if res.next_start_after.is_none() {
+                break
+            }
+            start_after = res.next_start_after;
+        }
+        assert_eq!(pages.iter().map(|page| page.len()).collect::<Vec<usize>>(), vec![3, 3, 1]);
+        assert_eq!(pages.concat(), other_token_ids);
+    }
+
+    #[test]
+    fn test_accrual_diff() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        let res: AccrualDiffResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AccrualDiff { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.next_claim_period, 1);
+        assert_eq!(res.current_period, 12);
+        assert_eq!(res.unclaimed_periods, 11);
+        assert_eq!(res.estimate.amount, 561);
+        assert_eq!(res.bond_status, BONDED);
+        assert!(!res.capped_by_unbonding);
+        assert_eq!(res.res_msg, SUCCESS);
+
+        // request unbond, the accrual is capped at the requested unbond time
+        let staker_info = mock_info(staker.as_str(), &[]);
+        test_unstake_function(deps.as_mut(), env.clone(), staker_info, config.clone(), token_id.clone(), None, env.block.time.seconds()).unwrap();
+        env.block.time = env.block.time.plus_seconds(4000);
+
+        let res: AccrualDiffResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AccrualDiff { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.current_period, 34);
+        assert_eq!(res.unclaimed_periods, 11);
+        assert_eq!(res.estimate.amount, 561);
+        assert_eq!(res.bond_status, UNBONDING);
+        assert!(res.capped_by_unbonding);
+
+        // no next claim
+        let res: AccrualDiffResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AccrualDiff { staker: staker.clone(), token_id: "not_staked".to_string() }).unwrap()).unwrap();
+        assert_eq!(res.res_msg, ContractError::EmptyNextClaim {}.to_string());
+    }
+
+    #[test]
+    fn test_estimate_rewards_claimable_via() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 11, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 561);
+        assert_eq!(res.claimable_via, CLAIMABLE_VIA_CLAIM);
+
+        // the unbonding token rewards are realized only by unstaking
+        let staker_info = mock_info(staker.as_str(), &[]);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(1000);
+
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 561);
+        assert_eq!(res.claimable_via, CLAIMABLE_VIA_UNSTAKE);
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::TokenIdIsUnbonding {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_estimate_rewards_rate_change() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 561);
+
+        // the new rate is applied to every unclaimed period, the estimate matches the claim
+        add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), REWARDS_PER_CYCLE * 2, config.clone()).unwrap();
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 1122);
+
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 1122));
+    }
+
+    #[test]
+    fn test_unstake_rate_change_during_unbonding() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // periods from 1 to 5 are claimed by the first rate
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+
+        // the rate is changed before and during the unbonding
+        add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), REWARDS_PER_CYCLE * 2, config.clone()).unwrap();
+        env.block.time = env.block.time.plus_seconds(1000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), REWARDS_PER_CYCLE * 3, config.clone()).unwrap();
+
+        // the rewards schedule has no history, the finalize pays the unclaimed periods from 6 to 11 by the current rate.
+        // periods after the requested unbond time are not paid although the finalize is late.
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 6 * 153));
+    }
+
+    #[test]
+    fn test_next_claims_batch() {
+        // do stake
+        let (deps, _info, env, _cw721_contract, _cw721_contract_address, _config, staker, token_id) = do_stake();
+
+        let keys = vec![(staker.clone(), token_id.clone()), (staker.clone(), "unknown".to_string()), (GRANTER.to_string(), token_id.clone())];
+        let res: Vec<NextClaimResponse> = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::NextClaimsBatch { keys }).unwrap()).unwrap();
+        assert_eq!(res.len(), 3);
+        assert_eq!(res[0], NextClaimResponse::new(NextClaim::new(1, 0)));
+        assert_eq!(res[1], NextClaimResponse::empty_next_claim());
+        assert_eq!(res[2], NextClaimResponse::empty_next_claim());
+
+        // the batch size is capped
+        let keys = vec![(staker.clone(), token_id.clone()); 31];
+        assert!(query(deps.as_ref(), env.clone(), QueryMsg::NextClaimsBatch { keys }).is_err());
+    }
+
+    #[test]
+    fn test_booster() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let booster_contract = "booster_contract".to_string();
+
+        // the whitelisted nft contract cannot be the booster contract
+        let res = set_booster(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(cw721_contract_address.to_string()), 10_000);
+        assert_eq!(ContractError::InvalidBoosterContract { booster_contract: cw721_contract_address.to_string() }.to_string(), res.err().unwrap().to_string());
+        set_booster(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(booster_contract.clone()), 10_000).unwrap();
+        let config = CONFIG_STATE.load(deps.as_ref().storage).unwrap();
+
+        // the other staker stakes without the booster
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: "token_id_other_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+
+        // the booster nft is routed into the booster map and earns nothing
+        let msg = Cw721ReceiveMsg {
+            sender: staker.clone(),
+            token_id: "booster_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(booster_contract.as_str(), &[]), config.clone(), msg.clone()).unwrap();
+        let res = stake_nft(deps.as_mut(), env.clone(), mock_info(booster_contract.as_str(), &[]), config.clone(), msg);
+        assert_eq!(ContractError::AlreadyStaked {}.to_string(), res.err().unwrap().to_string());
+        assert!(TOKEN_INFOS.may_load(deps.as_ref().storage, "booster_0".to_string()).unwrap().is_none());
+        let res: BoostersResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Boosters { staker: staker.clone() }).unwrap()).unwrap();
+        assert_eq!(res.booster_count, 1);
+        assert_eq!(res.booster_bps, 10_000);
+
+        // the booster contract cannot be changed while the booster nft is staked
+        let res = set_booster(deps.as_mut(), info.clone(), env.clone(), config.clone(), None, 0);
+        assert_eq!(ContractError::BoosterContractInUse {}.to_string(), res.err().unwrap().to_string());
+
+        // rewards are doubled with the booster except the period of the booster stake
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 1071));
+        let res = claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, "token_id_other_0".to_string(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), 561));
+
+        // only the staker of the booster can unstake it
+        let res = unstake_booster(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), config.clone(), "booster_0".to_string());
+        assert!(res.is_err());
+        let res = unstake_booster(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), config.clone(), "booster_0".to_string()).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: booster_contract.clone(), 
+            msg: to_binary(&Cw721BaseExecuteMsg::<Extension, Empty>::TransferNft { recipient: staker.clone(), token_id: "booster_0".to_string() }).unwrap(), 
+            funds: vec![],
+        }));
+        let res: BoostersResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Boosters { staker: staker.clone() }).unwrap()).unwrap();
+        assert_eq!(res.booster_count, 0);
+
+        // rewards are not boosted after the booster is unstaked
+        env.block.time = env.block.time.plus_seconds(180);
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51));
+    }
+
+    #[test]
+    fn test_booster_by_period() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let booster_contract = "booster_contract".to_string();
+        set_booster(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(booster_contract.clone()), 10_000).unwrap();
+        let config = CONFIG_STATE.load(deps.as_ref().storage).unwrap();
+        let msg = Cw721ReceiveMsg {
+            sender: staker.clone(),
+            token_id: "booster_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+
+        // the booster staked just before the claim does not boost the unclaimed periods
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        stake_nft(deps.as_mut(), env.clone(), mock_info(booster_contract.as_str(), &[]), config.clone(), msg.clone()).unwrap();
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+
+        // the booster unstaked in the period does not boost the period
+        unstake_booster(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), config.clone(), "booster_0".to_string()).unwrap();
+        env.block.time = env.block.time.plus_seconds(180);
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51));
+
+        // the booster held for the full period boosts it, and the period of the unstake is not boosted
+        stake_nft(deps.as_mut(), env.clone(), mock_info(booster_contract.as_str(), &[]), config.clone(), msg).unwrap();
+        env.block.time = env.block.time.plus_seconds(360);
+        unstake_booster(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), config.clone(), "booster_0".to_string()).unwrap();
+        env.block.time = env.block.time.plus_seconds(180);
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51 + 102 + 51));
+    }
+
+    #[test]
+    fn test_idempotent_start() {
+        // test environment is already started
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        let start_timestamp = START_TIMESTAMP.load(deps.as_ref().storage).unwrap();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = start(deps.as_mut(), info.clone(), env.clone(), config.clone(), None);
+        assert_eq!(ContractError::AlreadyStarted {}.to_string(), res.err().unwrap().to_string());
+        let res = start(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(false));
+        assert_eq!(ContractError::AlreadyStarted {}.to_string(), res.err().unwrap().to_string());
+
+        // the second start returns the original timestamp
+        let res = start(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(true)).unwrap();
+        assert_eq!(res.attributes[1].value, start_timestamp.to_string());
+        assert_eq!(res.attributes[2].value, "true");
+        assert_eq!(START_TIMESTAMP.load(deps.as_ref().storage).unwrap(), start_timestamp);
+
+        // the owner check is kept
+        let res = start(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone(), Some(true));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_project_stake_rewards() {
+        // test environment
+        let (deps, _info, env, _cw721_contract, _cw721_contract_address, _config, _staker, _token_id) = test_environment();
+        let res: ProjectStakeRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::ProjectStakeRewards { periods: 11 }).unwrap()).unwrap();
+        assert_eq!(res.rewards_per_cycle, REWARDS_PER_CYCLE);
+        assert_eq!(res.period_length_in_cycles, PERIOD_LENGTH_IN_CYCLES);
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+        let projected_rewards = res.projected_rewards;
+
+        // the projection matches the estimate of the real stake over the same periods
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, _config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.periods, 11);
+        assert_eq!(res.claim.amount, projected_rewards);
+    }
+
+    #[test]
+    fn test_reward_for_period() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let start_time = env.block.time;
+        let reward_for_period = |deps: Deps, env: Env, period: u64| -> RewardForPeriodResponse {
+            from_binary(&query(deps, env, QueryMsg::RewardForPeriod { staker: staker.clone(), token_id: token_id.clone(), period }).unwrap()).unwrap()
+        };
+
+        // the sum of every period matches the estimate
+        let mut env = env.clone();
+        env.block.time = start_time.plus_seconds(2000);
+        let total: u128 = (1..=11).map(|period| reward_for_period(deps.as_ref(), env.clone(), period).amount).sum();
+        assert_eq!(total, 561);
+        let res = reward_for_period(deps.as_ref(), env.clone(), 5);
+        assert_eq!(res.amount, REWARDS_PER_CYCLE * PERIOD_LENGTH_IN_CYCLES as u128);
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+
+        // the current period is not earned yet
+        assert_eq!(reward_for_period(deps.as_ref(), env.clone(), 12).amount, 0);
+
+        // staked at cycle 1, unstaked at cycle 5 and staked again at cycle 11
+        let key = staker_tokenid_key(staker.clone(), token_id.clone());
+        let staker_history = vec![Snapshot::new(true, 1), Snapshot::new(false, 5), Snapshot::new(true, 11)];
+        STAKER_HISTORIES.save(deps.as_mut().storage, key.clone(), &staker_history).unwrap();
+        let expected = [(1, 3), (2, 1), (3, 0), (4, 2), (5, 3)];
+        for (period, cycles) in expected {
+            assert_eq!(reward_for_period(deps.as_ref(), env.clone(), period).amount, REWARDS_PER_CYCLE * cycles);
+        }
+
+        // unstaked by deferred claim, periods from the requested unbond time are zero
+        STAKER_HISTORIES.save(deps.as_mut().storage, key.clone(), &[Snapshot::new(true, 1)]).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = start_time.plus_seconds(2100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+        env.block.time = start_time.plus_seconds(5000);
+        assert_eq!(reward_for_period(deps.as_ref(), env.clone(), 11).amount, 51);
+        assert_eq!(reward_for_period(deps.as_ref(), env.clone(), 12).amount, 0);
+        assert_eq!(reward_for_period(deps.as_ref(), env.clone(), 20).amount, 0);
+
+        // the other staker did not stake the token
+        let res: RewardForPeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::RewardForPeriod { staker: GRANTER.to_string(), token_id: token_id.clone(), period: 5 }).unwrap()).unwrap();
+        assert_eq!(res.amount, 0);
+    }
+
+    #[test]
+    fn test_is_reward_minter() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = test_environment();
+        let is_reward_minter = |deps: Deps, env: Env| -> bool {
+            let res: IsRewardMinterResponse = from_binary(&query(deps, env, QueryMsg::IsRewardMinter {}).unwrap()).unwrap();
+            res.is_reward_minter
+        };
+        let mock_minter = |deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, minter: String| {
+            let cw20_contract_address = mock_env_cw20().contract.address.to_string();
+            deps.querier.update_wasm(move |query| {
+                match query {
+                    WasmQuery::Smart { contract_addr, msg } if *contract_addr == cw20_contract_address => {
+                        match from_binary(msg).unwrap() {
+                            Cw20QueryMsg::Minter {} => {
+                                let res = Some(MinterResponse { minter: minter.clone(), cap: None });
+                                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                            },
+                            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw20".to_string() }),
+                        }
+                    },
+                    _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+                }
+            });
+        };
+
+        // the rewards token which does not respond to the minter query is not minted by the contract
+        assert!(!is_reward_minter(deps.as_ref(), env.clone()));
+
+        // the nft staking contract is the minter
+        mock_minter(&mut deps, env.contract.address.to_string());
+        let res = refresh_reward_minter(deps.as_mut(), env.clone(), config.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, "true");
+        assert!(is_reward_minter(deps.as_ref(), env.clone()));
+
+        // the minter role is moved to the other address
+        mock_minter(&mut deps, MINTER.to_string());
+        refresh_reward_minter(deps.as_mut(), env.clone(), config.clone()).unwrap();
+        assert!(!is_reward_minter(deps.as_ref(), env.clone()));
+    }
+
+    #[test]
+    fn test_token_multiplier() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // the other token is staked at the same time without the multiplier
+        let other_token_id = "token_id_other_0".to_string();
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: other_token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+
+        let res: TokenMultiplierResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TokenMultiplier { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.bps, 10000);
+
+        let res = set_token_multiplier(deps.as_mut(), staker_info.clone(), env.clone(), config.clone(), token_id.clone(), 20000);
+        assert!(res.is_err());
+        set_token_multiplier(deps.as_mut(), info.clone(), env.clone(), config.clone(), token_id.clone(), 20000).unwrap();
+        let res: TokenMultiplierResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TokenMultiplier { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res, TokenMultiplierResponse { token_id: token_id.clone(), bps: 20000 });
+
+        // only the token with the 2x multiplier accrues doubled rewards
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561 * 2));
+        let res = claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, other_token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), 561));
+
+        // the multiplier of 1x is removed, the running max multiplier is kept
+        set_token_multiplier(deps.as_mut(), info.clone(), env.clone(), config.clone(), token_id.clone(), 10000).unwrap();
+        assert!(TOKEN_MULTIPLIERS.may_load(deps.as_ref().storage, token_id.clone()).unwrap().is_none());
+        assert_eq!(MAX_TOKEN_MULTIPLIER_BPS.load(deps.as_ref().storage).unwrap(), 20000);
+    }
+
+    #[test]
+    fn test_early_staker_boost() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let start_time = env.block.time;
+        let reward_for_period = |deps: Deps, env: Env, staker: String, token_id: String, period: u64| -> u128 {
+            let res: RewardForPeriodResponse = from_binary(&query(deps, env, QueryMsg::RewardForPeriod { staker, token_id, period }).unwrap()).unwrap();
+            res.amount
+        };
+
+        let res = set_early_staker_boost(deps.as_mut(), staker_info.clone(), env.clone(), config.clone(), 10000, 4, 4);
+        assert!(res.is_err());
+
+        // the token staked in period 1 is doubled until period 4 and the boost decays to zero at period 8
+        set_early_staker_boost(deps.as_mut(), info.clone(), env.clone(), config.clone(), 10000, 4, 4).unwrap();
+        let res: EarlyStakerBoostResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetEarlyStakerBoost {}).unwrap()).unwrap();
+        assert_eq!(res.early_staker_boost, Some(EarlyStakerBoost { boost_bps: 10000, boost_deadline_period: 4, boost_decay_periods: 4 }));
+
+        // the token staked at the deadline period does not get the boost
+        let mut env = env.clone();
+        env.block.time = start_time.plus_seconds(600);
+        let other_token_id = "token_id_other_0".to_string();
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: other_token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+
+        env.block.time = start_time.plus_seconds(2000);
+        let expected = [(1, 102), (4, 102), (5, 89), (6, 76), (7, 63), (8, 51), (11, 51)];
+        for (period, amount) in expected {
+            assert_eq!(reward_for_period(deps.as_ref(), env.clone(), staker.clone(), token_id.clone(), period), amount);
+        }
+        assert_eq!(reward_for_period(deps.as_ref(), env.clone(), GRANTER.to_string(), other_token_id.clone(), 5), 51);
+
+        // the claim sums the decayed boost of every period
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 4 * 102 + 89 + 76 + 63 + 4 * 51));
+    }
+
+    #[test]
+    fn test_claim_error_details() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+        // caught up, no periods remain to claim
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::NoClaimablePeriods {}.to_string(), res.err().unwrap().to_string());
+
+        // the next claim of period zero is not initialized
+        NEXT_CLAIMS.save(deps.as_mut().storage, staker_tokenid_key.clone(), &NextClaim::new(0, 0)).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::ClaimNotInitialized {}.to_string(), res.err().unwrap().to_string());
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.res_msg, ContractError::ClaimNotInitialized {}.to_string());
+
+        // the estimate without the next claim
+        NEXT_CLAIMS.remove(deps.as_mut().storage, staker_tokenid_key);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.res_msg, ContractError::ClaimNotInitialized {}.to_string());
+    }
+
+    #[test]
+    fn test_claim_is_fully_caught_up() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let is_fully_caught_up = |res: &Response| res.attributes.iter().find(|attr| attr.key == "is_fully_caught_up").unwrap().value.clone();
+
+        // the claim is capped by the requested periods before the current period
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.attributes[2].value, "5");
+        assert_eq!(is_fully_caught_up(&res), "false");
+
+        // the requested periods exceed the remaining claimable periods
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.attributes[2].value, "6");
+        assert_eq!(is_fully_caught_up(&res), "true");
+    }
+
+    #[test]
+    fn test_claim_keeps_accrual_after_history_pruning() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let start_time = env.block.time;
+        let key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+        // staked in period 1, unstaked in period 2 and staked again from period 4
+        let staker_history = vec![Snapshot::new(true, 1), Snapshot::new(false, 4), Snapshot::new(true, 10)];
+        STAKER_HISTORIES.save(deps.as_mut().storage, key.clone(), &staker_history).unwrap();
+
+        // the claim of periods 1 to 2 frees the first snapshot
+        let mut env = env.clone();
+        env.block.time = start_time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 2, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51));
+        assert_eq!(STAKER_HISTORIES.load(deps.as_ref().storage, key.clone()).unwrap(), vec![Snapshot::new(false, 4), Snapshot::new(true, 10)]);
+        assert_eq!(NEXT_CLAIMS.load(deps.as_ref().storage, key.clone()).unwrap().staker_snapshot_index, 0);
+
+        // the claim of periods 3 to 11 against the pruned history
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51 * 8));
+        assert_eq!(STAKER_HISTORIES.load(deps.as_ref().storage, key.clone()).unwrap(), vec![Snapshot::new(true, 10)]);
+
+        // the staked token keeps accruing every period after the claims
+        env.block.time = start_time.plus_seconds(2600);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.attributes[1].value, "12");
+        assert_eq!(res.attributes[2].value, "3");
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51 * 3));
+        assert_eq!(STAKER_HISTORIES.load(deps.as_ref().storage, key.clone()).unwrap(), vec![Snapshot::new(true, 10)]);
+    }
+
+    #[test]
+    fn test_estimate_rewards_without_rewards_schedule() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, _config, staker, token_id) = do_stake();
+
+        // started but the rewards schedule is not set yet
+        REWARDS_SCHEDULE.remove(deps.as_mut().storage);
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim, Claim::default());
+        assert_eq!(res.res_msg, ContractError::NoRewardsSchedule {}.to_string());
+    }
+
+    #[test]
+    fn test_estimated_apr() {
+        // a year has 525600 cycles of 60 seconds
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, _token_id) = do_stake();
+        let res: EstimatedAprResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimatedApr {}).unwrap()).unwrap();
+        assert_eq!(res.emission_mode, EmissionMode::Flat);
+        assert_eq!(res.cycles_per_year, 525600);
+        assert_eq!(res.number_of_staked_nfts, 1);
+        assert_eq!(res.rewards_per_nft_per_year, REWARDS_PER_CYCLE * 525600);
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+
+        // the shared reward per period is split by the staked nfts
+        set_emission_mode(deps.as_mut(), info.clone(), env.clone(), config.clone(), EmissionMode::SharedPerPeriod, Some(60)).unwrap();
+        let res: EstimatedAprResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimatedApr {}).unwrap()).unwrap();
+        assert_eq!(res.rewards_per_nft_per_year, 60 * 525600 / PERIOD_LENGTH_IN_CYCLES as u128);
+
+        NUMBER_OF_STAKED_NFTS.save(deps.as_mut().storage, &2).unwrap();
+        let res: EstimatedAprResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimatedApr {}).unwrap()).unwrap();
+        assert_eq!(res.rewards_per_nft_per_year, 60 * 525600 / PERIOD_LENGTH_IN_CYCLES as u128 / 2);
+
+        // no staked nfts, the first staker would earn the whole reward per period
+        NUMBER_OF_STAKED_NFTS.save(deps.as_mut().storage, &0).unwrap();
+        let res: EstimatedAprResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimatedApr {}).unwrap()).unwrap();
+        assert_eq!(res.number_of_staked_nfts, 0);
+        assert_eq!(res.rewards_per_nft_per_year, 60 * 525600 / PERIOD_LENGTH_IN_CYCLES as u128);
+    }
+
+    #[test]
+    fn test_query_defaults_on_missing_items() {
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, _config, _staker, _token_id) = do_stake();
+
+        let res: MaxComputePeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetMaxComputePeriod {}).unwrap()).unwrap();
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+        let res: NumberOfStakedNftsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::NumberOfStakedNfts {}).unwrap()).unwrap();
+        assert_eq!(res.number_of_staked_nfts, 1);
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+
+        // items left unset by a migration are defaulted
+        MAX_COMPUTE_PERIOD.remove(deps.as_mut().storage);
+        UNBONDING_DURATION.remove(deps.as_mut().storage);
+        NUMBER_OF_STAKED_NFTS.remove(deps.as_mut().storage);
+
+        let res: MaxComputePeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetMaxComputePeriod {}).unwrap()).unwrap();
+        assert_eq!(res.max_compute_period, DEFAULT_MAX_COMPUTE_PERIOD);
+        assert_eq!(res.res_msg, ContractError::DefaultedSetting { setting: "max_compute_period".to_string(), default: "2500".to_string() }.to_string());
+
+        let res: UnbondingDurationResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetUnbondingDuration {}).unwrap()).unwrap();
+        assert_eq!(res.unbonding_duration, 1814400);
+        assert_eq!(res.res_msg, ContractError::DefaultedSetting { setting: "unbonding_duration".to_string(), default: "1814400".to_string() }.to_string());
+
+        let res: NumberOfStakedNftsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::NumberOfStakedNfts {}).unwrap()).unwrap();
+        assert_eq!(res.number_of_staked_nfts, 0);
+        assert_eq!(res.res_msg, ContractError::DefaultedSetting { setting: "number_of_staked_nfts".to_string(), default: "0".to_string() }.to_string());
+    }
+
+    #[test]
+    fn test_accrued_up_to_now() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        // mid first period, nothing is claimable but 150 seconds are accrued
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(150);
+        let res: AccruedUpToNowResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AccruedUpToNow { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claimable_amount, 0);
+        assert_eq!(res.in_progress_amount, REWARDS_PER_CYCLE * 150 / 60);
+        assert_eq!(res.accrued_amount, res.in_progress_amount);
+
+        // 30 seconds into the 12th period
+        env.block.time = env.block.time.plus_seconds(1860);
+        let res: AccruedUpToNowResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AccruedUpToNow { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claimable_amount, 561);
+        assert_eq!(res.in_progress_amount, REWARDS_PER_CYCLE * 30 / 60);
+        assert_eq!(res.accrued_amount, 569);
+
+        // the in progress amount is not claimed
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+        let res: AccruedUpToNowResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AccruedUpToNow { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claimable_amount, 0);
+        assert_eq!(res.in_progress_amount, 8);
+    }
+
+    #[test]
+    fn test_estimate_rewards_disabled() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(!res.disabled);
+
+        // accrued rewards are still estimated while disabled
+        disable(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 561);
+        assert!(res.disabled);
+        assert_eq!(res.res_msg, SUCCESS.to_string());
+
+        // the estimate is claimable once enabled
+        enable(deps.as_mut(), info.clone(), env.clone(), config.clone()).unwrap();
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+    }
+
+    #[test]
+    fn test_claim_cliff() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        set_claim_cliff_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), 20).unwrap();
+
+        let res: ClaimCliffPeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetClaimCliffPeriod {}).unwrap()).unwrap();
+        assert_eq!(res.claim_cliff_period, 20);
+
+        // current period is 12, rewards are accrued but cannot be claimed
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 10, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::BeforeCliff { cliff: 20 }.to_string(), res.err().unwrap().to_string());
+
+        let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
+        let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key(staker.clone(), token_id.clone()), 11, env.block.time.seconds(), start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(claim.amount, 561);
+
+        // unstake is not blocked by the cliff, the rewards are deferred
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(res.attributes[2].value, "false");
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap().bond_status, UNBONDED);
+
+        // the deferred rewards are still before the cliff
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::BeforeCliff { cliff: 20 }.to_string(), res.err().unwrap().to_string());
+
+        // current period is 29, after the cliff
+        env.block.time = env.block.time.plus_seconds(3000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+    }
+
+    #[test]
+    fn test_claim_after_cliff() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        set_claim_cliff_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), 12).unwrap();
+
+        // current period is 12, the cliff is reached
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+    }
+
+    #[test]
+    fn test_auto_disable_threshold() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, 1000, vec![]);
+        set_auto_disable_threshold(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(500)).unwrap();
+
+        let res: AutoDisableThresholdResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetAutoDisableThreshold {}).unwrap()).unwrap();
+        assert_eq!(res.auto_disable_threshold, Some(500));
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // remain balance is 1000 - 255 = 745, bigger than the threshold
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), None, None).unwrap();
+        assert!(res.events.is_empty());
+        assert!(!DISABLE.load(deps.as_mut().storage).unwrap());
+
+        // remain balance is 745 - 306 = 439, less than the threshold
+        mock_querier_contracts(&mut deps, 745, vec![]);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 6, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 306));
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "auto_disabled");
+        assert!(DISABLE.load(deps.as_mut().storage).unwrap());
+
+        // cannot claim anymore
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 1, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::Disabled {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_max_reward_per_claim_per_token() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        // max reward is same as rewards of 3 periods
+        set_max_reward_per_claim_per_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(153)).unwrap();
+        let res: MaxRewardPerClaimPerTokenResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetMaxRewardPerClaimPerToken {}).unwrap()).unwrap();
+        assert_eq!(res.max_reward_per_claim_per_token, Some(153));
+
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 11, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 153);
+        assert_eq!(res.claim.periods, 3);
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 153));
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        assert_eq!(NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().period, 4);
+
+        // over the cap, the period exceeding the max reward remains claimable
+        set_max_reward_per_claim_per_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(180)).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 153));
+        assert_eq!(NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().period, 7);
+
+        // remove the cap, the remaining 5 periods are claimed at once
+        set_max_reward_per_claim_per_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), None).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+    }
+
+    #[test]
+    fn test_max_reward_per_claim_per_token_remainder() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        // the first period earns 51 which exceeds the max reward, the excess is carried
+        set_max_reward_per_claim_per_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(20)).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 20));
+        let next_claim = NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
+        assert_eq!(next_claim.period, 2);
+        assert_eq!(next_claim.remainder, 31);
+
+        // the carried excess is paid by the next claims before the next period
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 20));
+        assert_eq!(NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap().remainder, 11);
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 11));
+        let next_claim = NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
+        assert_eq!(next_claim.period, 2);
+        assert_eq!(next_claim.remainder, 0);
+
+        // nothing is lost, the remaining periods are claimed without the cap
+        set_max_reward_per_claim_per_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), None).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561 - 51));
+    }
+
+    #[test]
+    fn test_min_pool_balance_to_stake() {
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = test_environment();
+        let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
+        let msg = Cw721ReceiveMsg {
+            sender: staker.clone(),
+            token_id: token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+
+        // the pool is below the minimum
+        set_min_pool_balance_to_stake(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(ADD_REWARDS_POOL + 1)).unwrap();
+        let res: FullSettingsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FullSettings {}).unwrap()).unwrap();
+        assert_eq!(res.min_pool_balance_to_stake, Some(ADD_REWARDS_POOL + 1));
+
+        let res = stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg.clone());
+        assert_eq!(ContractError::PoolBelowStakingMinimum { balance: ADD_REWARDS_POOL, minimum: ADD_REWARDS_POOL + 1 }.to_string(), res.err().unwrap().to_string());
+
+        // the pool is at the minimum
+        set_min_pool_balance_to_stake(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(ADD_REWARDS_POOL)).unwrap();
+        stake_nft(deps.as_mut(), env.clone(), cw721_info.clone(), config.clone(), msg.clone()).unwrap();
+        assert!(TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap().is_staked);
+
+        // unauthorized
+        let res = set_min_pool_balance_to_stake(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), config.clone(), None);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_emission_end_period() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+
+        // rewards are accrued until period 5
+        set_emission_end_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(6)).unwrap();
+        let res: EmissionEndPeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetEmissionEndPeriod {}).unwrap()).unwrap();
+        assert_eq!(res.emission_end_period, Some(6));
+
+        // claim before the end period, period 4
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(600);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 153));
+
+        // claim across the end period, period 12
+        env.block.time = env.block.time.plus_seconds(1400);
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 102);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 102));
+
+        // no rewards past the end period
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::NoClaimablePeriods {}.to_string(), res.err().unwrap().to_string());
+
+        // staking is rejected after the end period
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: "token_id_other_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        let res = stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg);
+        assert_eq!(ContractError::EmissionEnded { emission_end_period: 6 }.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_token_reward_recipient() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let treasury = "treasury".to_string();
+
+        // only the staker of the token can set
+        let res = set_token_reward_recipient(deps.as_mut(), mock_info("anyone", &[]), token_id.clone(), Some(treasury.clone()), None);
+        assert!(res.is_err());
+
+        set_token_reward_recipient(deps.as_mut(), staker_info.clone(), token_id.clone(), Some(treasury.clone()), None).unwrap();
+        let res: TokenRewardRecipientResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TokenRewardRecipient { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.recipient, Some(treasury.clone()));
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        // the default recipient is applied
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(treasury.clone(), 255));
+
+        // the explicit recipient overrides the default recipient
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 3, token_id.clone(), config.clone(), Some(GRANTER.to_string()), None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), 153));
+
+        // unstake also uses the default recipient
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(treasury.clone(), 153));
+
+        // remove the default recipient
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, _config, staker, token_id) = do_stake();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        set_token_reward_recipient(deps.as_mut(), staker_info.clone(), token_id.clone(), Some(treasury.clone()), None).unwrap();
+        set_token_reward_recipient(deps.as_mut(), staker_info.clone(), token_id.clone(), None, None).unwrap();
+        let res: TokenRewardRecipientResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TokenRewardRecipient { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.recipient, None);
+    }
+
+    #[test]
+    fn test_token_reward_recipient_expires() {
+        // do stake
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let treasury = "treasury".to_string();
+        let expires = Expiration::AtTime(env.block.time.plus_seconds(1000));
+
+        set_token_reward_recipient(deps.as_mut(), staker_info.clone(), token_id.clone(), Some(treasury.clone()), Some(expires)).unwrap();
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(999);
+        let res: TokenRewardRecipientResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TokenRewardRecipient { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.recipient, Some(treasury.clone()));
+        assert_eq!(res.expires, Some(expires));
+        assert!(!res.expired);
+
+        // the default recipient is applied before it expires
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 1, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(treasury.clone(), 51));
+
+        // the expired default recipient reverts to the staker
+        env.block.time = env.block.time.plus_seconds(1001);
+        let res: TokenRewardRecipientResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TokenRewardRecipient { staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(res.expired);
+
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 255));
+    }
+
+    #[test]
+    fn test_require_approved_recipient() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let attacker = "attacker".to_string();
+
+        set_require_approved_recipient(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+
+        // not approved recipient
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 5, token_id.clone(), config.clone(), Some(attacker.clone()), None);
+        assert_eq!(ContractError::RecipientNotApproved {}.to_string(), res.err().unwrap().to_string());
+
+        // the staker self is always allowed
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 1, token_id.clone(), config.clone(), Some(staker.clone()), None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51));
+
+        // only the staker can approve
+        let res = approve_reward_recipient(deps.as_mut(), info.clone(), token_id.clone(), GRANTER.to_string());
+        assert!(res.is_err());
+
+        approve_reward_recipient(deps.as_mut(), staker_info.clone(), token_id.clone(), GRANTER.to_string()).unwrap();
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 4, token_id.clone(), config.clone(), Some(GRANTER.to_string()), None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(GRANTER.to_string(), 204));
+
+        // unstake is guarded as well
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), Some(attacker.clone()), None);
+        assert_eq!(ContractError::RecipientNotApproved {}.to_string(), res.err().unwrap().to_string());
+
+        // not required
+        set_require_approved_recipient(deps.as_mut(), info.clone(), env.clone(), config.clone(), false).unwrap();
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), Some(attacker.clone()), None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(attacker.clone(), 306));
     }
 
     #[test]
-    fn test_claim_exceeding_max_compute_period() {
+    fn test_compute_rewards_loads_rewards_schedule_once() {
         // do stake
-        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
-
-        let timestamp = env.block.time.seconds() + 5000;
-        let staker_info = mock_info(staker.as_str(), &[]);
-
-        // exceed max compute period that default value is 2500
-        let request_claim_period = 3000;
-        let claim_recipient_address = None;
+        let (deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        let start_timestamp = START_TIMESTAMP.load(&deps.storage).unwrap();
+
+        // time passed by 100000 seconds, more than 500 periods are claimable
+        let now = env.block.time.seconds() + 100000;
+        let counting_storage = ReadCountingStorage::new(&deps.storage, b"rewards_schedule");
+        let counting_deps = Deps {
+            storage: &counting_storage,
+            api: &deps.api,
+            querier: QuerierWrapper::new(&deps.querier),
+        };
 
-        // claim error
-        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp.clone());
-        assert_eq!(ContractError::InvalidMaxPeriod {
-            periods: request_claim_period,
-            max_compute_period: DEFAULT_MAX_COMPUTE_PERIOD,
-        }.to_string(), res.err().unwrap().to_string());
+        let (claim, _) = compute_rewards(counting_deps, staker_tokenid_key(staker.clone(), token_id.clone()), 500, now, start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(claim.periods, 500);
+        assert_eq!(claim.amount, 500 * 3 * REWARDS_PER_CYCLE);
+        assert_eq!(counting_storage.reads(), 1);
     }
 
     #[test]
-    fn test_claim_other_recipient_address() {
+    fn test_skip_to_current_period() {
         // do stake
         let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
-
-        let timestamp = env.block.time.seconds() + 5000;
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
         let staker_info = mock_info(staker.as_str(), &[]);
-        let request_claim_period = 5;
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
 
-        // set the recipient address is granter address
-        let claim_recipient_address = Some(GRANTER.to_string());
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
 
-        // claim
-        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp.clone());
+        // only the staker can skip
+        let res = skip_to_current_period(deps.as_mut(), env.clone(), mock_info("anyone", &[]), config.clone(), token_id.clone(), 561);
+        assert!(res.is_err());
 
-        // --------------------------------
-        // check after run claim function
-        let staker_rewards = query_balance(deps.as_ref(), staker.clone()).unwrap();
-        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
-        let granter_rewards = query_balance(deps.as_ref(), GRANTER.to_string()).unwrap();
+        // the forfeit amount must match
+        let res = skip_to_current_period(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), 560);
+        assert_eq!(ContractError::ForfeitAmountMismatch { forfeit_amount: 560, forfeited_rewards: 561 }.to_string(), res.err().unwrap().to_string());
+    
+        // 11 periods are forfeited.
+        // the failed execution is not reverted in the unit test, so restore the next claim.
+        NEXT_CLAIMS.save(deps.as_mut().storage, staker_tokenid_key.clone(), &NextClaim::new(1, 0)).unwrap();
+        let res = skip_to_current_period(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), 561).unwrap();
+        assert!(res.messages.is_empty());
 
-        // the granter receives claim rewards
-        assert_eq!(255, granter_rewards.balance.u128());
-        assert_eq!(1999999745, contract_balance.balance.u128());
-        assert_eq!(0, staker_rewards.balance.u128());
-        assert_eq!(res.as_ref().unwrap().attributes.get(2).unwrap().value, GRANTER.to_string());
-        assert_eq!(res.as_ref().unwrap().attributes.get(3).unwrap().value, 255.to_string());
+        let next_claim = NEXT_CLAIMS.load(deps.as_mut().storage, staker_tokenid_key.clone()).unwrap();
+        assert_eq!(next_claim.period, 12);
+
+        // nothing to claim in the current period
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 10, token_id.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::NoClaimablePeriods {}.to_string(), res.err().unwrap().to_string());
+
+        // rewards are accrued again from the current period
+        env.block.time = env.block.time.plus_seconds(180);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 10, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 51));
     }
 
+    #[test]
+    fn test_claim_receipts() {
+        // do stake
+        let (mut deps, _info, mut env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+
+        let res: ClaimReceiptsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::ClaimReceipts { staker: None, start_after: None, limit: None }).unwrap()).unwrap();
+        assert!(res.receipts.is_empty());
+
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), mock_info(staker.as_str(), &[]), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.attributes.last().unwrap().value, "1");
+
+        // the other staker claims
+        let other_token_id = "token_id_other_0".to_string();
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: other_token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+        env.block.time = env.block.time.plus_seconds(360);
+        claim_rewards(deps.as_mut(), mock_info(GRANTER, &[]), env.clone(), 100, other_token_id.clone(), config.clone(), Some("treasury".to_string()), None).unwrap();
+
+        // a receipt is recorded per claim
+        let res: ClaimReceiptsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::ClaimReceipts { staker: None, start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.receipts.len(), 2);
+        assert_eq!(res.next_start_after, None);
+        assert_eq!(res.receipts[0].index, 1);
+        assert_eq!(res.receipts[0].receipt, ClaimReceipt {
+            staker: staker.clone(),
+            token_id: token_id.clone(),
+            amount: 561,
+            recipient: staker.clone(),
+            start_period: 1,
+            periods: 11,
+            timestamp: env.block.time.seconds() - 360,
+        });
+        assert_eq!(res.receipts[1].index, 2);
+        assert_eq!(res.receipts[1].receipt.staker, GRANTER.to_string());
+        assert_eq!(res.receipts[1].receipt.token_id, other_token_id);
+        assert_eq!(res.receipts[1].receipt.recipient, "treasury".to_string());
+        assert_eq!(res.receipts[1].receipt.timestamp, env.block.time.seconds());
+
+        // filter by the staker
+        let res: ClaimReceiptsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::ClaimReceipts { staker: Some(GRANTER.to_string()), start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.receipts.len(), 1);
+        assert_eq!(res.receipts[0].index, 2);
+
+        // paginate
+        let res: ClaimReceiptsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::ClaimReceipts { staker: None, start_after: None, limit: Some(1) }).unwrap()).unwrap();
+        assert_eq!(res.receipts.len(), 1);
+        assert_eq!(res.next_start_after, Some(1));
+        let res: ClaimReceiptsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::ClaimReceipts { staker: None, start_after: Some(1), limit: Some(1) }).unwrap()).unwrap();
+        assert_eq!(res.receipts[0].index, 2);
+        assert_eq!(res.next_start_after, None);
+    }
 
     #[test]
-    fn test_claim_while_unbonding_duration() {
+    fn test_orphaned_claims() {
         // do stake
-        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        let staked_key = staker_tokenid_key(staker.clone(), token_id.clone());
 
-        let staker_info = mock_info(STAKER, &[]);
-        let timestamp = env.block.time.seconds() + 2000;
-        let claim_recipient_address = None;
-        let request_claim_period = 5;
+        let res: OrphanedClaimsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::OrphanedClaims { start_after: None, limit: None }).unwrap()).unwrap();
+        assert!(res.staker_tokenid_keys.is_empty());
 
-        // request unbond nft. the nft is unbonding
-        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp.clone()).unwrap();
-        let token_info = TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone());
-        assert_eq!(token_info.unwrap().bond_status, UNBONDING);
+        // the token info does not exist, and the token is staked by the other staker
+        let missing_key = staker_tokenid_key("ghost".to_string(), "missing_token".to_string());
+        let other_staker_key = staker_tokenid_key("other".to_string(), token_id.clone());
+        for key in [missing_key.clone(), other_staker_key.clone()] {
+            NEXT_CLAIMS.save(deps.as_mut().storage, key.clone(), &NextClaim::new(1, 0)).unwrap();
+            STAKER_HISTORIES.save(deps.as_mut().storage, key, &[Snapshot::new(true, 1)]).unwrap();
+        }
 
-        // claim error
-        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp.clone());
-        assert_eq!(ContractError::TokenIdIsUnbonding {}.to_string(), res.err().unwrap().to_string());
+        let res: OrphanedClaimsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::OrphanedClaims { start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.staker_tokenid_keys, vec![missing_key.clone(), other_staker_key.clone()]);
+        assert_eq!(res.next_start_after, None);
+
+        let res: OrphanedClaimsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::OrphanedClaims { start_after: None, limit: Some(1) }).unwrap()).unwrap();
+        assert_eq!(res.staker_tokenid_keys, vec![missing_key.clone()]);
+        assert_eq!(res.next_start_after, Some(missing_key.clone()));
+
+        // only the owner can prune
+        let res = prune_orphaned_claims(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone(), None, None);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+
+        // the limit bounds the scanned next claims, the next page starts after the last scanned key
+        let res = prune_orphaned_claims(deps.as_mut(), info.clone(), env.clone(), config.clone(), None, Some(1)).unwrap();
+        assert_eq!(res.attributes[1].value, missing_key.clone());
+        assert_eq!(res.attributes[2].value, "1");
+        assert_eq!(res.attributes[3].value, missing_key.clone());
+        assert!(!NEXT_CLAIMS.has(deps.as_ref().storage, missing_key.clone()));
+
+        let res = prune_orphaned_claims(deps.as_mut(), info.clone(), env.clone(), config.clone(), Some(missing_key.clone()), None).unwrap();
+        assert_eq!(res.attributes[2].value, "1");
+        assert_eq!(res.attributes[3].value, "none");
+        assert!(!NEXT_CLAIMS.has(deps.as_ref().storage, missing_key.clone()));
+        assert!(STAKER_HISTORIES.may_load(deps.as_ref().storage, other_staker_key).unwrap().is_none());
+
+        // the next claim of the staked token remains
+        assert!(NEXT_CLAIMS.has(deps.as_ref().storage, staked_key.clone()));
+        assert!(STAKER_HISTORIES.may_load(deps.as_ref().storage, staked_key).unwrap().is_some());
+        let res: OrphanedClaimsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::OrphanedClaims { start_after: None, limit: None }).unwrap()).unwrap();
+        assert!(res.staker_tokenid_keys.is_empty());
     }
 
     #[test]
-    fn test_claim_empty_rewards_pool() {
-        // do stake
-        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
+    fn test_sweep_abandoned_rewards() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        UNBONDING_DURATION.save(deps.as_mut().storage, &60).unwrap();
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
 
-        let staker_info = mock_info(STAKER, &[]);
-        let timestamp = env.block.time.seconds() + 2000;
-        let claim_recipient_address = None;
-        let request_claim_period = 5;
+        // unbonded with the deferred rewards of periods 1..11
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, Some(false)).unwrap();
+
+        // only the owner can sweep
+        let res = sweep_abandoned_rewards(deps.as_mut(), staker_info.clone(), env.clone(), config.clone(), 5, None, None);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+
+        // the unbond time is not older than 5 periods yet
+        let res = sweep_abandoned_rewards(deps.as_mut(), info.clone(), env.clone(), config.clone(), 5, None, None).unwrap();
+        assert_eq!(res.attributes[1].value, "0");
+        assert!(NEXT_CLAIMS.has(deps.as_ref().storage, staker_tokenid_key.clone()));
+
+        // the token infos are scanned after start after
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = sweep_abandoned_rewards(deps.as_mut(), info.clone(), env.clone(), config.clone(), 5, Some(token_id.clone()), None).unwrap();
+        assert_eq!(res.attributes[1].value, "0");
+        assert_eq!(res.attributes[3].value, "none");
+        assert!(NEXT_CLAIMS.has(deps.as_ref().storage, staker_tokenid_key.clone()));
+
+        // the deferred rewards are forfeited and the next claim is cleared
+        let res = sweep_abandoned_rewards(deps.as_mut(), info.clone(), env.clone(), config.clone(), 5, None, Some(1)).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(res.attributes[1].value, token_id);
+        assert_eq!(res.attributes[2].value, "561");
+        assert_eq!(res.attributes[3].value, "1");
+        assert_eq!(res.attributes[4].value, "561");
+        assert_eq!(res.attributes[5].value, token_id);
+        assert!(!NEXT_CLAIMS.has(deps.as_ref().storage, staker_tokenid_key.clone()));
+
+        // the swept token is reset as the unstaked token without the owner and history
+        let token_info = TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap();
+        assert_eq!(token_info.owner, "");
+        assert_eq!(token_info.bond_status, UNSPECIFIED);
+        assert!(STAKER_HISTORIES.may_load(deps.as_ref().storage, staker_tokenid_key.clone()).unwrap().is_none());
+
+        // the swept token has nothing to sweep again
+        let res = sweep_abandoned_rewards(deps.as_mut(), info.clone(), env.clone(), config.clone(), 5, None, None).unwrap();
+        assert_eq!(res.attributes[1].value, "0");
+    }
 
-        // withdraw all rewards pool
-        test_execute_token_contract_transfer(deps.as_mut(), env.clone(), info, MINTER.to_string(), ADD_REWARDS_POOL);
+    #[test]
+    fn test_pool_constrained_emission() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        let staker_info = mock_info(staker.as_str(), &[]);
 
-        // claim error
-        let res = claim_rewards_function(deps.as_mut(), staker_info.clone(), env.clone(), request_claim_period, token_id.clone(), config.clone(), claim_recipient_address.clone(), timestamp.clone());
-        assert_eq!(ContractError::InsufficientRewardsPool {
-            rewards_pool_balance: test_query_rewards_token_balance(deps.as_mut(), env.clone().contract.address.to_string()).balance.u128(),
-            claim_amount: 255, 
-        }.to_string(), res.err().unwrap().to_string());
+        // it is only able to be set before start
+        let res = set_pool_constrained_emission(deps.as_mut(), info.clone(), env.clone(), config.clone(), true);
+        assert_eq!(ContractError::AlreadyStarted {}.to_string(), res.err().unwrap().to_string());
+
+        // the pool funds two periods of the staked nft, 51 per period
+        START_TIMESTAMP.remove(deps.as_mut().storage);
+        mock_querier_contracts(&mut deps, 102, vec![]);
+        let res = set_pool_constrained_emission(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        assert_eq!(res.attributes.get(2).unwrap().value, "102");
+        start(deps.as_mut(), info.clone(), env.clone(), config.clone(), None).unwrap();
+
+        // periods from 3 to 11 are not funded after the pool is drained
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 102));
+
+        // refill three periods at period 12
+        let total_rewards_pool = TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap();
+        mock_querier_contracts(&mut deps, total_rewards_pool + 153, vec![]);
+        let msg = Cw20ReceiveMsg {
+            sender: MINTER.to_string(),
+            amount: Uint128::from(153u128),
+            msg: Binary::from(r#"{add_rewards}"#.as_bytes()),
+        };
+        add_rewards_pool(deps.as_mut(), mock_info(mock_env_cw20().contract.address.as_str(), &[]), env.clone(), config.clone(), msg).unwrap();
+
+        // the unfunded periods before the refill do not accrue, the emission resumes from period 12
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: RewardForPeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::RewardForPeriod { staker: staker.clone(), token_id: token_id.clone(), period: 14 }).unwrap()).unwrap();
+        assert_eq!(res.amount, 51);
+        let res: RewardForPeriodResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::RewardForPeriod { staker: staker.clone(), token_id: token_id.clone(), period: 15 }).unwrap()).unwrap();
+        assert_eq!(res.amount, 0);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 153));
+
+        let res: EmissionLedgerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EmissionLedger { start_after: None, limit: None }).unwrap()).unwrap();
+        assert!(res.pool_constrained_emission);
+        assert_eq!(res.processed_period, 22);
+        assert_eq!(res.budget, 0);
+        assert_eq!(res.unfunded_periods, vec![3, 4, 5, 6, 7, 8, 9, 10, 11, 15]);
+        assert_eq!(res.next_start_after, Some(15));
+        let res: EmissionLedgerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EmissionLedger { start_after: Some(15), limit: None }).unwrap()).unwrap();
+        assert_eq!(res.unfunded_periods, (16..=22).collect::<Vec<u64>>());
+        assert_eq!(res.next_start_after, None);
+    }
+
+    #[test]
+    fn test_process_emission_ledger() {
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+        let staker_info = mock_info(staker.as_str(), &[]);
 
+        // the pool constrained emission is required
+        let res = process_emission_ledger(deps.as_mut(), env.clone(), config.clone());
+        assert_eq!(ContractError::PoolConstrainedEmissionDisabled {}.to_string(), res.err().unwrap().to_string());
+
+        // the pool funds two periods of the staked nft, 51 per period
+        START_TIMESTAMP.remove(deps.as_mut().storage);
+        mock_querier_contracts(&mut deps, 102, vec![]);
+        set_pool_constrained_emission(deps.as_mut(), info.clone(), env.clone(), config.clone(), true).unwrap();
+        start(deps.as_mut(), info.clone(), env.clone(), config.clone(), None).unwrap();
+
+        // nothing happens for 250 periods, anyone is able to process the ledger by the max periods per call
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(250 * PERIOD_LENGTH_IN_CYCLES * CYCLE_LENGTH_IN_SECONDS);
+        let res = process_emission_ledger(deps.as_mut(), env.clone(), config.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, MAX_EMISSION_LEDGER_PERIODS.to_string());
+        assert_eq!(res.attributes[2].value, "251");
+
+        // the deposit is rejected until every completed period is decided
+        let total_rewards_pool = TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap();
+        mock_querier_contracts(&mut deps, total_rewards_pool + 153, vec![]);
+        let msg = Cw20ReceiveMsg {
+            sender: MINTER.to_string(),
+            amount: Uint128::from(153u128),
+            msg: to_binary("add rewards pool").unwrap(),
+        };
+        let res = add_rewards_pool(deps.as_mut(), mock_info(mock_env_cw20().contract.address.as_str(), &[]), env.clone(), config.clone(), msg.clone());
+        assert_eq!(ContractError::EmissionLedgerNotSettled { processed_period: 200, current_period: 251 }.to_string(), res.err().unwrap().to_string());
+
+        // the claim is able to be executed while the ledger is processed
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 1000, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 102));
+        let res = process_emission_ledger(deps.as_mut(), env.clone(), config.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, "250");
+
+        // the deposit funds the periods from the current period after the ledger is settled
+        let total_rewards_pool = TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap();
+        mock_querier_contracts(&mut deps, total_rewards_pool + 153, vec![]);
+        add_rewards_pool(deps.as_mut(), mock_info(mock_env_cw20().contract.address.as_str(), &[]), env.clone(), config.clone(), msg).unwrap();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 1000, token_id.clone(), config.clone(), None, None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 153));
     }
 
     #[test]
-    fn test_unstake() {
+    fn test_blocked_tokens() {
         // do stake
-        let (mut deps, _info, env, cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
-        
-        let staker_info = mock_info(STAKER, &[]);
-        let timestamp = env.block.time.seconds() + 2000;
+        let (mut deps, info, mut env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let blocked_token_id = "token_id_other_0".to_string();
+
+        // only the owner can block
+        let res = block_token(deps.as_mut(), mock_info("anyone", &[]), env.clone(), config.clone(), blocked_token_id.clone());
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+        block_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), blocked_token_id.clone()).unwrap();
+
+        let res: TokenBlockedResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsTokenBlocked { token_id: blocked_token_id.clone() }).unwrap()).unwrap();
+        assert!(res.blocked);
+
+        // the blocked token id cannot be staked
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: blocked_token_id.clone(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        let res = stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg.clone());
+        assert_eq!(ContractError::TokenBlocked {}.to_string(), res.err().unwrap().to_string());
+
+        // the unblocked token id is able to be staked
+        unblock_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), blocked_token_id.clone()).unwrap();
+        let res: TokenBlockedResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsTokenBlocked { token_id: blocked_token_id.clone() }).unwrap()).unwrap();
+        assert!(!res.blocked);
+        stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+
+        // the already staked token id which becomes blocked is able to be unstaked
+        block_token(deps.as_mut(), info.clone(), env.clone(), config.clone(), token_id.clone()).unwrap();
+        env.block.time = env.block.time.plus_seconds(2000);
+        unstake_nft(deps.as_mut(), env.clone(), mock_info(staker.as_str(), &[]), config.clone(), token_id.clone(), None, None).unwrap();
+        assert_eq!(TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap().bond_status, UNBONDING);
+    }
 
-        // not claim to other address
-        let claim_recipient_address = None;
+    #[test]
+    fn test_mark_token_burned() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
 
-        // request unbond nft
-        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp.clone()).unwrap();
+        // the staked token id no longer exists in the cw721 contract
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
 
-        // requested unbonding period value
-        let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
-        let requested_unbonding_period = get_current_period(timestamp, start_timestamp, config.clone()).unwrap();
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
 
-        let unbonding_duration = UNBONDING_DURATION.load(deps.as_mut().storage).unwrap();
-        assert_eq!(unbonding_duration, 1814400);
+        // the granted address cannot mark the token
+        grant(deps.as_mut(), info.clone(), env.clone(), config.clone(), GRANTER.to_string(), None).unwrap();
+        let res = mark_token_burned(deps.as_mut(), env.clone(), mock_info(GRANTER, &[]), config.clone(), token_id.clone());
+        assert_eq!(ContractError::OwnerOnly {}.to_string(), res.err().unwrap().to_string());
 
-        // current time is after sum of timestamp and unbonding duration + 1 
-        let timestamp = timestamp + unbonding_duration + 1;
+        // the accrued rewards are not paid before the claim cliff
+        set_claim_cliff_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), 20).unwrap();
+        let res = mark_token_burned(deps.as_mut(), env.clone(), info.clone(), config.clone(), token_id.clone());
+        assert_eq!(ContractError::BeforeCliff { cliff: 20 }.to_string(), res.err().unwrap().to_string());
+        set_claim_cliff_period(deps.as_mut(), info.clone(), env.clone(), config.clone(), 0).unwrap();
 
-        // re-request unstake the nft has "UNBONDED" as bond_status
-        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp.clone()).unwrap();
+        let res = mark_token_burned(deps.as_mut(), env.clone(), info.clone(), config.clone(), token_id.clone()).unwrap();
 
-        // for test, seperate executing transfer nft and unstake function.
-        test_execute_transfer_nft_unstake(deps.as_mut(), env.clone(), staker.clone(), token_id, cw721_contract);
+        // rewards accrued until period 11 are paid to the staker, 11 * 3 * 17 = 561
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
 
-        // --------------------------------
-        // check after run unstake function
-        let staker_rewards = query_balance(deps.as_ref(), staker).unwrap();
-        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
+        let token_info = TOKEN_INFOS.load(deps.as_mut().storage, token_id.clone()).unwrap();
+        assert!(token_info.burned);
+        assert!(!token_info.is_staked);
+        assert_eq!(0, NUMBER_OF_STAKED_NFTS.load(deps.as_mut().storage).unwrap());
 
-        // deposit cycle = 1.
-        // cycle length in seconds is 60 and period length in cycles is 3 for test.
-        // requested unbonding time is now + 2000 seconds.
-        // rewards per cycle is 17.
+        // burned token does not accrue rewards anymore
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
+        let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key, 10, env.block.time.seconds() + 5000, start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(0, claim.amount);
 
-        // requested unbonding period that requested unbonding time is included is 12 (last cycle second is 2,160) and previous period that is claimable period is 11(last cycle second is 1,980).
-        // claimable rewards is limited at requested unbonding time, although current time is passed by requested unbonding time + unbonding duration(1814400) + 1.
-        // so, the equation of claimable rewards value = 1980 / 60 * 17 = 561
-        // nft staking contract's rewards pool (i.e. cw20 token balance of contract) is 2000000000 - 561 = 1999999439
-        
-        assert_eq!(12, requested_unbonding_period);
-        assert_eq!(561, staker_rewards.balance.u128());
-        assert_eq!(1999999439, contract_balance.balance.u128());
+        let res: TokenBurnedResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsTokenBurned { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(res.burned);
+
+        // the staker cannot unstake the burned token
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let res = unstake_nft(deps.as_mut(), env.clone(), staker_info, config.clone(), token_id.clone(), None, None);
+        assert_eq!(ContractError::TokenBurned {}.to_string(), res.err().unwrap().to_string());
     }
 
     #[test]
-    fn test_unstake_not_reach_unbonding_time() {
+    fn test_mark_token_burned_existing_token() {
         // do stake
-        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
-                
-        let staker_info = mock_info(STAKER, &[]);
-        let timestamp = env.block.time.seconds();
-        let claim_recipient_address = None;
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
 
-        // request unbond nft
-        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp.clone()).unwrap();
+        // the staked token id still exists in the cw721 contract
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![token_id.clone()]);
 
-        // re-request unstake before end of unbonding duration
-        let unbonding_duration = UNBONDING_DURATION.load(deps.as_mut().storage).unwrap();
-        let before_unbonding_duration = unbonding_duration - 10;
-        assert_eq!(1814390, before_unbonding_duration);
+        let res = mark_token_burned(deps.as_mut(), env.clone(), info.clone(), config.clone(), token_id.clone());
+        assert_eq!(ContractError::TokenNotBurned { owner: MOCK_CONTRACT_ADDR.to_string() }.to_string(), res.err().unwrap().to_string());
 
-        // unbonding duration error
-        let res = test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), before_unbonding_duration.clone());
-        assert_eq!(ContractError::NotReachUnbondingTime {}.to_string(), res.err().unwrap().to_string());
+        let res: TokenBurnedResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::IsTokenBurned { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(!res.burned);
     }
 
     #[test]
-    fn test_unstake_staker_has_alotof_rewards() {
+    fn test_mark_token_burned_query_error() {
+        // the owner query fails by the other error than not found
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
+        deps.querier.update_wasm(|_| SystemResult::Ok(ContractResult::Err("out of gas".to_string())));
+
+        let res = mark_token_burned(deps.as_mut(), env.clone(), info.clone(), config.clone(), token_id.clone());
+        assert!(res.err().unwrap().to_string().contains("out of gas"));
+
+        let token_info = TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap();
+        assert!(!token_info.burned);
+        assert!(token_info.is_staked);
+    }
+
+    #[test]
+    fn test_mark_token_burned_other_not_found_error() {
+        // the owner query fails by the error which contains not found, but it is not the missing token of the cw721 contract
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, _staker, token_id) = do_stake();
+        deps.querier.update_wasm(|_| SystemResult::Ok(ContractResult::Err("contract not found".to_string())));
+
+        let res = mark_token_burned(deps.as_mut(), env.clone(), info.clone(), config.clone(), token_id.clone());
+        assert!(res.err().unwrap().to_string().contains("contract not found"));
+
+        let token_info = TOKEN_INFOS.load(deps.as_ref().storage, token_id.clone()).unwrap();
+        assert!(!token_info.burned);
+        assert!(token_info.is_staked);
+    }
+
+    #[test]
+    fn test_staked_all_nft_info() {
         // do stake
-        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
-        
-        let staker_info = mock_info(STAKER, &[]);
+        let (mut deps, _info, env, _cw721_contract, _cw721_contract_address, _config, _staker, token_id) = do_stake();
+
+        // the mocked cw721 contract does not implement AllNftInfo
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![token_id.clone()]);
+        let res: StakedAllNftInfoResponse<Extension> = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedAllNftInfo { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(res.is_error);
+        assert_ne!(res.res_msg, SUCCESS);
+        assert_eq!(res.all_nft_info.access.owner, "");
+
+        // the cw721 contract implements AllNftInfo
+        deps.querier.update_wasm(move |_| {
+            let res = AllNftInfoResponse::<Extension> {
+                access: OwnerOfResponse { owner: MOCK_CONTRACT_ADDR.to_string(), approvals: vec![] },
+                info: NftInfoResponse { token_uri: None, extension: None },
+            };
+            SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+        });
+        let res: StakedAllNftInfoResponse<Extension> = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::StakedAllNftInfo { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(!res.is_error);
+        assert_eq!(res.res_msg, SUCCESS);
+        assert_eq!(res.all_nft_info.access.owner, MOCK_CONTRACT_ADDR);
+    }
 
-        // pass many time enough to exceed max compute period
-        let timestamp = env.block.time.seconds() + 10000000;
-        let claim_recipient_address = None;
+    #[test]
+    fn test_settings_missing_after_migrate() {
+        let (mut deps, _info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        let staker_info = mock_info(staker.as_str(), &[]);
 
-        // current period is much bigger than default max compute period
+        // the contract migrated from the previous version has no settings which are saved at instantiate
+        MIN_TOKENS_TO_EARN.remove(deps.as_mut().storage);
+        CLAIM_CLIFF_PERIOD.remove(deps.as_mut().storage);
+        EMISSION_MODE.remove(deps.as_mut().storage);
+        MIN_UNSTAKE_CYCLES.remove(deps.as_mut().storage);
+        FEE_ON_TRANSFER_TOKEN.remove(deps.as_mut().storage);
+        REQUIRE_APPROVED_RECIPIENT.remove(deps.as_mut().storage);
+        ALLOW_PRESTAKE.remove(deps.as_mut().storage);
+
+        let mut env = env.clone();
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res: FullSettingsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::FullSettings {}).unwrap()).unwrap();
+        assert_eq!(res.min_tokens_to_earn, 0);
+
+        let res: EstimateRewardsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::EstimateRewards { periods: 100, staker: staker.clone(), token_id: token_id.clone() }).unwrap()).unwrap();
+        assert_eq!(res.claim.amount, 561);
+        let res = claim_rewards(deps.as_mut(), staker_info.clone(), env.clone(), 100, token_id.clone(), config.clone(), Some(staker.clone()), None).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer_msg(staker.clone(), 561));
+
+        let res: UnstakeAvailableAtResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::UnstakeAvailableAt { token_id: token_id.clone() }).unwrap()).unwrap();
+        assert!(res.available_now);
+        unstake_nft(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), None, None).unwrap();
+
+        // the rewards pool is able to be refilled.
+        let total_rewards_pool = TOTAL_REWARDS_POOL.load(deps.as_ref().storage).unwrap();
+        mock_querier_contracts(&mut deps, total_rewards_pool + 1000, vec![]);
+        let msg = Cw20ReceiveMsg {
+            sender: MINTER.to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary("add rewards pool").unwrap(),
+        };
+        add_rewards_pool(deps.as_mut(), mock_info(mock_env_cw20().contract.address.as_str(), &[]), env.clone(), config.clone(), msg).unwrap();
+
+        // the prestake is not allowed before start.
+        START_TIMESTAMP.remove(deps.as_mut().storage);
+        let msg = Cw721ReceiveMsg {
+            sender: GRANTER.to_string(),
+            token_id: "token_id_other_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        let res = stake_nft(deps.as_mut(), env.clone(), mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg);
+        assert_eq!(ContractError::NotStarted {}.to_string(), res.err().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_min_tokens_to_earn() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, _cw721_contract_address, config, staker, token_id) = do_stake();
+
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
         let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
-        let current_period = get_current_period(timestamp, start_timestamp, config.clone()).unwrap();
-        assert_eq!(55556, current_period);
-        assert!(current_period > DEFAULT_MAX_COMPUTE_PERIOD);
+        let timestamp = env.block.time.seconds() + 5000;
+        assert_eq!(1, STAKER_NFT_COUNTS.load(deps.as_mut().storage, staker.clone()).unwrap());
 
-        // request unbond nft
-        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp.clone()).unwrap();
+        // default is 0, the staker earns rewards.
+        let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), 5, timestamp, start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(255, claim.amount);
 
-        let unbonding_duration = UNBONDING_DURATION.load(deps.as_mut().storage).unwrap();
+        // the staker stakes only one nft, so does not earn rewards.
+        set_min_tokens_to_earn(deps.as_mut(), info.clone(), env.clone(), config.clone(), 2).unwrap();
+        let res: MinTokensToEarnResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetMinTokensToEarn {}).unwrap()).unwrap();
+        assert_eq!(2, res.min_tokens_to_earn);
 
-        // current time is after sum of timestamp and unbonding duration + 1 
-        let timestamp = timestamp + unbonding_duration + 1;
+        let (claim, next_claim) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), 5, timestamp, start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(0, claim.amount);
+        assert_eq!(5, claim.periods);
+        assert_eq!(6, next_claim.period);
 
-        // re-request unstake the nft has "UNBONDED" as bond_status
-        test_unstake_function(deps.as_mut(), env.clone(), staker_info.clone(), config.clone(), token_id.clone(), claim_recipient_address.clone(), timestamp.clone()).unwrap();
+        // only the contract owner or granted address can set.
+        let staker_info = mock_info(staker.as_str(), &[]);
+        let res = set_min_tokens_to_earn(deps.as_mut(), staker_info, env.clone(), config.clone(), 1);
+        assert_eq!(ContractError::Unauthorized {}.to_string(), res.err().unwrap().to_string());
+    }
 
-        // --------------------------------
-        // check after run unstake function
-        let staker_rewards = query_balance(deps.as_ref(), staker).unwrap();
-        let contract_balance = query_balance(deps.as_ref(), env.contract.address.to_string()).unwrap();
+    #[test]
+    fn test_min_tokens_to_earn_by_period() {
+        // do stake
+        let (mut deps, info, env, _cw721_contract, cw721_contract_address, config, staker, token_id) = do_stake();
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
+        set_min_tokens_to_earn(deps.as_mut(), info.clone(), env.clone(), config.clone(), 2).unwrap();
 
-        // deposit cycle = 1.
-        // cycle length in seconds is 60 and period length in cycles is 3 for test.
-        // requested unbonding period is 55556.
-        // rewards per cycle is 17.
+        let staker_tokenid_key = staker_tokenid_key(staker.clone(), token_id.clone());
+        let start_timestamp = START_TIMESTAMP.load(deps.as_mut().storage).unwrap();
 
-        // claimable period is 55555 which is previous period of the requested unbonding period
-        // so, the equation of claimable rewards value = 55555 * 3 * 17 = 2,833,305
-        // nft staking contract's rewards pool (i.e. cw20 token balance of contract) is 2000000000 - 2833305 = 1997166695
-        assert_eq!(2833305, staker_rewards.balance.u128());
-        assert_eq!(1997166695, contract_balance.balance.u128());
+        // the staker stakes the second nft in period 3
+        let mut stake_env = env.clone();
+        stake_env.block.time = stake_env.block.time.plus_seconds(360);
+        let msg = Cw721ReceiveMsg {
+            sender: staker.clone(),
+            token_id: "token_id_other_0".to_string(),
+            msg: to_binary("send nft to stake").unwrap(),
+        };
+        stake_nft(deps.as_mut(), stake_env, mock_info(cw721_contract_address.as_str(), &[]), config.clone(), msg).unwrap();
+        assert_eq!(2, STAKER_NFT_COUNTS.load(deps.as_mut().storage, staker.clone()).unwrap());
+
+        // the topped up number does not unlock the periods before, and period 3 is not held for the full period.
+        // only periods 4 and 5 are earned, 2 * 3 * 17 = 102
+        let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), 10, env.block.time.seconds() + 900, start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(102, claim.amount);
+        assert_eq!(5, claim.periods);
+
+        // the staker drops below the threshold in period 6, but periods 4 and 5 which are already earned are kept
+        manage_number_nfts(deps.as_mut(), staker.clone(), cw721_contract_address.to_string(), false, env.block.time.seconds() + 900);
+        assert_eq!(1, STAKER_NFT_COUNTS.load(deps.as_mut().storage, staker.clone()).unwrap());
+        let (claim, _) = compute_rewards(deps.as_ref(), staker_tokenid_key.clone(), 10, env.block.time.seconds() + 1260, start_timestamp, config.clone(), token_id.clone()).unwrap();
+        assert_eq!(102, claim.amount);
+        assert_eq!(7, claim.periods);
     }
 
     // test helpers
-    fn test_environment()
-    ->  (
-            OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
-            MessageInfo,
-            Env,
-            Cw721Contract<'static, Extension, Empty, Empty, Empty>,
-            Addr,
-            Config,
-            String,
-            String,
-        ) {
+    // deps, owner info, env, cw721 contract, cw721 contract address, config, staker and token id.
+    type TestEnvironment = (
+        OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        MessageInfo,
+        Env,
+        Cw721Contract<'static, Extension, Empty, Empty, Empty>,
+        Addr,
+        Config,
+        String,
+        String,
+    );
+
+    fn test_environment() -> TestEnvironment {
         let minter = String::from(MINTER);
         let staker = String::from(STAKER);
         let token_id = String::from(TOKEN_ID);
@@ -420,7 +4432,7 @@ mod tests{
 
         // instantiate
         let instantiate_res = do_instantiate(deps.as_mut(), info.clone(), env.clone(), cw721_contract_address.clone().to_string(), cw20_contract_address.clone().to_string());
-        assert_eq!(instantiate_res.attributes.get(0).unwrap().value, "instantiate");
+        assert_eq!(instantiate_res.attributes.first().unwrap().value, "instantiate");
         assert_eq!(instantiate_res.attributes.get(1).unwrap().value, minter);
         assert_eq!(instantiate_res.attributes.get(2).unwrap().value, CYCLE_LENGTH_IN_SECONDS.to_string());
         assert_eq!(instantiate_res.attributes.get(3).unwrap().value, PERIOD_LENGTH_IN_CYCLES.to_string());
@@ -431,13 +4443,13 @@ mod tests{
 
         // set reward schedule includes rewards_per_cycle
         let add_rewards_schedule = add_rewards_for_periods(deps.as_mut(), env.clone(), info.clone(), REWARDS_PER_CYCLE, config.clone()).unwrap();
-        assert_eq!(add_rewards_schedule.attributes.get(0).unwrap().value, "add_rewards_for_periods");
+        assert_eq!(add_rewards_schedule.attributes.first().unwrap().value, "add_rewards_for_periods");
         assert_eq!(add_rewards_schedule.attributes.get(1).unwrap().value, REWARDS_PER_CYCLE.to_string());
 
         // minter sends cw20 tokens to contract for supplying rewards pool
         let msg = Cw20ExecuteMsg::Send {
             contract: env.contract.address.clone().to_string(),
-            amount: add_rewards.clone(),
+            amount: add_rewards,
             msg: send_msg.clone(),
         };
         let res = execute(deps.as_mut(), mock_env_cw20().clone(), info.clone(), msg.clone()).unwrap();
@@ -446,7 +4458,7 @@ mod tests{
         // received msg
         let msg = Cw20ReceiveMsg {
             sender: minter.clone(),
-            amount: add_rewards.clone(),
+            amount: add_rewards,
             msg: send_msg.clone()
         };
 
@@ -459,18 +4471,19 @@ mod tests{
         assert_eq!(res.messages[0].msg, cm_msg);
 
         let cw20_info = mock_info(cw20_contract_address.as_str(), &[]);
+        mock_querier_contracts(&mut deps, ADD_REWARDS_POOL, vec![]);
         let add_rewards_pool = add_rewards_pool(deps.as_mut(), cw20_info.clone(), env.clone(), config.clone(), msg).unwrap();
 
         // check balance as token rewards pool of nft staking contract
         let balance_response = test_query_rewards_token_balance(deps.as_mut(), env.clone().contract.address.to_string());
         assert_eq!(balance_response.balance, add_rewards);
-        assert_eq!(add_rewards_pool.attributes.get(0).unwrap().value, "add_rewards_pool");        
+        assert_eq!(add_rewards_pool.attributes.first().unwrap().value, "add_rewards_pool");        
         assert_eq!(add_rewards_pool.attributes.get(1).unwrap().value, ADD_REWARDS_POOL.to_string());
         assert_eq!(add_rewards_pool.attributes.get(2).unwrap().value, ADD_REWARDS_POOL.to_string());
 
         contract_test_start(deps.as_mut(), info.clone(), env.clone(), config.clone());
 
-        return (deps, info, env, cw721_contract, cw721_contract_address, config, staker, token_id)
+        (deps, info, env, cw721_contract, cw721_contract_address, config, staker, token_id)
     }
 
     fn do_instantiate(
@@ -484,9 +4497,12 @@ mod tests{
             cycle_length_in_seconds: CYCLE_LENGTH_IN_SECONDS,
             period_length_in_cycles: PERIOD_LENGTH_IN_CYCLES,
             white_listed_nft_contract,
-            rewards_token_contract
+            rewards_token_contract,
+            validate_contracts: None,
+            min_cycle_length_override: None,
+            owner: None,
         };
-        return nft_staking_instantiate(deps, env, info, msg).unwrap();        
+        nft_staking_instantiate(deps, env, info, msg).unwrap()
     }    
 
     fn get_config(
@@ -503,7 +4519,7 @@ mod tests{
         env: Env,
         config: Config,
     ) {
-        start(deps, info, env, config).unwrap();
+        start(deps, info, env, config, None).unwrap();
     }
 
     fn setup_contract_cw721(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty, Empty, Empty> {
@@ -584,16 +4600,100 @@ mod tests{
         balance_response
     }
 
-    fn do_stake() -> (
-        OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
-        MessageInfo,
-        Env,
-        Cw721Contract<'static, Extension, Empty, Empty, Empty>,
-        Addr,
-        Config,
-        String,
-        String,
+    // the querier of mock dependencies responds cw20 balance and cw721 owner of queries instead of cw20 and cw721 contracts.
+    // token ids which are not included in existing_token_ids are handled as burned.
+    fn mock_querier_contracts(
+        deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        rewards_pool_balance: u128,
+        existing_token_ids: Vec<String>,
     ) {
+        let cw20_contract_address = mock_env_cw20().contract.address.to_string();
+        let cw721_contract_address = mock_env_cw721().contract.address.to_string();
+
+        deps.querier.update_wasm(move |query| {
+            match query {
+                WasmQuery::Smart { contract_addr, msg } if *contract_addr == cw20_contract_address => {
+                    match from_binary(msg).unwrap() {
+                        Cw20QueryMsg::Balance { address: _ } => {
+                            let res = BalanceResponse { balance: Uint128::from(rewards_pool_balance) };
+                            SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                        },
+                        _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw20".to_string() }),
+                    }
+                },
+                WasmQuery::Smart { contract_addr, msg } if *contract_addr == cw721_contract_address => {
+                    match from_binary(msg).unwrap() {
+                        Cw721QueryMsg::OwnerOf { token_id, include_expired: _ } => {
+                            if existing_token_ids.contains(&token_id) {
+                                let res = OwnerOfResponse { owner: MOCK_CONTRACT_ADDR.to_string(), approvals: vec![] };
+                                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                            } else {
+                                SystemResult::Ok(ContractResult::Err(CW721_TOKEN_NOT_FOUND.to_string()))
+                            }
+                        },
+                        _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw721".to_string() }),
+                    }
+                },
+                WasmQuery::Smart { contract_addr, msg: _ } => SystemResult::Err(SystemError::NoSuchContract { addr: contract_addr.clone() }),
+                _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+            }
+        });
+    }
+
+    // expected cw20 transfer message of rewards.
+    fn cw20_transfer_msg(
+        recipient: String,
+        amount: u128,
+    ) -> CosmosMsg {
+        CosmosMsg::Wasm(WasmMsg::Execute { 
+            contract_addr: mock_env_cw20().contract.address.to_string(), 
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { 
+                recipient, 
+                amount: Uint128::from(amount), 
+            }).unwrap(), 
+            funds: vec![] 
+        })
+    }
+
+    // read only storage which counts reads of the key.
+    struct ReadCountingStorage<'a> {
+        storage: &'a dyn Storage,
+        key: Vec<u8>,
+        reads: RefCell<u64>,
+    }
+
+    impl<'a> ReadCountingStorage<'a> {
+        fn new(storage: &'a dyn Storage, key: &[u8]) -> Self {
+            ReadCountingStorage { storage, key: key.to_vec(), reads: RefCell::new(0) }
+        }
+
+        fn reads(&self) -> u64 {
+            *self.reads.borrow()
+        }
+    }
+
+    impl<'a> Storage for ReadCountingStorage<'a> {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            if key == self.key.as_slice() {
+                *self.reads.borrow_mut() += 1;
+            }
+            self.storage.get(key)
+        }
+
+        fn range<'b>(&'b self, start: Option<&[u8]>, end: Option<&[u8]>, order: Order) -> Box<dyn Iterator<Item = Record> + 'b> {
+            self.storage.range(start, end, order)
+        }
+
+        fn set(&mut self, _key: &[u8], _value: &[u8]) {
+            unimplemented!("read only storage")
+        }
+
+        fn remove(&mut self, _key: &[u8]) {
+            unimplemented!("read only storage")
+        }
+    }
+
+    fn do_stake() -> TestEnvironment {
         // test environment
         let (mut deps, info, env, cw721_contract, cw721_contract_address, config, staker, token_id) = test_environment();
         let staker_info = mock_info(staker.as_str(), &[]);
@@ -622,7 +4722,7 @@ mod tests{
         let cw721_info = mock_info(cw721_contract_address.as_str(), &[]);
         stake_function(deps.as_mut(), cw721_info, env.clone(), timestamp, config.clone(), payload);
 
-        return (deps, info, env, cw721_contract, cw721_contract_address, config, staker, token_id)
+        (deps, info, env, cw721_contract, cw721_contract_address, config, staker, token_id)
     }
 
     fn stake_function(
@@ -644,7 +4744,7 @@ mod tests{
 
         // check rewards schedule
         let rewards_schedule = REWARDS_SCHEDULE.may_load(deps.branch().storage).unwrap();
-        assert!(!rewards_schedule.is_none());
+        assert!(rewards_schedule.is_some());
 
         // whitelisted nft contract only send nft
         assert_eq!(info.sender.to_string(), config.clone().white_listed_nft_contract);        
@@ -669,11 +4769,11 @@ mod tests{
         assert_eq!(update_histories_response.staker, staker_tokenid_key);
 
         let token_infos = TOKEN_INFOS.may_load(deps.branch().storage, token_id.clone()).unwrap();
-        if !token_infos.is_none() {
+        if let Some(token_infos) = token_infos {
             // prevent duplication.
-            assert!(!token_infos.clone().unwrap().is_staked);
+            assert!(!token_infos.is_staked);
             
-            let withdraw_cycle = token_infos.unwrap().withdraw_cycle;
+            let withdraw_cycle = token_infos.withdraw_cycle;
             // cannot re-stake when current cycle of block time is same setup withdraw cycle
             assert_ne!(current_cycle, withdraw_cycle)
         }
@@ -686,13 +4786,13 @@ mod tests{
             NEXT_CLAIMS.save(deps.branch().storage, staker_tokenid_key.clone(), &new_next_claim).unwrap();            
         }
 
-        let new_token_info = TokenInfo::stake(staker.clone(), IS_STAKED, current_cycle);
+        let new_token_info = TokenInfo::stake(staker.clone(), IS_STAKED, current_cycle, info.sender.to_string());
         assert_eq!(new_token_info.owner, STAKER.to_string());
         assert!(new_token_info.is_staked);
         assert_eq!(new_token_info.bond_status, BONDED);
         
-        TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &new_token_info).unwrap();        
-        manage_number_nfts(deps.branch(), true);
+        save_token_info(deps.branch().storage, token_id.clone(), &new_token_info).unwrap();        
+        manage_number_nfts(deps.branch(), staker.clone(), info.sender.to_string(), true, env.block.time.seconds());
     }
 
     pub fn test_unstake_function(
@@ -716,6 +4816,7 @@ mod tests{
         // the timestamp is temp value which is input of function
         // let timestamp = env.block.time.seconds();
         let is_staked = token_info.clone().is_staked;
+        let nft_contract = token_info.staked_nft_contract(config.clone());
     
         // the bond status of requested nft that is "BONDED" is replaced to "UNBONDING".
         if token_info.bond_status == BONDED {
@@ -724,9 +4825,10 @@ mod tests{
                 is_staked, 
                 token_info.clone().deposit_cycle, 
                 token_info.clone().withdraw_cycle,
-                timestamp.clone(),
+                timestamp,
+                nft_contract.clone(),
             );
-            TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &token_info_unbonding)?;
+            save_token_info(deps.branch().storage, token_id.clone(), &token_info_unbonding)?;
     
             // check token id's bond status
             let check_token_info = TOKEN_INFOS.load(deps.branch().storage, token_id.clone())?;
@@ -739,7 +4841,7 @@ mod tests{
             )
         }
 
-        check_unbonding_end(deps.as_ref(), token_info.clone(), timestamp.clone())?; 
+        check_unbonding_end(deps.as_ref(), token_info.clone(), timestamp)?; 
 
         let current_cycle = get_cycle(timestamp, start_timestamp, config.clone())?;
         let disable = check_disable(deps.branch())?;
@@ -748,7 +4850,7 @@ mod tests{
         let mut remain_rewards = true;
         let mut remain_rewards_value: u128 = 0;
         let mut recipient: Option<String> = Some(staker.clone());
-        if !claim_recipient_address.is_none() {
+        if claim_recipient_address.is_some() {
             recipient = claim_recipient_address;
         }
 
@@ -761,8 +4863,9 @@ mod tests{
                 token_info.clone().deposit_cycle, 
                 token_info.clone().withdraw_cycle,
                 token_info.clone().req_unbond_time,
+                nft_contract.clone(),
             );
-            TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &token_info_unbonded)?;
+            save_token_info(deps.branch().storage, token_id.clone(), &token_info_unbonded)?;
 
             while remain_rewards {
                 let compute_reward = compute_rewards(
@@ -776,7 +4879,7 @@ mod tests{
                 ).unwrap();
 
                 if compute_reward.0.amount != 0 {
-                    remain_rewards_value = remain_rewards_value + compute_reward.0.amount;
+                    remain_rewards_value += compute_reward.0.amount;
                     // next claim set last computed rewards.
                     NEXT_CLAIMS.save(deps.branch().storage, staker_tokenid_key.clone(), &compute_reward.1)?;
                 } else {
@@ -785,9 +4888,9 @@ mod tests{
             }
             update_histories(deps.branch(), staker_tokenid_key.clone(), !is_staked, current_cycle)?;
 
-            let token_info = TokenInfo::unstake(!is_staked, token_info.clone().deposit_cycle, current_cycle);
+            let token_info = TokenInfo::unstake(!is_staked, token_info.clone().deposit_cycle, current_cycle, nft_contract.clone());
 
-            TOKEN_INFOS.save(deps.branch().storage, token_id.clone(), &token_info)?;
+            save_token_info(deps.branch().storage, token_id.clone(), &token_info)?;
         }
 
         if remain_rewards_value != 0 {
@@ -798,7 +4901,7 @@ mod tests{
         }
 
         NEXT_CLAIMS.remove(deps.branch().storage, staker_tokenid_key.clone());
-        manage_number_nfts(deps.branch(), false);
+        manage_number_nfts(deps.branch(), staker.clone(), nft_contract, false, env.block.time.seconds());
 
         Ok(Response::new()
             .add_attribute("method", "unstake_nft")
@@ -818,11 +4921,10 @@ mod tests{
         let nft_staking_contract_info = mock_info(env.contract.address.as_str(), &[]);
 
         let msg = Cw20ExecuteMsg::Transfer { 
-            recipient: recipient, 
+            recipient, 
             amount: Uint128::from(amount)
         };
-        let res = execute(deps, mock_env_cw20().clone(), nft_staking_contract_info.clone(), msg.clone()).unwrap();
-        return res
+        execute(deps, mock_env_cw20().clone(), nft_staking_contract_info.clone(), msg.clone()).unwrap()
     }
 
     fn test_execute_transfer_nft_unstake(
@@ -834,10 +4936,10 @@ mod tests{
     ) -> Response {
         let nft_staking_contract_info = mock_info(env.contract.address.as_str(), &[]);
 
-        let res = cw721_contract.transfer_nft(deps, mock_env_cw721(), nft_staking_contract_info, recipient, token_id).unwrap();
-        return res
+        cw721_contract.transfer_nft(deps, mock_env_cw721(), nft_staking_contract_info, recipient, token_id).unwrap()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn claim_rewards_function(
         mut deps: DepsMut,
         info: MessageInfo,
@@ -860,7 +4962,7 @@ mod tests{
         }
 
         let next_claim = NEXT_CLAIMS.may_load(deps.storage, staker_tokenid_key.clone()).unwrap();
-        assert!(!next_claim.is_none());
+        assert!(next_claim.is_some());
 
         let next_claim = next_claim.unwrap();
         let now = timestamp;
@@ -890,21 +4992,21 @@ mod tests{
         }
 
         let staker_history = STAKER_HISTORIES.may_load(deps.storage, staker_tokenid_key.clone()).unwrap();
-        assert!(!staker_history.is_none());
+        assert!(staker_history.is_some());
 
         let mut staker_history = staker_history.unwrap();
-        while next_claim.staker_snapshot_index < new_next_claim.staker_snapshot_index {
-            let delete_index = next_claim.staker_snapshot_index + 1;
-            staker_history.remove(delete_index as usize);
+        // snapshots before the snapshot of the new next claim are never computed again.
+        if next_claim.staker_snapshot_index < new_next_claim.staker_snapshot_index {
+            staker_history.drain(..new_next_claim.staker_snapshot_index as usize);
             STAKER_HISTORIES.save(deps.storage, staker_tokenid_key.clone(), &staker_history).unwrap();
         }
 
         assert_ne!(claim.periods, 0);
         assert_ne!(next_claim.period, 0);
 
-        let last_staker_snapshot = staker_history[(staker_history.len() - 1) as usize];
+        let last_staker_snapshot = staker_history[staker_history.len() - 1];
         let last_claimed_cycle = (claim.start_period + claim.periods - 1) * config.period_length_in_cycles;
-        if last_claimed_cycle >= last_staker_snapshot.start_cycle && last_staker_snapshot.is_staked == false {
+        if last_claimed_cycle >= last_staker_snapshot.start_cycle && !last_staker_snapshot.is_staked {
             NEXT_CLAIMS.remove(deps.storage, staker_tokenid_key.clone());
         } else {
             NEXT_CLAIMS.save(deps.storage, staker_tokenid_key.clone(), &new_next_claim).unwrap();
@@ -912,10 +5014,7 @@ mod tests{
 
         assert_ne!(claim.amount, 0);
 
-        let mut recipient = staker;
-        if !claim_recipient_address.is_none() {
-            recipient = claim_recipient_address.unwrap();
-        }
+        let recipient = claim_recipient_address.unwrap_or(staker);
 
         Ok(test_execute_token_contract_transfer(deps.branch(), env.clone(), info.clone(), recipient, claim.amount))
     }